@@ -1,7 +1,13 @@
 #[allow(unused)]
 
 pub const USER_STACK_SIZE: usize = 4096 * 2;
+/// Apps that need a bigger user stack than `USER_STACK_SIZE`, by app name.
+pub const APP_STACK_SIZES: &[(&str, usize)] =
+    &[("stack_deep", 4096 * 16), ("stack_growth", 4096 * 16)];
 pub const KERNEL_STACK_SIZE: usize = 4096 * 2;
+/// Bytes at the bottom of each kernel stack filled with a canary. Finding it
+/// changed on trap entry or exit means the stack came close to overflowing.
+pub const KERNEL_STACK_RED_ZONE: usize = 1024;
 pub const KERNEL_HEAP_SIZE: usize = 0x100_0000;
 pub const MEMORY_END: usize = 0x88000000;
 pub const PAGE_SIZE: usize = 0x1000;
@@ -9,5 +15,95 @@ pub const PAGE_SIZE_BITS: usize = 0xc;
 
 pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
 pub const TRAP_CONTEXT_BASE: usize = TRAMPOLINE - PAGE_SIZE;
+/// `mmap` with `start == 0` picks a free range in `[MMAP_BASE, MMAP_TOP)`,
+/// fixed mappings, `munmap`, `mprotect` and `madvise` must stay inside it.
+pub const MMAP_BASE: usize = 0x10_0000_0000;
+pub const MMAP_TOP: usize = 0x20_0000_0000;
+
+/// Frames that user memory (`mmap`, lazy page faults, `fork`, app images,
+/// user stacks and trap contexts) may not take, so the kernel can still
+/// allocate kernel stacks and page tables.
+pub const RESERVED_FRAMES: usize = 64;
+
+/// Physical frame allocator: `Stack` reuses the most recently freed frame,
+/// `Bitmap` keeps one bit per frame and hands out the lowest free one.
+pub const FRAME_ALLOCATOR_KIND: FrameAllocatorKind = FrameAllocatorKind::Stack;
+
+/// Faults in one of the last `UNMAPPED_HISTORY` ranges `munmap`ed within
+/// `UNMAPPED_HISTORY_MS` are reported as use after unmap.
+pub const UNMAPPED_HISTORY: usize = 8;
+pub const UNMAPPED_HISTORY_MS: usize = 1000;
+
+/// Slots for environment variables per process, and the maximum length of a
+/// key or value.
+pub const ENV_MAX_VARS: usize = 16;
+pub const ENV_MAX_LEN: usize = 64;
+
+/// On a user fault in the first page, which is never mapped, panic instead
+/// of killing the task, to stop at the first null pointer dereference.
+pub const NULL_FAULT_PANICS: bool = false;
+
+/// What to do after a kernel panic is reported: power off, reboot, or open
+/// a debug prompt on the console, see `PanicAction`.
+pub const PANIC_ACTION: PanicAction = PanicAction::Halt;
+
+/// Run the kernel self-tests in `selftest.rs` before launching apps.
+pub const BOOT_SELF_TEST: bool = false;
+
+/// Never preempt on timer interrupts, so tasks only switch on yield, block or
+/// exit and interleavings are reproducible. Timers (sleep, alarms) still fire.
+pub const DETERMINISTIC_SCHED: bool = false;
+
+/// Longest a task may keep preemption off with `sys_preempt_disable`, counted
+/// from the first call since it got the CPU, however often it turns it back
+/// on in between. The first timer interrupt after that turns it back on.
+pub const PREEMPT_DISABLE_MAX_MS: usize = 100;
+
+/// Scheduler the kernel boots with, see `SchedulerKind`. `sys_set_scheduler`
+/// can switch it at runtime.
+pub const SCHEDULER: SchedulerKind = SchedulerKind::RoundRobin;
+/// Tuning of `SchedulerKind::MemoryAware`.
+pub const MEMORY_PRESSURE_FRAMES: usize = 1024;
+pub const MEMORY_PRESSURE_MAX_SKIPS: usize = 8;
+/// Tuning of `SchedulerKind::Fair`: a task's recent CPU time halves every
+/// `VRUNTIME_HALF_LIFE_MS`, so usage long ago stops counting against it.
+pub const VRUNTIME_HALF_LIFE_MS: usize = 100;
+
+/// App started as the first process. Orphans are handed to it, so it should
+/// reap children like `initproc` does. If it is missing, the first app in the
+/// root directory is started instead.
+pub const INIT_APP_NAME: &str = "initproc";
+
+/// `sys_profile_start` groups sampled PCs into buckets of
+/// `PROFILE_BUCKET_BYTES` (a power of two) and keeps at most
+/// `PROFILE_MAX_BUCKETS` of them per task.
+pub const PROFILE_BUCKET_BYTES: usize = 16;
+pub const PROFILE_MAX_BUCKETS: usize = 256;
+/// While any of its threads is profiling, the working set of a process is
+/// the number of its user pages accessed in the last `WORKING_SET_WINDOW_MS`.
+/// Each sample walks the whole user page table from the timer handler,
+/// clearing accessed bits, then flushes the TLB: the tick costs time linear
+/// in the mapped pages, and every page is walked again on its next access.
+pub const WORKING_SET_WINDOW_MS: usize = 50;
+
+/// Largest copy `sys_membench` times. It allocates twice this from the
+/// kernel heap.
+pub const MEMBENCH_MAX_SIZE: usize = 0x10_0000;
+
+/// Copies `sys_membench` times, reporting the fastest.
+pub const MEMBENCH_RUNS: usize = 3;
+
+/// Clock ticks per second reported by `sys_times`.
+pub const CLOCKS_PER_SEC: usize = 100;
+
+/// Nesting depth of kernel traps at which the kernel panics instead of
+/// recursing until the kernel stack overflows.
+pub const MAX_KERNEL_TRAP_DEPTH: usize = 4;
+
+/// Shut down the machine when no task is ready, instead of idling with `wfi`.
+pub const SHUTDOWN_WHEN_IDLE: bool = false;
 
 pub use crate::board::{CLOCK_FREQ, MMIO};
+use crate::lang_items::PanicAction;
+use crate::mm::FrameAllocatorKind;
+use crate::task::SchedulerKind;
@@ -12,3 +12,45 @@ pub use chardev::UART;
 pub use gpu::*;
 pub use input::*;
 pub use net::*;
+
+use crate::sync::UPIntrFreeCell;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use lazy_static::*;
+use log::debug;
+
+/// A subsystem that may hold writes back, pushed out by `sys_sync`.
+pub trait Flushable: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn flush(&self);
+}
+
+/// Placeholder until a subsystem actually buffers writes.
+struct NoopFlush;
+
+impl Flushable for NoopFlush {
+    fn name(&self) -> &'static str {
+        "noop"
+    }
+    fn flush(&self) {}
+}
+
+lazy_static! {
+    static ref FLUSHABLES: UPIntrFreeCell<Vec<Arc<dyn Flushable>>> =
+        unsafe { UPIntrFreeCell::new(vec![Arc::new(NoopFlush) as Arc<dyn Flushable>]) };
+}
+
+#[allow(unused)]
+pub fn register_flushable(flushable: Arc<dyn Flushable>) {
+    FLUSHABLES.exclusive_access().push(flushable);
+}
+
+/// Flush every registered subsystem in registration order.
+pub fn flush_all() {
+    let flushables = FLUSHABLES.exclusive_session(|flushables| flushables.clone());
+    for flushable in flushables {
+        flushable.flush();
+        debug!("[kernel] sync: flushed {}", flushable.name());
+    }
+}
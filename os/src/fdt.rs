@@ -0,0 +1,84 @@
+//! Just enough of a flattened device tree reader to look up a property.
+
+use crate::config::MEMORY_END;
+
+const FDT_MAGIC: u32 = 0xd00d_feed;
+const FDT_BEGIN_NODE: u32 = 1;
+const FDT_END_NODE: u32 = 2;
+const FDT_PROP: u32 = 3;
+const FDT_NOP: u32 = 4;
+
+fn be32(blob: &[u8], offset: usize) -> Option<u32> {
+    let bytes = blob.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// The device tree blob at physical address `pa`, as passed in `a1` by the
+/// SBI, if there is a valid one after the kernel image and within RAM.
+///
+/// # Safety
+///
+/// Physical memory must be directly accessible and the blob must not have
+/// been overwritten yet, i.e. this runs before the frame allocator is set up.
+pub unsafe fn blob_at(pa: usize) -> Option<&'static [u8]> {
+    extern "C" {
+        fn ekernel();
+    }
+    if pa < ekernel as usize || pa % 8 != 0 || pa.checked_add(8)? > MEMORY_END {
+        return None;
+    }
+    let header = core::slice::from_raw_parts(pa as *const u8, 8);
+    let size = be32(header, 4)? as usize;
+    if be32(header, 0)? != FDT_MAGIC || pa.checked_add(size)? > MEMORY_END {
+        return None;
+    }
+    Some(core::slice::from_raw_parts(pa as *const u8, size))
+}
+
+/// Value of the first property called `name` in `blob`, in any node.
+/// `None` if there is none or the blob is malformed.
+pub fn property<'a>(blob: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    if be32(blob, 0)? != FDT_MAGIC {
+        return None;
+    }
+    let strings = be32(blob, 12)? as usize;
+    let mut offset = be32(blob, 8)? as usize;
+    loop {
+        let token = be32(blob, offset)?;
+        offset += 4;
+        match token {
+            FDT_BEGIN_NODE => {
+                let name_len = blob.get(offset..)?.iter().position(|&b| b == 0)?;
+                offset = align4(offset + name_len + 1);
+            }
+            FDT_PROP => {
+                let len = be32(blob, offset)? as usize;
+                let name_offset = be32(blob, offset + 4)? as usize;
+                offset += 8;
+                let value = blob.get(offset..offset.checked_add(len)?)?;
+                let prop_name = blob.get(strings.checked_add(name_offset)?..)?;
+                let name_len = prop_name.iter().position(|&b| b == 0)?;
+                if &prop_name[..name_len] == name.as_bytes() {
+                    return Some(value);
+                }
+                offset = align4(offset + len);
+            }
+            FDT_END_NODE | FDT_NOP => {}
+            // FDT_END, or garbage
+            _ => return None,
+        }
+    }
+}
+
+/// A property holding a single 32 or 64 bit cell.
+pub fn cell_value(value: &[u8]) -> Option<usize> {
+    match value.len() {
+        4 => Some(u32::from_be_bytes(value.try_into().ok()?) as usize),
+        8 => Some(u64::from_be_bytes(value.try_into().ok()?) as usize),
+        _ => None,
+    }
+}
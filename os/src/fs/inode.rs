@@ -2,6 +2,7 @@ use super::File;
 use crate::drivers::BLOCK_DEVICE;
 use crate::mm::UserBuffer;
 use crate::sync::UPIntrFreeCell;
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use bitflags::*;
@@ -41,6 +42,10 @@ impl OSInode {
         }
         v
     }
+    /// Read at `offset` without moving the file offset.
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        self.inner.exclusive_access().inode.read_at(offset, buf)
+    }
 }
 
 lazy_static! {
@@ -50,9 +55,28 @@ lazy_static! {
     };
 }
 
+/// Names of the files in the root directory that are ELF images, leaving out
+/// data files that apps have created.
+pub fn app_names() -> Vec<String> {
+    ROOT_INODE
+        .ls()
+        .into_iter()
+        .filter(|name| {
+            let mut magic = [0u8; 4];
+            let inode = ROOT_INODE.find(name).unwrap();
+            inode.read_at(0, &mut magic) == magic.len() && magic == *b"\x7fELF"
+        })
+        .collect()
+}
+
+/// Position of app `name` in `app_names`, which is its id.
+pub fn app_id(name: &str) -> Option<usize> {
+    app_names().iter().position(|app| app == name)
+}
+
 pub fn list_apps() {
     println!("/**** APPS ****");
-    for app in ROOT_INODE.ls() {
+    for app in app_names() {
         println!("{}", app);
     }
     println!("**************/")
@@ -1,4 +1,5 @@
 mod inode;
+mod null;
 mod pipe;
 mod stdio;
 
@@ -11,6 +12,7 @@ pub trait File: Send + Sync {
     fn write(&self, buf: UserBuffer) -> usize;
 }
 
-pub use inode::{list_apps, open_file, OSInode, OpenFlags, ROOT_INODE};
+pub use inode::{app_id, app_names, list_apps, open_file, OSInode, OpenFlags, ROOT_INODE};
+pub use null::{Null, NULL_DEVICE};
 pub use pipe::{make_pipe, Pipe};
-pub use stdio::{Stdin, Stdout};
+pub use stdio::{flush_stdout, reset_stdout_log, stdout_log, Stdin, Stdout};
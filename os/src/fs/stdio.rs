@@ -1,7 +1,26 @@
 use super::File;
 use crate::drivers::chardev::CharDevice;
 use crate::drivers::chardev::UART;
+use crate::logging::LogRing;
 use crate::mm::UserBuffer;
+use crate::sync::UPIntrFreeCell;
+use crate::task::{current_task, TaskControlBlock};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use lazy_static::*;
+
+const LF: u8 = 0x0a;
+const CR: u8 = 0x0d;
+/// Longest partial line held back in line-buffered mode before it is
+/// written out anyway.
+const LINE_BUFFER_MAX: usize = 1024;
+
+lazy_static! {
+    /// What apps wrote to the console through `Stdout`, as it came out, for
+    /// tests that check how output from several tasks was ordered.
+    static ref STDOUT_LOG: UPIntrFreeCell<LogRing> = unsafe { UPIntrFreeCell::new(LogRing::new()) };
+}
 
 pub struct Stdin;
 pub struct Stdout;
@@ -13,14 +32,21 @@ impl File for Stdin {
     fn writable(&self) -> bool {
         false
     }
-    fn read(&self, mut user_buf: UserBuffer) -> usize {
-        assert_eq!(user_buf.len(), 1);
-        //println!("before UART.read() in Stdin::read()");
-        let ch = UART.read();
-        unsafe {
-            user_buf.buffers[0].as_mut_ptr().write_volatile(ch);
+    /// Fill `user_buf` until it is full or a line ends, sleeping while no
+    /// character is available. The line terminator is included in the count.
+    fn read(&self, user_buf: UserBuffer) -> usize {
+        let mut count = 0;
+        for byte_ref in user_buf.into_iter() {
+            let ch = UART.read();
+            unsafe {
+                byte_ref.write_volatile(ch);
+            }
+            count += 1;
+            if ch == LF || ch == CR {
+                break;
+            }
         }
-        1
+        count
     }
     fn write(&self, _user_buf: UserBuffer) -> usize {
         panic!("Cannot write to stdin!");
@@ -37,10 +63,80 @@ impl File for Stdout {
     fn read(&self, _user_buf: UserBuffer) -> usize {
         panic!("Cannot read from stdout!");
     }
+    /// In line-buffered mode only complete lines are printed, so a line
+    /// written in pieces comes out in one go.
     fn write(&self, user_buf: UserBuffer) -> usize {
-        for buffer in user_buf.buffers.iter() {
-            print!("{}", core::str::from_utf8(*buffer).unwrap());
+        let task = current_task().unwrap();
+        let lines = task.inner.exclusive_session(|task_inner| {
+            if !task_inner.stdout_line_buffered {
+                return None;
+            }
+            let pending = &mut task_inner.stdout_buffer;
+            for buffer in user_buf.buffers.iter() {
+                pending.extend_from_slice(buffer);
+            }
+            let end = match pending.iter().rposition(|&b| b == LF) {
+                Some(pos) => pos + 1,
+                None if pending.len() >= LINE_BUFFER_MAX => utf8_prefix_len(pending),
+                None => 0,
+            };
+            Some(pending.drain(..end).collect::<Vec<u8>>())
+        });
+        match lines {
+            Some(lines) => print_lines(&task, [lines.as_slice()]),
+            None => print_lines(&task, user_buf.buffers.iter().map(|b| &**b)),
         }
         user_buf.len()
     }
 }
+
+/// Length of the longest prefix of `bytes` that does not end inside a UTF-8
+/// sequence.
+fn utf8_prefix_len(bytes: &[u8]) -> usize {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => s.len(),
+        Err(e) => e.valid_up_to(),
+    }
+}
+
+/// Print `buffers` as output of `task`, starting lines with its prefix.
+fn print_lines<'a>(task: &TaskControlBlock, buffers: impl IntoIterator<Item = &'a [u8]>) {
+    let (prefix, mut at_line_start) = task.inner.exclusive_session(|task_inner| {
+        (
+            task_inner.stdout_prefix.clone(),
+            task_inner.stdout_at_line_start,
+        )
+    });
+    for buffer in buffers {
+        for line in core::str::from_utf8(buffer).unwrap().split_inclusive('\n') {
+            let mut log = STDOUT_LOG.exclusive_access();
+            if let (Some(prefix), true) = (&prefix, at_line_start) {
+                print!("[{}] ", prefix);
+                write!(log, "[{}] ", prefix).unwrap();
+            }
+            print!("{}", line);
+            log.write_str(line).unwrap();
+            at_line_start = line.ends_with('\n');
+        }
+    }
+    task.inner_exclusive_access().stdout_at_line_start = at_line_start;
+}
+
+/// Print whatever line-buffered output `task` still holds back.
+pub fn flush_stdout(task: &Arc<TaskControlBlock>) {
+    let pending = core::mem::take(&mut task.inner_exclusive_access().stdout_buffer);
+    if !pending.is_empty() {
+        print_lines(task, [pending.as_slice()]);
+    }
+}
+
+/// Up to the last `max` bytes apps wrote to the console, starting at a line
+/// boundary.
+pub fn stdout_log(max: usize) -> Vec<u8> {
+    STDOUT_LOG.exclusive_access().tail(max)
+}
+
+/// Drop everything in `STDOUT_LOG`.
+pub fn reset_stdout_log() {
+    STDOUT_LOG.exclusive_access().clear();
+}
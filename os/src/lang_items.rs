@@ -1,11 +1,129 @@
-use crate::sbi::shutdown;
-use crate::task::current_kstack_top;
+use crate::config::PANIC_ACTION;
+use crate::mm::{frames_free, frames_used};
+use crate::sbi::{console_getchar, reboot, shutdown};
+use crate::task::{current_kstack_top, switch_count, tasks_created};
+use crate::timer::get_time_ms;
 use core::arch::asm;
 use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
 use log::*;
 
+/// What the kernel does after reporting a panic, see `PANIC_ACTION`.
+#[allow(unused)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PanicAction {
+    /// power off, reporting failure to the host
+    Halt,
+    /// reset the machine to a cold boot
+    Reboot,
+    /// read `DebugCommand`s from the console until `continue`, then halt
+    Shell,
+}
+
+/// Commands of the debug prompt opened by `PanicAction::Shell`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DebugCommand {
+    /// print the registers captured on entry to the panic handler
+    Regs,
+    /// print uptime, switch and task counters and frame usage
+    Metrics,
+    /// leave the prompt and halt
+    Continue,
+}
+
+impl DebugCommand {
+    pub fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "regs" => Some(Self::Regs),
+            "metrics" => Some(Self::Metrics),
+            "continue" | "c" => Some(Self::Continue),
+            _ => None,
+        }
+    }
+}
+
+/// Set on the first panic, so that a panic while handling it, for example
+/// in a debug command, halts right away.
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// Registers captured on entry to the panic handler, shown by
+/// `DebugCommand::Regs`.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct PanicRegs {
+    pub sp: usize,
+    pub ra: usize,
+    pub s0: usize,
+    pub sstatus: usize,
+    pub sepc: usize,
+    pub scause: usize,
+    pub stval: usize,
+    pub satp: usize,
+}
+
+impl PanicRegs {
+    /// Read the registers of the caller. Inlined, so that `ra` is still the
+    /// caller's return address.
+    #[inline(always)]
+    pub fn capture() -> Self {
+        let mut regs = Self::default();
+        unsafe {
+            asm!("mv {}, sp", out(reg) regs.sp);
+            asm!("mv {}, ra", out(reg) regs.ra);
+            asm!("mv {}, s0", out(reg) regs.s0);
+            asm!("csrr {}, sstatus", out(reg) regs.sstatus);
+            asm!("csrr {}, sepc", out(reg) regs.sepc);
+            asm!("csrr {}, scause", out(reg) regs.scause);
+            asm!("csrr {}, stval", out(reg) regs.stval);
+            asm!("csrr {}, satp", out(reg) regs.satp);
+        }
+        regs
+    }
+}
+
+/// What a `PanicAction` acts on: the machine through the SBI in the panic
+/// handler, or a recorder in the self-tests.
+pub trait PanicTarget {
+    /// power off, reporting failure
+    fn halt(&mut self);
+    fn reboot(&mut self);
+    /// a byte typed on the console, `None` if there is none yet
+    fn getchar(&mut self) -> Option<u8>;
+}
+
+struct Sbi;
+
+impl PanicTarget for Sbi {
+    fn halt(&mut self) {
+        shutdown(true)
+    }
+    fn reboot(&mut self) {
+        reboot()
+    }
+    fn getchar(&mut self) -> Option<u8> {
+        console_getchar()
+    }
+}
+
+/// Carry out `action` on `target`, with `regs` for the debug prompt. Only
+/// returns if `target` does.
+pub fn perform(action: PanicAction, regs: &PanicRegs, target: &mut impl PanicTarget) {
+    match action {
+        PanicAction::Halt => target.halt(),
+        PanicAction::Reboot => target.reboot(),
+        PanicAction::Shell => {
+            debug_prompt(regs, target);
+            target.halt()
+        }
+    }
+}
+
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
+    let regs = PanicRegs::capture();
+    if PANICKING.swap(true, Ordering::SeqCst) {
+        error!("[kernel] Panicked again: {}", info.message().unwrap());
+        shutdown(true)
+    }
     if let Some(location) = info.location() {
         error!(
             "[kernel] Panicked at {}:{} {}",
@@ -19,6 +137,7 @@ fn panic(info: &PanicInfo) -> ! {
     unsafe {
         backtrace();
     }
+    perform(PANIC_ACTION, &regs, &mut Sbi);
     shutdown(true)
 }
 
@@ -36,3 +155,79 @@ unsafe fn backtrace() {
     }
     println!("---END   BACKTRACE---");
 }
+
+/// Run debug commands typed on the console until `continue`. Input is read
+/// by polling the SBI, as interrupts and the UART driver may be unusable.
+fn debug_prompt(regs: &PanicRegs, target: &mut impl PanicTarget) {
+    println!("[kernel] debug prompt, commands: regs, metrics, continue");
+    let mut line = [0u8; 32];
+    loop {
+        print!("panic> ");
+        let len = read_line(&mut line, target);
+        match core::str::from_utf8(&line[..len])
+            .ok()
+            .and_then(DebugCommand::parse)
+        {
+            Some(DebugCommand::Regs) => dump_regs(regs),
+            Some(DebugCommand::Metrics) => dump_metrics(),
+            Some(DebugCommand::Continue) => return,
+            None => println!("unknown command"),
+        }
+    }
+}
+
+/// Read a line into `buf`, echoing it, and return its length. Input beyond
+/// the end of `buf` is dropped.
+fn read_line(buf: &mut [u8], target: &mut impl PanicTarget) -> usize {
+    const BS: u8 = 0x08;
+    const DL: u8 = 0x7f;
+    let mut len = 0;
+    loop {
+        let c = match target.getchar() {
+            Some(c) => c,
+            None => continue,
+        };
+        match c {
+            b'\r' | b'\n' => {
+                println!("");
+                return len;
+            }
+            BS | DL if len > 0 => {
+                len -= 1;
+                print!("{} {}", BS as char, BS as char);
+            }
+            _ if len < buf.len() && (c.is_ascii_graphic() || c == b' ') => {
+                buf[len] = c;
+                len += 1;
+                print!("{}", c as char);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn dump_regs(regs: &PanicRegs) {
+    println!(
+        "sp      = {:#018x}  ra    = {:#018x}  s0     = {:#018x}",
+        regs.sp, regs.ra, regs.s0
+    );
+    println!(
+        "sstatus = {:#018x}  sepc  = {:#018x}  scause = {:#018x}",
+        regs.sstatus, regs.sepc, regs.scause
+    );
+    println!(
+        "stval   = {:#018x}  satp  = {:#018x}",
+        regs.stval, regs.satp
+    );
+}
+
+fn dump_metrics() {
+    println!(
+        "uptime {} ms, {} switches, {} tasks created, {} frames used, {} free",
+        get_time_ms(),
+        switch_count(),
+        tasks_created(),
+        frames_used(),
+        frames_free()
+    );
+}
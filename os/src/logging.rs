@@ -0,0 +1,158 @@
+use crate::sync::UPIntrFreeCell;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use lazy_static::*;
+use log::{self, Level, LevelFilter, Log, Metadata, Record};
+
+/// Bytes of recent output kept in a `LogRing`, for `sys_dmesg` and
+/// `sys_stdout_log`.
+pub const DMESG_LEN: usize = 4096;
+
+/// The last `DMESG_LEN` bytes written, oldest overwritten first.
+pub struct LogRing {
+    buf: [u8; DMESG_LEN],
+    /// where the next byte goes
+    head: usize,
+    wrapped: bool,
+    /// the byte just before the oldest one kept: the last one overwritten,
+    /// or a line end while nothing has been
+    dropped: u8,
+}
+
+impl LogRing {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; DMESG_LEN],
+            head: 0,
+            wrapped: false,
+            dropped: b'\n',
+        }
+    }
+    /// Up to the last `max` bytes written, oldest first, starting at a line
+    /// boundary.
+    pub fn tail(&self, max: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if self.wrapped {
+            bytes.extend_from_slice(&self.buf[self.head..]);
+        }
+        bytes.extend_from_slice(&self.buf[..self.head]);
+        let mut start = bytes.len().saturating_sub(max);
+        let before = match start {
+            0 => self.dropped,
+            _ => bytes[start - 1],
+        };
+        // drop a line cut by wraparound or by `max`, unless the cut falls
+        // right after a line end
+        if before != b'\n' {
+            if let Some(pos) = bytes[start..].iter().position(|&b| b == b'\n') {
+                start += pos + 1;
+            }
+        }
+        bytes.drain(..start);
+        bytes
+    }
+    /// Drop everything written so far.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.wrapped = false;
+        self.dropped = b'\n';
+    }
+}
+
+impl Write for LogRing {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &byte in s.as_bytes() {
+            if self.wrapped {
+                self.dropped = self.buf[self.head];
+            }
+            self.buf[self.head] = byte;
+            self.head += 1;
+            if self.head == DMESG_LEN {
+                self.head = 0;
+                self.wrapped = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+lazy_static! {
+    static ref DMESG: UPIntrFreeCell<LogRing> = unsafe { UPIntrFreeCell::new(LogRing::new()) };
+}
+
+/// Up to the last `max` bytes of kernel log, oldest first, starting at a line
+/// boundary.
+pub fn dmesg(max: usize) -> Vec<u8> {
+    DMESG.exclusive_access().tail(max)
+}
+
+/// Drop everything in the log ring.
+pub fn reset_dmesg() {
+    DMESG.exclusive_access().clear();
+}
+
+struct SimpleLogger;
+
+impl Log for SimpleLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let color = match record.level() {
+            Level::Error => 31, // Red
+            Level::Warn => 93,  // BrightYellow
+            Level::Info => 34,  // Blue
+            Level::Debug => 32, // Green
+            Level::Trace => 90, // BrightBlack
+        };
+        println!(
+            "\u{1B}[{}m[{:>5}] {}\u{1B}[0m",
+            color,
+            record.level(),
+            record.args(),
+        );
+        writeln!(
+            DMESG.exclusive_access(),
+            "[{:>5}] {}",
+            record.level(),
+            record.args()
+        )
+        .unwrap();
+    }
+    fn flush(&self) {}
+}
+
+pub fn init() {
+    static LOGGER: SimpleLogger = SimpleLogger;
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(match option_env!("LOG") {
+        Some("OFF") => LevelFilter::Off,
+        Some("WARN") => LevelFilter::Warn,
+        Some("INFO") => LevelFilter::Info,
+        Some("DEBUG") => LevelFilter::Debug,
+        Some("TRACE") => LevelFilter::Trace,
+        _ => LevelFilter::Error,
+    });
+}
+
+/// Change the level filter at runtime. `log` macros compare against it before
+/// formatting, so filtered records cost nothing.
+///
+/// `level` is 0 (off) to 5 (trace). Returns the previous level.
+pub fn set_level(level: usize) -> Option<usize> {
+    let filter = match level {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        5 => LevelFilter::Trace,
+        _ => return None,
+    };
+    let prev = log::max_level() as usize;
+    log::set_max_level(filter);
+    Some(prev)
+}
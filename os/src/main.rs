@@ -17,11 +17,14 @@ mod board;
 mod console;
 mod config;
 mod drivers;
+mod fdt;
 mod fs;
 mod lang_items;
+mod logging;
 mod mm;
 mod net;
 mod sbi;
+mod selftest;
 mod sync;
 mod syscall;
 mod task;
@@ -53,10 +56,15 @@ lazy_static! {
 }
 
 #[no_mangle]
-pub fn rust_main() -> ! {
+pub fn rust_main(hart_id: usize, dtb_pa: usize) -> ! {
     clear_bss();
+    task::set_boot_hart_id(hart_id);
+    // the device tree lies in memory the frame allocator hands out
+    timer::init_clock_freq(dtb_pa);
     mm::init();
     UART.init();
+    logging::init();
+    println!("KERN: timer frequency {} Hz", timer::clock_freq());
     println!("KERN: init gpu");
     let _gpu = GPU_DEVICE.clone();
     println!("KERN: init keyboard");
@@ -69,6 +77,9 @@ pub fn rust_main() -> ! {
     timer::set_next_trigger();
     board::device_init();
     fs::list_apps();
+    if config::BOOT_SELF_TEST {
+        selftest::run();
+    }
     task::add_initproc();
     *DEV_NON_BLOCKING_ACCESS.exclusive_access() = true;
     task::run_tasks();
@@ -113,6 +113,11 @@ impl VirtAddr {
     pub fn aligned(&self) -> bool {
         self.page_offset() == 0
     }
+    /// Pages touched by the `len` bytes starting at this address.
+    pub fn page_range(&self, len: usize) -> VPNRange {
+        let end: VirtAddr = (self.0 + len).into();
+        VPNRange::new(self.floor(), end.ceil())
+    }
 }
 impl From<VirtAddr> for VirtPageNum {
     fn from(v: VirtAddr) -> Self {
@@ -1,6 +1,7 @@
 use super::{PhysAddr, PhysPageNum};
-use crate::config::MEMORY_END;
+use crate::config::{FRAME_ALLOCATOR_KIND, MEMORY_END, RESERVED_FRAMES};
 use crate::sync::UPIntrFreeCell;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug, Formatter};
 use lazy_static::*;
@@ -32,14 +33,17 @@ impl Drop for FrameTracker {
     }
 }
 
-trait FrameAllocator {
+pub trait FrameAllocator {
     fn new() -> Self;
     fn alloc(&mut self) -> Option<PhysPageNum>;
     fn alloc_more(&mut self, pages: usize) -> Option<Vec<PhysPageNum>>;
     fn dealloc(&mut self, ppn: PhysPageNum);
+    fn frames_total(&self) -> usize;
+    fn frames_free(&self) -> usize;
 }
 
 pub struct StackFrameAllocator {
+    start: usize,
     current: usize,
     end: usize,
     recycled: Vec<usize>,
@@ -47,6 +51,7 @@ pub struct StackFrameAllocator {
 
 impl StackFrameAllocator {
     pub fn init(&mut self, l: PhysPageNum, r: PhysPageNum) {
+        self.start = l.0;
         self.current = l.0;
         self.end = r.0;
         // println!("last {} Physical Frames.", self.end - self.current);
@@ -55,6 +60,7 @@ impl StackFrameAllocator {
 impl FrameAllocator for StackFrameAllocator {
     fn new() -> Self {
         Self {
+            start: 0,
             current: 0,
             end: 0,
             recycled: Vec::new(),
@@ -89,9 +95,167 @@ impl FrameAllocator for StackFrameAllocator {
         // recycle
         self.recycled.push(ppn);
     }
+    fn frames_total(&self) -> usize {
+        self.end - self.start
+    }
+    fn frames_free(&self) -> usize {
+        self.end - self.current + self.recycled.len()
+    }
+}
+
+/// Keeps one bit per frame and hands out the lowest free frame after the last
+/// allocation, wrapping around.
+pub struct BitmapFrameAllocator {
+    start: usize,
+    end: usize,
+    /// bit `i` is set while frame `start + i` is allocated
+    bits: Vec<u64>,
+    free: usize,
+    /// word the next search starts at
+    next: usize,
+}
+
+impl BitmapFrameAllocator {
+    pub fn init(&mut self, l: PhysPageNum, r: PhysPageNum) {
+        let frames = r.0 - l.0;
+        self.start = l.0;
+        self.end = r.0;
+        self.bits = vec![0; (frames + 63) / 64];
+        // frames past `end` in the last word are never free
+        if frames % 64 != 0 {
+            *self.bits.last_mut().unwrap() = !0 << (frames % 64);
+        }
+        self.free = frames;
+        self.next = 0;
+    }
+    fn is_allocated(&self, idx: usize) -> bool {
+        self.bits[idx / 64] & (1 << (idx % 64)) != 0
+    }
+    fn set_allocated(&mut self, idx: usize, allocated: bool) {
+        if allocated {
+            self.bits[idx / 64] |= 1 << (idx % 64);
+        } else {
+            self.bits[idx / 64] &= !(1 << (idx % 64));
+        }
+    }
+}
+impl FrameAllocator for BitmapFrameAllocator {
+    fn new() -> Self {
+        Self {
+            start: 0,
+            end: 0,
+            bits: Vec::new(),
+            free: 0,
+            next: 0,
+        }
+    }
+    fn alloc(&mut self) -> Option<PhysPageNum> {
+        if self.free == 0 {
+            return None;
+        }
+        let words = self.bits.len();
+        let word = (0..words)
+            .map(|i| (self.next + i) % words)
+            .find(|&i| self.bits[i] != !0)?;
+        let idx = word * 64 + (!self.bits[word]).trailing_zeros() as usize;
+        self.set_allocated(idx, true);
+        self.free -= 1;
+        self.next = word;
+        Some((self.start + idx).into())
+    }
+    fn alloc_more(&mut self, pages: usize) -> Option<Vec<PhysPageNum>> {
+        // contiguous like the stack allocator, highest frame first
+        let mut run = 0;
+        for idx in 0..self.end - self.start {
+            if self.is_allocated(idx) {
+                run = 0;
+                continue;
+            }
+            run += 1;
+            if run == pages {
+                let first = idx + 1 - pages;
+                for i in first..=idx {
+                    self.set_allocated(i, true);
+                }
+                self.free -= pages;
+                return Some(
+                    (first..=idx)
+                        .rev()
+                        .map(|i| (self.start + i).into())
+                        .collect(),
+                );
+            }
+        }
+        None
+    }
+    fn dealloc(&mut self, ppn: PhysPageNum) {
+        let ppn = ppn.0;
+        // validity check
+        if ppn < self.start || ppn >= self.end || !self.is_allocated(ppn - self.start) {
+            panic!("Frame ppn={:#x} has not been allocated!", ppn);
+        }
+        self.set_allocated(ppn - self.start, false);
+        self.free += 1;
+    }
+    fn frames_total(&self) -> usize {
+        self.end - self.start
+    }
+    fn frames_free(&self) -> usize {
+        self.free
+    }
+}
+
+/// Frame allocators `FRAME_ALLOCATOR_KIND` can choose from.
+#[allow(unused)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FrameAllocatorKind {
+    Stack,
+    Bitmap,
+}
+
+pub enum FrameAllocatorImpl {
+    Stack(StackFrameAllocator),
+    Bitmap(BitmapFrameAllocator),
+}
+
+/// Calls `$method` on whichever allocator is in use.
+macro_rules! dispatch {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self {
+            FrameAllocatorImpl::Stack(allocator) => allocator.$method($($arg),*),
+            FrameAllocatorImpl::Bitmap(allocator) => allocator.$method($($arg),*),
+        }
+    };
 }
 
-type FrameAllocatorImpl = StackFrameAllocator;
+impl FrameAllocatorImpl {
+    pub fn init(&mut self, l: PhysPageNum, r: PhysPageNum) {
+        dispatch!(self.init(l, r))
+    }
+}
+impl FrameAllocator for FrameAllocatorImpl {
+    fn new() -> Self {
+        match FRAME_ALLOCATOR_KIND {
+            FrameAllocatorKind::Stack => Self::Stack(StackFrameAllocator::new()),
+            FrameAllocatorKind::Bitmap => Self::Bitmap(BitmapFrameAllocator::new()),
+        }
+    }
+    fn alloc(&mut self) -> Option<PhysPageNum> {
+        dispatch!(self.alloc())
+    }
+    fn alloc_more(&mut self, pages: usize) -> Option<Vec<PhysPageNum>> {
+        dispatch!(self.alloc_more(pages))
+    }
+    fn dealloc(&mut self, ppn: PhysPageNum) {
+        dispatch!(self.dealloc(ppn))
+    }
+    fn frames_total(&self) -> usize {
+        dispatch!(self.frames_total())
+    }
+    fn frames_free(&self) -> usize {
+        dispatch!(self.frames_free())
+    }
+}
 
 lazy_static! {
     pub static ref FRAME_ALLOCATOR: UPIntrFreeCell<FrameAllocatorImpl> =
@@ -126,6 +290,70 @@ pub fn frame_dealloc(ppn: PhysPageNum) {
     FRAME_ALLOCATOR.exclusive_access().dealloc(ppn);
 }
 
+pub fn frames_total() -> usize {
+    FRAME_ALLOCATOR.exclusive_access().frames_total()
+}
+
+#[cfg(feature = "testing")]
+lazy_static! {
+    /// Set by `inject_frame_exhaustion`, cleared by the next `frames_free`.
+    static ref FRAMES_EXHAUSTED_ONCE: UPIntrFreeCell<bool> = unsafe { UPIntrFreeCell::new(false) };
+}
+
+/// Make the next `frames_free` call report no free frames.
+#[cfg(feature = "testing")]
+pub fn inject_frame_exhaustion() {
+    *FRAMES_EXHAUSTED_ONCE.exclusive_access() = true;
+}
+
+pub fn frames_free() -> usize {
+    #[cfg(feature = "testing")]
+    if core::mem::take(&mut *FRAMES_EXHAUSTED_ONCE.exclusive_access()) {
+        return 0;
+    }
+    FRAME_ALLOCATOR.exclusive_access().frames_free()
+}
+
+/// Frames user memory may still take without eating into `RESERVED_FRAMES`.
+pub fn user_frames_available() -> usize {
+    frames_free().saturating_sub(RESERVED_FRAMES)
+}
+
+/// Allocate a frame for user memory, `None` once only the reserve is left.
+pub fn user_frame_alloc() -> Option<FrameTracker> {
+    if user_frames_available() == 0 {
+        return None;
+    }
+    frame_alloc()
+}
+
+pub fn frames_used() -> usize {
+    let allocator = FRAME_ALLOCATOR.exclusive_access();
+    allocator.frames_total() - allocator.frames_free()
+}
+
+/// Physical frame usage copied to user space by `sys_meminfo`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct MemInfo {
+    pub frames_total: usize,
+    pub frames_used: usize,
+    pub frames_free: usize,
+    /// `RESERVED_FRAMES`: free frames user memory may not take
+    pub frames_reserved: usize,
+}
+
+impl MemInfo {
+    pub fn current() -> Self {
+        Self {
+            frames_total: frames_total(),
+            frames_used: frames_used(),
+            frames_free: frames_free(),
+            frames_reserved: RESERVED_FRAMES,
+        }
+    }
+}
+
 #[allow(unused)]
 pub fn frame_allocator_test() {
     let mut v: Vec<FrameTracker> = Vec::new();
@@ -33,10 +33,11 @@ pub fn heap_test() {
     assert!(bss_range.contains(&(a.as_ref() as *const _ as usize)));
     drop(a);
     let mut v: Vec<usize> = Vec::new();
-    for i in 0..500 {
+    for i in 0..1000 {
         v.push(i);
     }
-    for (i, val) in v.iter().take(500).enumerate() {
+    assert_eq!(v.len(), 1000);
+    for (i, val) in v.iter().enumerate() {
         assert_eq!(*val, i);
     }
     assert!(bss_range.contains(&(v.as_ptr() as usize)));
@@ -1,13 +1,18 @@
-use super::{frame_alloc, FrameTracker};
+use super::{frame_alloc, user_frame_alloc, user_frames_available, FrameTracker};
 use super::{PTEFlags, PageTable, PageTableEntry};
 use super::{PhysAddr, PhysPageNum, VirtAddr, VirtPageNum};
 use super::{StepByOne, VPNRange};
-use crate::config::{MEMORY_END, MMIO, PAGE_SIZE, TRAMPOLINE};
+use crate::config::{
+    MEMORY_END, MMAP_BASE, MMAP_TOP, MMIO, PAGE_SIZE, TRAMPOLINE, UNMAPPED_HISTORY,
+    UNMAPPED_HISTORY_MS,
+};
 use crate::sync::UPIntrFreeCell;
-use alloc::collections::BTreeMap;
+use crate::timer::get_time_ms;
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::arch::asm;
+use core::ops::Range;
 use lazy_static::*;
 use riscv::register::satp;
 
@@ -36,6 +41,24 @@ pub fn kernel_token() -> usize {
 pub struct MemorySet {
     page_table: PageTable,
     areas: Vec<MapArea>,
+    /// Ranges recently removed by `munmap`, oldest first.
+    unmapped: VecDeque<UnmappedRange>,
+    /// Pages added by `mmap` and not unmapped since.
+    mmap_pages: usize,
+}
+
+/// A range removed by `munmap`, remembered to diagnose later accesses.
+struct UnmappedRange {
+    start: usize,
+    len: usize,
+    time_ms: usize,
+}
+
+/// Whether `[start, start + len)` lies in `[MMAP_BASE, MMAP_TOP)`, the only
+/// range user calls may map, unmap or change. Below it are the ELF image and
+/// the thread stacks, which the kernel manages itself.
+fn in_mmap_window(start: usize, len: usize) -> bool {
+    start >= MMAP_BASE && matches!(start.checked_add(len), Some(end) if end <= MMAP_TOP)
 }
 
 impl MemorySet {
@@ -43,12 +66,23 @@ impl MemorySet {
         Self {
             page_table: PageTable::new(),
             areas: Vec::new(),
+            unmapped: VecDeque::new(),
+            mmap_pages: 0,
         }
     }
     pub fn token(&self) -> usize {
         self.page_table.token()
     }
-    /// Assume that no conflicts.
+    /// Pages added by `mmap` and not unmapped since, mapped or not.
+    pub fn mmap_pages(&self) -> usize {
+        self.mmap_pages
+    }
+    /// Number of frames backing the user-visible areas.
+    pub fn rss_pages(&self) -> usize {
+        self.areas.iter().map(|area| area.data_frames.len()).sum()
+    }
+    /// Assume that no conflicts. The frames may come out of the reserve, so
+    /// this is for kernel areas only, see `try_insert_framed_area`.
     pub fn insert_framed_area(
         &mut self,
         start_va: VirtAddr,
@@ -60,6 +94,36 @@ impl MemorySet {
             None,
         );
     }
+    /// `insert_framed_area` for user memory, with frames from outside the
+    /// reserve. Returns false, with nothing mapped, if there are not enough.
+    pub fn try_insert_framed_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+    ) -> bool {
+        self.try_push(
+            MapArea::new(start_va, end_va, MapType::Framed, permission),
+            None,
+        )
+    }
+    /// Add a user stack that may grow down to `bottom`. Only the top page is
+    /// mapped now, the others get frames when the stack first reaches them.
+    /// Returns false, adding nothing, if there is no frame outside the
+    /// reserve for the top page.
+    pub fn insert_stack_area(
+        &mut self,
+        bottom: VirtAddr,
+        top: VirtAddr,
+        permission: MapPermission,
+    ) -> bool {
+        let mut area = MapArea::new(bottom, top, MapType::Framed, permission);
+        if !area.try_map_one(&mut self.page_table, VirtPageNum(top.ceil().0 - 1)) {
+            return false;
+        }
+        self.areas.push(area);
+        true
+    }
     pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
         if let Some((idx, area)) = self
             .areas
@@ -71,6 +135,287 @@ impl MemorySet {
             self.areas.remove(idx);
         }
     }
+    fn overlapping_areas(
+        &self,
+        start_vpn: VirtPageNum,
+        end_vpn: VirtPageNum,
+    ) -> impl Iterator<Item = &MapArea> {
+        self.areas.iter().filter(move |area| {
+            area.vpn_range.get_start() < end_vpn && start_vpn < area.vpn_range.get_end()
+        })
+    }
+    /// Whether `va` lies in an area mapped without write permission.
+    pub fn is_read_only(&self, va: VirtAddr) -> bool {
+        let vpn = va.floor();
+        self.areas.iter().any(|area| {
+            area.vpn_range.get_start() <= vpn
+                && vpn < area.vpn_range.get_end()
+                && !area.map_perm.contains(MapPermission::W)
+        })
+    }
+    /// Find the lowest free range of `len` bytes in `[MMAP_BASE, MMAP_TOP)`.
+    pub fn find_free_vregion(&self, len: usize) -> Option<VirtAddr> {
+        let pages = (len + PAGE_SIZE - 1) / PAGE_SIZE;
+        let top_vpn = VirtAddr::from(MMAP_TOP).floor();
+        let mut start_vpn = VirtAddr::from(MMAP_BASE).floor();
+        loop {
+            let end_vpn = VirtPageNum(start_vpn.0 + pages);
+            if end_vpn > top_vpn {
+                return None;
+            }
+            // skip past everything in the way and try again
+            match self
+                .overlapping_areas(start_vpn, end_vpn)
+                .map(|area| area.vpn_range.get_end())
+                .max()
+            {
+                Some(next_vpn) => start_vpn = next_vpn,
+                None => return Some(start_vpn.into()),
+            }
+        }
+    }
+    /// Map `len` bytes at `start`, or at a free range chosen by
+    /// `find_free_vregion` if `start` is 0. Returns where the range begins.
+    ///
+    /// Frames are allocated on first touch by `handle_lazy_fault`, unless
+    /// `populate` is set: then all of them are mapped now, or nothing is if
+    /// there are not enough free frames for the pages and their page tables.
+    /// Pages never take the last `RESERVED_FRAMES`, page tables may.
+    pub fn mmap(
+        &mut self,
+        start: usize,
+        len: usize,
+        perm: MapPermission,
+        populate: bool,
+    ) -> Option<VirtAddr> {
+        if len == 0 || start % PAGE_SIZE != 0 {
+            return None;
+        }
+        let start_va = if start == 0 {
+            self.find_free_vregion(len)?
+        } else {
+            if !in_mmap_window(start, len) {
+                return None;
+            }
+            let start_va = VirtAddr::from(start);
+            let end_va = VirtAddr::from(start + len);
+            if self
+                .overlapping_areas(start_va.floor(), end_va.ceil())
+                .next()
+                .is_some()
+            {
+                return None;
+            }
+            start_va
+        };
+        let end_va = VirtAddr::from(usize::from(start_va) + len);
+        if populate {
+            if user_frames_available() < end_va.ceil().0 - start_va.floor().0 {
+                return None;
+            }
+            let mut area = MapArea::new(start_va, end_va, MapType::Framed, perm);
+            if !area.try_map(&mut self.page_table) {
+                return None;
+            }
+            self.areas.push(area);
+        } else {
+            self.areas
+                .push(MapArea::new(start_va, end_va, MapType::Framed, perm));
+        }
+        self.coalesce_last_area();
+        self.mmap_pages += end_va.ceil().0 - start_va.floor().0;
+        Some(start_va)
+    }
+    /// Merge the area pushed last with framed areas directly before and after
+    /// it that have the same permission. Only areas in `[MMAP_BASE, MMAP_TOP)`
+    /// are merged: nothing but `mmap` puts areas there, while areas below may
+    /// be stacks that are later removed by their start address.
+    fn coalesce_last_area(&mut self) {
+        let mut area = self.areas.pop().unwrap();
+        let perm = area.map_perm;
+        let mergeable = |other: &MapArea| {
+            other.map_type == MapType::Framed
+                && other.map_perm == perm
+                && usize::from(VirtAddr::from(other.vpn_range.get_start())) >= MMAP_BASE
+        };
+        if mergeable(&area) {
+            if let Some(idx) = self.areas.iter().position(|other| {
+                mergeable(other) && other.vpn_range.get_end() == area.vpn_range.get_start()
+            }) {
+                let mut before = self.areas.swap_remove(idx);
+                before.append(area);
+                area = before;
+            }
+            if let Some(idx) = self.areas.iter().position(|other| {
+                mergeable(other) && other.vpn_range.get_start() == area.vpn_range.get_end()
+            }) {
+                let after = self.areas.swap_remove(idx);
+                area.append(after);
+            }
+        }
+        self.areas.push(area);
+    }
+    /// Number of areas, which adjacent `mmap`s may share.
+    pub fn area_count(&self) -> usize {
+        self.areas.len()
+    }
+    /// Back the page holding `va` with a frame if it lies in a framed area but
+    /// has not been touched yet. Returns false if `va` is not such a page, or
+    /// if no frame is left outside the reserve.
+    pub fn handle_lazy_fault(&mut self, va: VirtAddr) -> bool {
+        let vpn = va.floor();
+        match self.areas.iter_mut().find(|area| {
+            area.map_type == MapType::Framed
+                && area.vpn_range.get_start() <= vpn
+                && vpn < area.vpn_range.get_end()
+                && !area.data_frames.contains_key(&vpn)
+        }) {
+            Some(area) => area.try_map_one(&mut self.page_table, vpn),
+            None => false,
+        }
+    }
+    /// Map every untouched lazy page in `[start, start + len)`, so the kernel
+    /// can access the range through the page table.
+    pub fn fault_in(&mut self, start: usize, len: usize) {
+        for vpn in VirtAddr::from(start).page_range(len) {
+            self.handle_lazy_fault(vpn.into());
+        }
+    }
+    /// Whether every page in `[start_vpn, end_vpn)` belongs to a framed area.
+    fn framed_cover(&self, start_vpn: VirtPageNum, end_vpn: VirtPageNum) -> bool {
+        let mut covered = 0;
+        for area in self.overlapping_areas(start_vpn, end_vpn) {
+            if area.map_type != MapType::Framed {
+                return false;
+            }
+            let l = area.vpn_range.get_start().max(start_vpn);
+            let r = area.vpn_range.get_end().min(end_vpn);
+            covered += r.0 - l.0;
+        }
+        covered == end_vpn.0 - start_vpn.0
+    }
+    /// Unmap `[start, start + len)`, which must be fully covered by framed
+    /// areas. Areas only partly covered are shrunk or split.
+    pub fn munmap(&mut self, start: usize, len: usize) -> bool {
+        if len == 0 || start % PAGE_SIZE != 0 {
+            return false;
+        }
+        if !in_mmap_window(start, len) {
+            return false;
+        }
+        let start_vpn = VirtAddr::from(start).floor();
+        let end_vpn = VirtAddr::from(start + len).ceil();
+        if !self.framed_cover(start_vpn, end_vpn) {
+            return false;
+        }
+        let mut idx = 0;
+        while idx < self.areas.len() {
+            let area = &mut self.areas[idx];
+            if area.vpn_range.get_start() >= end_vpn || start_vpn >= area.vpn_range.get_end() {
+                idx += 1;
+                continue;
+            }
+            let tail = area.unmap_range(&mut self.page_table, start_vpn, end_vpn);
+            if area.vpn_range.get_start() == area.vpn_range.get_end() {
+                self.areas.remove(idx);
+            } else {
+                idx += 1;
+            }
+            if let Some(tail) = tail {
+                self.areas.push(tail);
+            }
+        }
+        self.mmap_pages = self.mmap_pages.saturating_sub(end_vpn.0 - start_vpn.0);
+        self.remember_unmapped(start, len);
+        true
+    }
+    /// Free the frames behind `[start, start + len)` but keep the mapping, so
+    /// the next access faults in a zeroed page. The range must be fully
+    /// covered by framed areas.
+    pub fn drop_frames(&mut self, start: usize, len: usize) -> bool {
+        if !in_mmap_window(start, len) {
+            return false;
+        }
+        let start_vpn = VirtAddr::from(start).floor();
+        let end_vpn = VirtAddr::from(start + len).ceil();
+        if !self.framed_cover(start_vpn, end_vpn) {
+            return false;
+        }
+        for area in self.areas.iter_mut() {
+            let l = area.vpn_range.get_start().max(start_vpn);
+            let r = area.vpn_range.get_end().min(end_vpn);
+            if l < r {
+                for vpn in VPNRange::new(l, r) {
+                    area.unmap_one(&mut self.page_table, vpn);
+                }
+            }
+        }
+        true
+    }
+    fn remember_unmapped(&mut self, start: usize, len: usize) {
+        let now = get_time_ms();
+        self.unmapped
+            .retain(|range| now - range.time_ms < UNMAPPED_HISTORY_MS);
+        if self.unmapped.len() == UNMAPPED_HISTORY {
+            self.unmapped.pop_front();
+        }
+        self.unmapped.push_back(UnmappedRange {
+            start,
+            len,
+            time_ms: now,
+        });
+    }
+    /// Whether `va` lies in a range `munmap`ed within the last
+    /// `UNMAPPED_HISTORY_MS`, to tell a use after unmap from a wild access.
+    pub fn recently_unmapped(&self, va: VirtAddr) -> bool {
+        let now = get_time_ms();
+        self.unmapped.iter().any(|range| {
+            now - range.time_ms < UNMAPPED_HISTORY_MS
+                && (range.start..range.start + range.len).contains(&va.0)
+        })
+    }
+    /// Change the permission of `[start, start + len)`, which must be fully
+    /// covered by framed areas. Areas only partly covered are split so that
+    /// pages faulted in later get the permission of their own part.
+    pub fn mprotect(&mut self, start: usize, len: usize, perm: MapPermission) -> bool {
+        if len == 0 || start % PAGE_SIZE != 0 {
+            return false;
+        }
+        if !in_mmap_window(start, len) {
+            return false;
+        }
+        let start_vpn = VirtAddr::from(start).floor();
+        let end_vpn = VirtAddr::from(start + len).ceil();
+        if !self.framed_cover(start_vpn, end_vpn) {
+            return false;
+        }
+        let mut idx = 0;
+        while idx < self.areas.len() {
+            let area = &mut self.areas[idx];
+            if area.vpn_range.get_start() >= end_vpn || start_vpn >= area.vpn_range.get_end() {
+                idx += 1;
+                continue;
+            }
+            if area.vpn_range.get_start() < start_vpn {
+                // the part from `start_vpn` on is handled when the loop reaches it
+                let tail = area.split_off(start_vpn);
+                self.areas.push(tail);
+                idx += 1;
+                continue;
+            }
+            let tail = if end_vpn < area.vpn_range.get_end() {
+                Some(area.split_off(end_vpn))
+            } else {
+                None
+            };
+            area.set_perm(&mut self.page_table, perm);
+            if let Some(tail) = tail {
+                self.areas.push(tail);
+            }
+            idx += 1;
+        }
+        true
+    }
     /// Add a new MapArea into this MemorySet.
     /// Assuming that there are no conflicts in the virtual address
     /// space.
@@ -81,6 +426,18 @@ impl MemorySet {
         }
         self.areas.push(map_area);
     }
+    /// `push` for a framed user area, whose frames come from outside the
+    /// reserve. Returns false, with nothing mapped, if there are not enough.
+    fn try_push(&mut self, mut map_area: MapArea, data: Option<&[u8]>) -> bool {
+        if !map_area.try_map(&mut self.page_table) {
+            return false;
+        }
+        if let Some(data) = data {
+            map_area.copy_data(&mut self.page_table, data);
+        }
+        self.areas.push(map_area);
+        true
+    }
     /// Mention that trampoline is not collected by areas.
     fn map_trampoline(&mut self) {
         self.page_table.map(
@@ -167,23 +524,43 @@ impl MemorySet {
         memory_set
     }
     /// Include sections in elf and trampoline,
-    /// also returns user_sp_base and entry point.
-    pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
+    /// also returns the range of the image, user_sp_base and entry point.
+    /// Fails if `elf_data` is not an ELF file or a loadable segment fails
+    /// `check_segment`, which is logged with `app_name` and the segment, or
+    /// if the segments need more frames than are left outside the reserve.
+    pub fn from_elf(
+        app_name: &str,
+        elf_data: &[u8],
+    ) -> Result<(Self, Range<usize>, usize, usize), LoadError> {
         let mut memory_set = Self::new_bare();
         // map trampoline
         memory_set.map_trampoline();
         // map program headers of elf, with U flag
-        let elf = xmas_elf::ElfFile::new(elf_data).unwrap();
+        let elf = xmas_elf::ElfFile::new(elf_data)?;
         let elf_header = elf.header;
         let magic = elf_header.pt1.magic;
-        assert_eq!(magic, [0x7f, 0x45, 0x4c, 0x46], "invalid elf!");
+        if magic != [0x7f, 0x45, 0x4c, 0x46] {
+            return Err(LoadError::Invalid("invalid elf"));
+        }
         let ph_count = elf_header.pt2.ph_count();
+        let mut min_start_va = usize::MAX;
         let mut max_end_vpn = VirtPageNum(0);
         for i in 0..ph_count {
-            let ph = elf.program_header(i).unwrap();
-            if ph.get_type().unwrap() == xmas_elf::program::Type::Load {
+            let ph = elf.program_header(i)?;
+            if ph.get_type()? == xmas_elf::program::Type::Load {
+                if let Err(msg) = check_segment(
+                    ph.offset() as usize,
+                    ph.file_size() as usize,
+                    ph.virtual_addr() as usize,
+                    ph.mem_size() as usize,
+                    elf_data.len(),
+                ) {
+                    warn!("app {}: program header {}: {}", app_name, i, msg);
+                    return Err(LoadError::Invalid(msg));
+                }
                 let start_va: VirtAddr = (ph.virtual_addr() as usize).into();
                 let end_va: VirtAddr = ((ph.virtual_addr() + ph.mem_size()) as usize).into();
+                min_start_va = min_start_va.min(start_va.into());
                 let mut map_perm = MapPermission::U;
                 let ph_flags = ph.flags();
                 if ph_flags.is_read() {
@@ -197,39 +574,55 @@ impl MemorySet {
                 }
                 let map_area = MapArea::new(start_va, end_va, MapType::Framed, map_perm);
                 max_end_vpn = map_area.vpn_range.get_end();
-                memory_set.push(
+                if !memory_set.try_push(
                     map_area,
                     Some(&elf.input[ph.offset() as usize..(ph.offset() + ph.file_size()) as usize]),
-                );
+                ) {
+                    return Err(LoadError::NoMem);
+                }
             }
         }
         let max_end_va: VirtAddr = max_end_vpn.into();
         let mut user_stack_base: usize = max_end_va.into();
         user_stack_base += PAGE_SIZE;
-        (
+        Ok((
             memory_set,
+            min_start_va..max_end_va.into(),
             user_stack_base,
             elf.header.pt2.entry_point() as usize,
-        )
+        ))
     }
-    pub fn from_existed_user(user_space: &MemorySet) -> MemorySet {
+    /// A copy of `user_space`, or `None` if there are not enough frames for
+    /// it outside the reserve.
+    pub fn from_existed_user(user_space: &MemorySet) -> Option<MemorySet> {
         let mut memory_set = Self::new_bare();
         // map trampoline
         memory_set.map_trampoline();
         // copy data sections/trap_context/user_stack
         for area in user_space.areas.iter() {
-            let new_area = MapArea::from_another(area);
-            memory_set.push(new_area, None);
+            let mut new_area = MapArea::from_another(area);
+            // untouched lazy pages stay lazy in the copy
+            let vpns: Vec<VirtPageNum> = match area.map_type {
+                MapType::Framed => area.data_frames.keys().copied().collect(),
+                _ => area.vpn_range.into_iter().collect(),
+            };
             // copy data from another space
-            for vpn in area.vpn_range {
+            for vpn in vpns {
+                if area.map_type != MapType::Framed {
+                    new_area.map_one(&mut memory_set.page_table, vpn);
+                } else if !new_area.try_map_one(&mut memory_set.page_table, vpn) {
+                    return None;
+                }
                 let src_ppn = user_space.translate(vpn).unwrap().ppn();
                 let dst_ppn = memory_set.translate(vpn).unwrap().ppn();
                 dst_ppn
                     .get_bytes_array()
                     .copy_from_slice(src_ppn.get_bytes_array());
             }
+            memory_set.areas.push(new_area);
         }
-        memory_set
+        memory_set.mmap_pages = user_space.mmap_pages;
+        Some(memory_set)
     }
     pub fn activate(&self) {
         let satp = self.page_table.token();
@@ -238,12 +631,28 @@ impl MemorySet {
             asm!("sfence.vma");
         }
     }
+    /// Number of user pages touched since the last call, found by clearing
+    /// their accessed bits. Only meaningful while this address space is
+    /// active, as the TLB is flushed so the bits get set again.
+    pub fn sample_working_set(&mut self) -> usize {
+        let accessed = self.page_table.take_accessed();
+        unsafe {
+            asm!("sfence.vma");
+        }
+        accessed
+    }
     pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
         self.page_table.translate(vpn)
     }
+    /// Free every frame of an address space nothing runs in anymore: the
+    /// data frames of all areas, `mmap`ed ones included, and the page table
+    /// frames except the now empty root, which stays until the memory set
+    /// itself is dropped.
     pub fn recycle_data_pages(&mut self) {
-        //*self = Self::new_bare();
         self.areas.clear();
+        self.unmapped.clear();
+        self.mmap_pages = 0;
+        self.page_table.recycle_tables();
     }
 }
 
@@ -278,6 +687,8 @@ impl MapArea {
             map_perm: another.map_perm,
         }
     }
+    /// Framed pages take any free frame, the reserve included, so user areas
+    /// go through `try_map_one` instead.
     pub fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
         let ppn: PhysPageNum;
         match self.map_type {
@@ -298,9 +709,41 @@ impl MapArea {
         let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
         page_table.map(vpn, ppn, pte_flags);
     }
+    /// Map every page of a framed area, or none if frames run out: the frames
+    /// taken so far, page tables included, are given back. Returns whether
+    /// the area was mapped.
+    pub fn try_map(&mut self, page_table: &mut PageTable) -> bool {
+        assert!(self.map_type == MapType::Framed);
+        let mark = page_table.table_mark();
+        for vpn in self.vpn_range {
+            if !self.try_map_one(page_table, vpn) {
+                for vpn in VPNRange::new(self.vpn_range.get_start(), vpn) {
+                    self.unmap_one(page_table, vpn);
+                }
+                page_table.release_tables_since(mark);
+                return false;
+            }
+        }
+        true
+    }
+    /// Map `vpn` of a framed user area, with a frame from outside the
+    /// reserve. Returns false if there is none, or none for a page table.
+    fn try_map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) -> bool {
+        let frame = match user_frame_alloc() {
+            Some(frame) => frame,
+            None => return false,
+        };
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        if !page_table.try_map(vpn, frame.ppn, pte_flags) {
+            return false;
+        }
+        self.data_frames.insert(vpn, frame);
+        true
+    }
     pub fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
-        if self.map_type == MapType::Framed {
-            self.data_frames.remove(&vpn);
+        // a lazy framed page that was never touched has nothing to unmap
+        if self.map_type == MapType::Framed && self.data_frames.remove(&vpn).is_none() {
+            return;
         }
         page_table.unmap(vpn);
     }
@@ -314,6 +757,61 @@ impl MapArea {
             self.unmap_one(page_table, vpn);
         }
     }
+    /// Unmap the part of this area inside `[start_vpn, end_vpn)`. The part
+    /// below stays in `self`, the part above (if any) is returned as a new area.
+    pub fn unmap_range(
+        &mut self,
+        page_table: &mut PageTable,
+        start_vpn: VirtPageNum,
+        end_vpn: VirtPageNum,
+    ) -> Option<MapArea> {
+        let area_start = self.vpn_range.get_start();
+        let area_end = self.vpn_range.get_end();
+        let l = area_start.max(start_vpn);
+        let r = area_end.min(end_vpn);
+        for vpn in VPNRange::new(l, r) {
+            self.unmap_one(page_table, vpn);
+        }
+        let tail = if r < area_end {
+            Some(MapArea {
+                vpn_range: VPNRange::new(r, area_end),
+                data_frames: self.data_frames.split_off(&r),
+                map_type: self.map_type,
+                map_perm: self.map_perm,
+            })
+        } else {
+            None
+        };
+        self.vpn_range = VPNRange::new(area_start, l);
+        tail
+    }
+    /// Extend this area over `other`, which must start where this one ends.
+    pub fn append(&mut self, mut other: MapArea) {
+        assert_eq!(self.vpn_range.get_end(), other.vpn_range.get_start());
+        self.data_frames.append(&mut other.data_frames);
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), other.vpn_range.get_end());
+    }
+    /// Shrink this area to end at `vpn` and return the rest as a new area.
+    pub fn split_off(&mut self, vpn: VirtPageNum) -> MapArea {
+        let tail = MapArea {
+            vpn_range: VPNRange::new(vpn, self.vpn_range.get_end()),
+            data_frames: self.data_frames.split_off(&vpn),
+            map_type: self.map_type,
+            map_perm: self.map_perm,
+        };
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), vpn);
+        tail
+    }
+    /// Switch a framed area to `perm`, rewriting the flags of the pages
+    /// already backed by frames.
+    pub fn set_perm(&mut self, page_table: &mut PageTable, perm: MapPermission) {
+        assert_eq!(self.map_type, MapType::Framed);
+        self.map_perm = perm;
+        let pte_flags = PTEFlags::from_bits(perm.bits).unwrap();
+        for &vpn in self.data_frames.keys() {
+            page_table.set_flags(vpn, pte_flags);
+        }
+    }
     /// data: start-aligned but maybe with shorter length
     /// assume that all frames were cleared before
     pub fn copy_data(&mut self, page_table: &mut PageTable, data: &[u8]) {
@@ -338,6 +836,45 @@ impl MapArea {
     }
 }
 
+/// Why `MemorySet::from_elf` could not load an app image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// not an ELF file, or a loadable segment fails `check_segment`
+    Invalid(&'static str),
+    /// no frame left for it outside `RESERVED_FRAMES`
+    NoMem,
+}
+
+impl From<&'static str> for LoadError {
+    fn from(msg: &'static str) -> Self {
+        Self::Invalid(msg)
+    }
+}
+
+/// Check that a loadable segment of an app image has its data inside the
+/// file of `file_len` bytes and is mapped below `MMAP_BASE`, with no
+/// arithmetic overflow on the way.
+pub fn check_segment(
+    offset: usize,
+    file_size: usize,
+    vaddr: usize,
+    mem_size: usize,
+    file_len: usize,
+) -> Result<(), &'static str> {
+    match offset.checked_add(file_size) {
+        Some(end) if end <= file_len => {}
+        _ => return Err("data extends past the end of the file"),
+    }
+    if file_size > mem_size {
+        return Err("more data than memory");
+    }
+    match vaddr.checked_add(mem_size) {
+        Some(end) if end <= MMAP_BASE => {}
+        _ => return Err("mapped outside the app area"),
+    }
+    Ok(())
+}
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum MapType {
     Identical,
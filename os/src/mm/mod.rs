@@ -6,13 +6,22 @@ mod page_table;
 
 pub use address::VPNRange;
 pub use address::{PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum};
-pub use frame_allocator::{frame_alloc, frame_alloc_more, frame_dealloc, FrameTracker};
+#[cfg(feature = "testing")]
+pub use frame_allocator::inject_frame_exhaustion;
+pub use frame_allocator::{
+    frame_alloc, frame_alloc_more, frame_dealloc, frames_free, frames_total, frames_used,
+    user_frame_alloc, user_frames_available, BitmapFrameAllocator, FrameAllocator,
+    FrameAllocatorKind, FrameTracker, MemInfo, StackFrameAllocator,
+};
 pub use memory_set::remap_test;
-pub use memory_set::{kernel_token, MapArea, MapPermission, MapType, MemorySet, KERNEL_SPACE};
+pub use memory_set::{
+    check_segment, kernel_token, LoadError, MapArea, MapPermission, MapType, MemorySet,
+    KERNEL_SPACE,
+};
 use page_table::PTEFlags;
 pub use page_table::{
-    translated_byte_buffer, translated_ref, translated_refmut, translated_str, PageTable,
-    PageTableEntry, UserBuffer, UserBufferIterator,
+    copy_from_user, copy_to_user, translated_byte_buffer, translated_refmut, translated_str,
+    user_buffer_valid, PageTable, PageTableEntry, UserBuffer, UserBufferIterator,
 };
 
 pub fn init() {
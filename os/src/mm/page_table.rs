@@ -1,8 +1,12 @@
-use super::{frame_alloc, FrameTracker, PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum};
+use super::{
+    frame_alloc, FrameTracker, MemorySet, PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum,
+};
+use crate::config::PAGE_SIZE;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use bitflags::*;
+use core::mem::MaybeUninit;
 
 bitflags! {
     pub struct PTEFlags: u8 {
@@ -84,7 +88,7 @@ impl PageTable {
                 break;
             }
             if !pte.is_valid() {
-                let frame = frame_alloc().unwrap();
+                let frame = frame_alloc()?;
                 *pte = PageTableEntry::new(frame.ppn, PTEFlags::V);
                 self.frames.push(frame);
             }
@@ -115,12 +119,97 @@ impl PageTable {
         assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
         *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
     }
+    /// Like `map`, but returns false instead of panicking if there is no
+    /// frame left for a page table.
+    pub fn try_map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) -> bool {
+        match self.find_pte_create(vpn) {
+            Some(pte) => {
+                assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
+                *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Marks how many page table frames are in use, for `release_tables_since`.
+    pub fn table_mark(&self) -> usize {
+        self.frames.len()
+    }
+    /// Free the page tables created after `mark` was taken. Nothing may be
+    /// mapped through them any more.
+    pub fn release_tables_since(&mut self, mark: usize) {
+        let released: Vec<PhysPageNum> =
+            self.frames[mark..].iter().map(|frame| frame.ppn).collect();
+        Self::unlink_tables(self.root_ppn, 0, &released);
+        self.frames.truncate(mark);
+    }
+    fn unlink_tables(ppn: PhysPageNum, level: usize, released: &[PhysPageNum]) {
+        for pte in ppn.get_pte_array().iter_mut() {
+            if !pte.is_valid() {
+                continue;
+            }
+            if released.contains(&pte.ppn()) {
+                *pte = PageTableEntry::empty();
+            } else if level < 1 {
+                Self::unlink_tables(pte.ppn(), level + 1, released);
+            }
+        }
+    }
     #[allow(unused)]
     pub fn unmap(&mut self, vpn: VirtPageNum) {
         let pte = self.find_pte(vpn).unwrap();
         assert!(pte.is_valid(), "vpn {:?} is invalid before unmapping", vpn);
         *pte = PageTableEntry::empty();
     }
+    /// Unmap everything and free all page table frames but the root.
+    pub fn recycle_tables(&mut self) {
+        self.root_ppn.get_pte_array().fill(PageTableEntry::empty());
+        self.frames.truncate(1);
+    }
+    /// Replace the flags of a mapped page, keeping its frame.
+    pub fn set_flags(&mut self, vpn: VirtPageNum, flags: PTEFlags) {
+        let pte = self.find_pte(vpn).unwrap();
+        assert!(pte.is_valid(), "vpn {:?} is invalid before remapping", vpn);
+        *pte = PageTableEntry::new(pte.ppn(), flags | PTEFlags::V);
+    }
+    /// All valid leaf entries, by increasing virtual page number.
+    pub fn mappings(&self) -> Vec<(VirtPageNum, PageTableEntry)> {
+        let mut mappings = Vec::new();
+        self.collect_mappings(self.root_ppn, 0, 0, &mut mappings);
+        mappings
+    }
+    fn collect_mappings(
+        &self,
+        ppn: PhysPageNum,
+        level: usize,
+        vpn_prefix: usize,
+        mappings: &mut Vec<(VirtPageNum, PageTableEntry)>,
+    ) {
+        for (idx, pte) in ppn.get_pte_array().iter().enumerate() {
+            if !pte.is_valid() {
+                continue;
+            }
+            let vpn = vpn_prefix << 9 | idx;
+            if level == 2 {
+                mappings.push((vpn.into(), *pte));
+            } else {
+                self.collect_mappings(pte.ppn(), level + 1, vpn, mappings);
+            }
+        }
+    }
+    /// Clear the accessed bit of every user page and return how many had it
+    /// set. The TLB may still hold the old entries until `sfence.vma`.
+    pub fn take_accessed(&mut self) -> usize {
+        let mut accessed = 0;
+        for (vpn, pte) in self.mappings() {
+            let flags = pte.flags();
+            if flags.contains(PTEFlags::U | PTEFlags::A) {
+                *self.find_pte(vpn).unwrap() = PageTableEntry::new(pte.ppn(), flags - PTEFlags::A);
+                accessed += 1;
+            }
+        }
+        accessed
+    }
     pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
         self.find_pte(vpn).map(|pte| *pte)
     }
@@ -137,6 +226,8 @@ impl PageTable {
     }
 }
 
+/// The pages of `[ptr, ptr + len)` in another address space as byte slices.
+/// Empty if `len` is 0, so callers that need data must check `len` first.
 pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&'static mut [u8]> {
     let page_table = PageTable::from_token(token);
     let mut start = ptr as usize;
@@ -159,12 +250,56 @@ pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&
     v
 }
 
-/// Load a string from other address spaces into kernel space without an end `\0`.
-pub fn translated_str(token: usize, ptr: *const u8) -> String {
-    let page_table = PageTable::from_token(token);
+/// Whether every page spanned by `[ptr, ptr + len)` in `memory_set` is
+/// mapped, user accessible and readable, and also writable if `writable` is
+/// set. Untouched lazy pages are mapped first.
+pub fn user_buffer_valid(
+    memory_set: &mut MemorySet,
+    ptr: *const u8,
+    len: usize,
+    writable: bool,
+) -> bool {
+    let start = ptr as usize;
+    let end = match start.checked_add(len) {
+        Some(end) => end,
+        None => return false,
+    };
+    if len == 0 {
+        return true;
+    }
+    // beyond the lower half of Sv39 there is no user memory
+    if usize::from(VirtAddr::from(end)) != end {
+        return false;
+    }
+    // lazily mapped pages get their frames before the kernel touches them
+    memory_set.fault_in(start, len);
+    let mut need = PTEFlags::V | PTEFlags::U | PTEFlags::R;
+    if writable {
+        need |= PTEFlags::W;
+    }
+    VirtAddr::from(start)
+        .page_range(len)
+        .into_iter()
+        .all(|vpn| {
+            memory_set
+                .translate(vpn)
+                .map_or(false, |pte| pte.flags().contains(need))
+        })
+}
+
+/// Load a `\0`-terminated string from `memory_set` into kernel space, without
+/// the `\0`. Returns `None` if it runs into a page the user cannot read.
+pub fn translated_str(memory_set: &mut MemorySet, ptr: *const u8) -> Option<String> {
+    let page_table = PageTable::from_token(memory_set.token());
     let mut string = String::new();
     let mut va = ptr as usize;
     loop {
+        // check each page once, when the string enters it
+        if (va == ptr as usize || va % PAGE_SIZE == 0)
+            && !user_buffer_valid(memory_set, va as *const u8, 1, false)
+        {
+            return None;
+        }
         let ch: u8 = *(page_table
             .translate_va(VirtAddr::from(va))
             .unwrap()
@@ -175,17 +310,12 @@ pub fn translated_str(token: usize, ptr: *const u8) -> String {
         string.push(ch as char);
         va += 1;
     }
-    string
-}
-
-pub fn translated_ref<T>(token: usize, ptr: *const T) -> &'static T {
-    let page_table = PageTable::from_token(token);
-    page_table
-        .translate_va(VirtAddr::from(ptr as usize))
-        .unwrap()
-        .get_ref()
+    Some(string)
 }
 
+/// A reference to `ptr` in another address space. Only for memory the kernel
+/// has mapped itself, such as the argument area `exec` builds; pointers from
+/// the user go through `copy_to_user` and `copy_from_user`.
 pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> &'static mut T {
     let page_table = PageTable::from_token(token);
     let va = ptr as usize;
@@ -195,6 +325,44 @@ pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> &'static mut T {
         .get_mut()
 }
 
+/// Copy `value` to `ptr` in another address space, even if it straddles a
+/// page boundary. Returns false without copying anything if the destination
+/// is not mapped writable for the user.
+pub fn copy_to_user<T: Copy>(memory_set: &mut MemorySet, ptr: *mut T, value: &T) -> bool {
+    let len = core::mem::size_of::<T>();
+    // an empty copy would "succeed" without the user seeing anything
+    assert_ne!(len, 0, "copy_to_user of a zero-sized type");
+    if !user_buffer_valid(memory_set, ptr as *const u8, len, true) {
+        return false;
+    }
+    let bytes = unsafe { core::slice::from_raw_parts(value as *const T as *const u8, len) };
+    let mut copied = 0;
+    for slice in translated_byte_buffer(memory_set.token(), ptr as *const u8, len) {
+        slice.copy_from_slice(&bytes[copied..copied + slice.len()]);
+        copied += slice.len();
+    }
+    true
+}
+
+/// Read a `T` from `ptr` in another address space, even if it straddles a
+/// page boundary. Returns `None` if the source is not mapped readable for
+/// the user.
+pub fn copy_from_user<T: Copy>(memory_set: &mut MemorySet, ptr: *const T) -> Option<T> {
+    let len = core::mem::size_of::<T>();
+    assert_ne!(len, 0, "copy_from_user of a zero-sized type");
+    if !user_buffer_valid(memory_set, ptr as *const u8, len, false) {
+        return None;
+    }
+    let mut value = MaybeUninit::<T>::uninit();
+    let bytes = unsafe { core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, len) };
+    let mut copied = 0;
+    for slice in translated_byte_buffer(memory_set.token(), ptr as *const u8, len) {
+        bytes[copied..copied + slice.len()].copy_from_slice(slice);
+        copied += slice.len();
+    }
+    Some(unsafe { value.assume_init() })
+}
+
 pub struct UserBuffer {
     pub buffers: Vec<&'static mut [u8]>,
 }
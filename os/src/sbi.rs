@@ -3,6 +3,15 @@ pub fn set_timer(timer: usize) {
     sbi_rt::set_timer(timer as _);
 }
 
+/// use sbi call to read a byte from the console, `None` if there is none yet
+#[allow(deprecated)]
+pub fn console_getchar() -> Option<u8> {
+    match sbi_rt::legacy::console_getchar() {
+        usize::MAX => None,
+        c => Some(c as u8),
+    }
+}
+
 /// use sbi call to shutdown the kernel
 pub fn shutdown(failure: bool) -> ! {
     use sbi_rt::{system_reset, NoReason, Shutdown, SystemFailure};
@@ -13,3 +22,10 @@ pub fn shutdown(failure: bool) -> ! {
     }
     unreachable!()
 }
+
+/// use sbi call to reset the machine to a cold boot
+pub fn reboot() -> ! {
+    use sbi_rt::{system_reset, ColdReboot, NoReason};
+    system_reset(ColdReboot, NoReason);
+    unreachable!()
+}
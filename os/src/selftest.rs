@@ -0,0 +1,505 @@
+//! Kernel self-tests, run before launching apps when `BOOT_SELF_TEST` is set.
+
+use crate::config::{
+    INIT_APP_NAME, KERNEL_STACK_RED_ZONE, MAX_KERNEL_TRAP_DEPTH, MMAP_BASE, PAGE_SIZE,
+};
+use crate::fdt;
+use crate::fs::{open_file, OpenFlags};
+use crate::lang_items::{perform, DebugCommand, PanicAction, PanicRegs, PanicTarget};
+use crate::logging::{LogRing, DMESG_LEN};
+use crate::mm::{
+    check_segment, frames_used, BitmapFrameAllocator, FrameAllocator, MapPermission, MemorySet,
+    PhysPageNum, StackFrameAllocator, VirtAddr,
+};
+use crate::sbi::shutdown;
+use crate::task::{idle, idle_time_us, init_app_name, kstack_alloc, TaskMetric};
+use crate::timer::{get_time_us, set_timeslice};
+use crate::trap::KernelTrapGuard;
+use alloc::vec::Vec;
+use core::arch::asm;
+use core::fmt::Write;
+use riscv::register::sstatus;
+
+type SelfTest = fn() -> Result<(), &'static str>;
+
+const SELF_TESTS: &[(&str, SelfTest)] = &[
+    ("heap", heap),
+    ("address alignment", address_alignment),
+    ("mmap round-trip", mmap_round_trip),
+    ("mmap coalescing", mmap_coalescing),
+    ("metric accounting", metric_accounting),
+    ("timer", timer),
+    ("idle wakeup", idle_wakeup),
+    ("kernel trap nesting", kernel_trap_nesting),
+    ("kernel stack guard", kernel_stack_guard),
+    ("init app", init_app),
+    ("elf segment bounds", elf_segment_bounds),
+    ("frame allocator kinds", frame_allocator_kinds),
+    ("device tree", device_tree),
+    ("panic debug commands", panic_debug_commands),
+    ("panic actions", panic_actions),
+    ("log ring boundaries", log_ring_boundaries),
+];
+
+pub fn run() {
+    let mut failed = 0;
+    for (name, test) in SELF_TESTS {
+        match test() {
+            Ok(()) => println!("[selftest] {} ... ok", name),
+            Err(msg) => {
+                println!("[selftest] {} ... FAILED: {}", name, msg);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        println!("[selftest] {} of {} failed", failed, SELF_TESTS.len());
+        shutdown(true);
+    }
+    println!("[selftest] all {} passed", SELF_TESTS.len());
+}
+
+fn heap() -> Result<(), &'static str> {
+    let v: Vec<usize> = (0..1000).collect();
+    if v.iter().sum::<usize>() != 999 * 1000 / 2 {
+        return Err("vec contents corrupted");
+    }
+    Ok(())
+}
+
+fn address_alignment() -> Result<(), &'static str> {
+    let boundary = VirtAddr::from(4 * PAGE_SIZE);
+    let before = VirtAddr::from(4 * PAGE_SIZE - 1);
+    let after = VirtAddr::from(4 * PAGE_SIZE + 1);
+    if !boundary.aligned() || before.aligned() || after.aligned() {
+        return Err("aligned() wrong next to a page boundary");
+    }
+    if boundary.floor() != boundary.ceil() || boundary.floor().0 != 4 {
+        return Err("floor/ceil of an aligned address differ");
+    }
+    if before.floor().0 != 3 || before.ceil().0 != 4 {
+        return Err("floor/ceil wrong just below a page boundary");
+    }
+    if after.floor().0 != 4 || after.ceil().0 != 5 {
+        return Err("floor/ceil wrong just above a page boundary");
+    }
+    let pages = |va: VirtAddr, len: usize| va.page_range(len).into_iter().count();
+    if pages(boundary, 0) != 0 || pages(boundary, PAGE_SIZE) != 1 {
+        return Err("page_range wrong for an aligned start");
+    }
+    if pages(before, 1) != 1 || pages(before, 2) != 2 {
+        return Err("page_range wrong across a page boundary");
+    }
+    Ok(())
+}
+
+fn mmap_round_trip() -> Result<(), &'static str> {
+    let used = frames_used();
+    let mut memory_set = MemorySet::new_bare();
+    let perm = MapPermission::R | MapPermission::W | MapPermission::U;
+    let start = memory_set
+        .mmap(0, 2 * PAGE_SIZE, perm, true)
+        .ok_or("mmap anywhere failed")?;
+    let second: VirtAddr = (usize::from(start) + PAGE_SIZE).into();
+    let present = |memory_set: &MemorySet| {
+        memory_set
+            .translate(second.floor())
+            .map_or(false, |pte| pte.is_valid())
+    };
+    if !present(&memory_set) {
+        return Err("mapped page not present");
+    }
+    if memory_set
+        .mmap(start.into(), PAGE_SIZE, perm, true)
+        .is_some()
+    {
+        return Err("overlapping fixed mmap accepted");
+    }
+    if !memory_set.munmap(start.into(), 2 * PAGE_SIZE) {
+        return Err("munmap failed");
+    }
+    if present(&memory_set) {
+        return Err("page still present after munmap");
+    }
+    drop(memory_set);
+    if frames_used() != used {
+        return Err("frames leaked");
+    }
+    Ok(())
+}
+
+fn mmap_coalescing() -> Result<(), &'static str> {
+    let mut memory_set = MemorySet::new_bare();
+    let rw = MapPermission::R | MapPermission::W | MapPermission::U;
+    let ro = MapPermission::R | MapPermission::U;
+    // a populated page after a lazy one
+    memory_set
+        .mmap(MMAP_BASE, PAGE_SIZE, rw, false)
+        .ok_or("first mmap failed")?;
+    memory_set
+        .mmap(MMAP_BASE + PAGE_SIZE, PAGE_SIZE, rw, true)
+        .ok_or("second mmap failed")?;
+    if memory_set.area_count() != 1 {
+        return Err("adjacent pages with the same prot not merged");
+    }
+    // filling a gap joins both neighbours
+    memory_set
+        .mmap(MMAP_BASE + 3 * PAGE_SIZE, PAGE_SIZE, rw, false)
+        .ok_or("third mmap failed")?;
+    memory_set
+        .mmap(MMAP_BASE + 2 * PAGE_SIZE, PAGE_SIZE, rw, false)
+        .ok_or("gap mmap failed")?;
+    if memory_set.area_count() != 1 {
+        return Err("gap not merged with both neighbours");
+    }
+    memory_set
+        .mmap(MMAP_BASE + 4 * PAGE_SIZE, PAGE_SIZE, ro, false)
+        .ok_or("read-only mmap failed")?;
+    if memory_set.area_count() != 2 {
+        return Err("pages with different prot merged");
+    }
+    // the merged area still splits on a partial munmap
+    if !memory_set.munmap(MMAP_BASE + PAGE_SIZE, PAGE_SIZE) || memory_set.area_count() != 3 {
+        return Err("merged area not split by munmap");
+    }
+    Ok(())
+}
+
+fn metric_accounting() -> Result<(), &'static str> {
+    let begin = get_time_us();
+    let mut metric = TaskMetric::new();
+    metric.switch_in();
+    metric.trap_return();
+    metric.trap_enter();
+    metric.switch_out();
+    let elapsed = get_time_us() - begin;
+    if metric.switches != 1 {
+        return Err("switch not counted");
+    }
+    if metric.user_time_us + metric.kernel_time_us > elapsed {
+        return Err("charged more time than elapsed");
+    }
+    Ok(())
+}
+
+fn timer() -> Result<(), &'static str> {
+    let a = get_time_us();
+    let b = get_time_us();
+    if b < a {
+        return Err("time went backwards");
+    }
+    let min = set_timeslice(0);
+    let max = set_timeslice(usize::MAX);
+    set_timeslice(10_000);
+    if min == 0 || max == usize::MAX {
+        return Err("timeslice not clamped");
+    }
+    Ok(())
+}
+
+/// The `SHUTDOWN_WHEN_IDLE == false` path of `run_tasks`: the hart must come
+/// back from `wfi` on the next timer tick and account the wait.
+fn idle_wakeup() -> Result<(), &'static str> {
+    let sie = sstatus::read().sie();
+    let before = idle_time_us();
+    // the first tick may already be pending, the second one is waited for
+    idle();
+    idle();
+    if !sie {
+        unsafe { sstatus::clear_sie() };
+    }
+    if idle_time_us() == before {
+        return Err("idle time not recorded");
+    }
+    Ok(())
+}
+
+fn kernel_trap_nesting() -> Result<(), &'static str> {
+    // stand in for traps that keep interrupting each other
+    let mut guards = Vec::new();
+    for _ in 0..MAX_KERNEL_TRAP_DEPTH {
+        guards.push(KernelTrapGuard::enter().ok_or("guard refused an allowed depth")?);
+    }
+    if KernelTrapGuard::enter().is_some() {
+        return Err("runaway nesting not caught");
+    }
+    guards.clear();
+    if KernelTrapGuard::enter().is_none() {
+        return Err("depth not restored when traps return");
+    }
+    Ok(())
+}
+
+/// Recurse with big frames until one lands in the red zone above `bottom`.
+#[inline(never)]
+extern "C" fn recurse_into_red_zone(bottom: usize) {
+    let mut frame = [0usize; 32];
+    for word in frame.iter_mut() {
+        unsafe { (word as *mut usize).write_volatile(usize::MAX) };
+    }
+    if frame.as_ptr() as usize >= bottom + KERNEL_STACK_RED_ZONE {
+        recurse_into_red_zone(bottom);
+    }
+    // keep the frame alive across the call
+    unsafe { frame.as_ptr().read_volatile() };
+}
+
+fn kernel_stack_guard() -> Result<(), &'static str> {
+    let kstack = kstack_alloc();
+    if kstack.overflowed() {
+        return Err("fresh kernel stack reported as overflowed");
+    }
+    // the check that panics on trap entry would fire for a task on this stack
+    unsafe {
+        asm!(
+            "mv s2, sp",
+            "mv sp, {top}",
+            "jalr {f}",
+            "mv sp, s2",
+            top = in(reg) kstack.get_top(),
+            f = in(reg) recurse_into_red_zone as usize,
+            in("a0") kstack.get_bottom(),
+            out("s2") _,
+            clobber_abi("C"),
+        );
+    }
+    if !kstack.overflowed() {
+        return Err("deep recursion into the red zone not caught");
+    }
+    Ok(())
+}
+
+fn init_app() -> Result<(), &'static str> {
+    let name = init_app_name();
+    if open_file(&name, OpenFlags::RDONLY).is_none() {
+        return Err("init app cannot be opened");
+    }
+    if open_file(INIT_APP_NAME, OpenFlags::RDONLY).is_some() && name != INIT_APP_NAME {
+        return Err("INIT_APP_NAME exists but was not chosen");
+    }
+    Ok(())
+}
+
+fn elf_segment_bounds() -> Result<(), &'static str> {
+    let file_len = 4 * PAGE_SIZE;
+    if check_segment(PAGE_SIZE, PAGE_SIZE, 0x10000, 2 * PAGE_SIZE, file_len).is_err() {
+        return Err("good segment rejected");
+    }
+    // synthetic bad headers: past the file end, overflowing offset, data
+    // larger than memory, overflowing or out of range addresses
+    let bad = [
+        (3 * PAGE_SIZE, 2 * PAGE_SIZE, 0x10000, 2 * PAGE_SIZE),
+        (usize::MAX, 2, 0x10000, PAGE_SIZE),
+        (0, 2 * PAGE_SIZE, 0x10000, PAGE_SIZE),
+        (0, PAGE_SIZE, usize::MAX - 1, PAGE_SIZE),
+        (0, PAGE_SIZE, MMAP_BASE, PAGE_SIZE),
+    ];
+    for (offset, file_size, vaddr, mem_size) in bad {
+        if check_segment(offset, file_size, vaddr, mem_size, file_len).is_ok() {
+            return Err("bad segment accepted");
+        }
+    }
+    Ok(())
+}
+
+/// Run one allocation pattern and record the free count after every step.
+/// Only frame numbers are handed around, the frames are never touched.
+fn free_counts<A: FrameAllocator>(allocator: &mut A) -> Result<Vec<usize>, &'static str> {
+    let mut counts = Vec::new();
+    let mut held: Vec<PhysPageNum> = Vec::new();
+    for _ in 0..40 {
+        held.push(allocator.alloc().ok_or("alloc failed")?);
+    }
+    counts.push(allocator.frames_free());
+    // free every third frame to leave holes
+    let mut i = 0;
+    held.retain(|&ppn| {
+        i += 1;
+        if i % 3 == 0 {
+            allocator.dealloc(ppn);
+            false
+        } else {
+            true
+        }
+    });
+    counts.push(allocator.frames_free());
+    held.extend(allocator.alloc_more(8).ok_or("alloc_more failed")?);
+    counts.push(allocator.frames_free());
+    for _ in 0..10 {
+        held.push(allocator.alloc().ok_or("alloc failed")?);
+    }
+    counts.push(allocator.frames_free());
+    let mut sorted = held.iter().map(|ppn| ppn.0).collect::<Vec<_>>();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != held.len() {
+        return Err("frame handed out twice");
+    }
+    for ppn in held {
+        allocator.dealloc(ppn);
+    }
+    counts.push(allocator.frames_free());
+    Ok(counts)
+}
+
+fn frame_allocator_kinds() -> Result<(), &'static str> {
+    // a range that does not fill the last bitmap word
+    let (l, r) = (PhysPageNum::from(0x1000), PhysPageNum::from(0x1000 + 100));
+    let mut stack = StackFrameAllocator::new();
+    stack.init(l, r);
+    let mut bitmap = BitmapFrameAllocator::new();
+    bitmap.init(l, r);
+    let stack_counts = free_counts(&mut stack)?;
+    if stack_counts != free_counts(&mut bitmap)? {
+        return Err("free counts differ between allocators");
+    }
+    if stack_counts.last() != Some(&100) || bitmap.frames_total() != 100 {
+        return Err("frames not all free again");
+    }
+    Ok(())
+}
+
+fn device_tree() -> Result<(), &'static str> {
+    // / { cpus { timebase-frequency = <10000000>; }; }
+    let structure: [u32; 12] = [1, 0, 1, 0x6370_7573, 0, 3, 4, 0, 10_000_000, 2, 2, 9];
+    let strings = b"timebase-frequency\0";
+    let struct_off = 40;
+    let strings_off = struct_off + structure.len() * 4;
+    let total = strings_off + strings.len();
+    let header = [
+        0xd00d_feed,
+        total,
+        struct_off,
+        strings_off,
+        0,
+        17,
+        16,
+        0,
+        strings.len(),
+        structure.len() * 4,
+    ];
+    let mut blob = Vec::new();
+    for word in header.iter().map(|&w| w as u32).chain(structure) {
+        blob.extend_from_slice(&word.to_be_bytes());
+    }
+    blob.extend_from_slice(strings);
+    let hz = fdt::property(&blob, "timebase-frequency").and_then(fdt::cell_value);
+    if hz != Some(10_000_000) {
+        return Err("timebase-frequency not found");
+    }
+    if fdt::property(&blob, "timebase").is_some() || fdt::property(&blob[..60], "cpus").is_some() {
+        return Err("found a property that is not there");
+    }
+    Ok(())
+}
+
+fn panic_debug_commands() -> Result<(), &'static str> {
+    let cases = [
+        ("regs", Some(DebugCommand::Regs)),
+        (" metrics ", Some(DebugCommand::Metrics)),
+        ("continue", Some(DebugCommand::Continue)),
+        ("c", Some(DebugCommand::Continue)),
+        ("", None),
+        ("reboot", None),
+    ];
+    for (line, command) in cases {
+        if DebugCommand::parse(line) != command {
+            return Err("debug prompt command parsed wrong");
+        }
+    }
+    Ok(())
+}
+
+/// Stands in for the machine: records what a `PanicAction` does, and types
+/// `input` on the console.
+struct PanicRecorder {
+    input: &'static [u8],
+    read: usize,
+    halts: usize,
+    reboots: usize,
+}
+
+impl PanicRecorder {
+    fn new(input: &'static [u8]) -> Self {
+        Self {
+            input,
+            read: 0,
+            halts: 0,
+            reboots: 0,
+        }
+    }
+}
+
+impl PanicTarget for PanicRecorder {
+    fn halt(&mut self) {
+        self.halts += 1;
+    }
+    fn reboot(&mut self) {
+        self.reboots += 1;
+    }
+    fn getchar(&mut self) -> Option<u8> {
+        // past the end of the input, keep typing "c" so a prompt that does
+        // not stop where it should still returns, with `read` too large
+        let c = match self.input.get(self.read) {
+            Some(&c) => c,
+            None if self.read % 2 == 0 => b'c',
+            None => b'\n',
+        };
+        self.read += 1;
+        Some(c)
+    }
+}
+
+fn panic_actions() -> Result<(), &'static str> {
+    let regs = PanicRegs::capture();
+    let mut halt = PanicRecorder::new(b"");
+    perform(PanicAction::Halt, &regs, &mut halt);
+    if halt.halts != 1 || halt.reboots != 0 || halt.read != 0 {
+        return Err("Halt did not just power off");
+    }
+    let mut reboot = PanicRecorder::new(b"");
+    perform(PanicAction::Reboot, &regs, &mut reboot);
+    if reboot.reboots != 1 || reboot.halts != 0 || reboot.read != 0 {
+        return Err("Reboot did not just reset");
+    }
+    const SESSION: &[u8] = b"regs\nbogus\nmetrics\ncontinue\n";
+    let mut shell = PanicRecorder::new(b"regs\nbogus\nmetrics\ncontinue\nregs\n");
+    perform(PanicAction::Shell, &regs, &mut shell);
+    if shell.read != SESSION.len() {
+        return Err("debug prompt did not stop at continue");
+    }
+    if shell.halts != 1 || shell.reboots != 0 {
+        return Err("Shell did not power off after the prompt");
+    }
+    Ok(())
+}
+
+fn log_ring_boundaries() -> Result<(), &'static str> {
+    let mut ring = LogRing::new();
+    ring.write_str("aaa\nbbb\n").unwrap();
+    if ring.tail(4) != b"bbb\n" {
+        return Err("a cut right after a line end dropped the next line");
+    }
+    if ring.tail(5) != b"bbb\n" {
+        return Err("a cut inside a line kept part of it");
+    }
+    if ring.tail(8) != b"aaa\nbbb\n" {
+        return Err("a read of everything lost a line");
+    }
+    // lines of 16 bytes fill the ring exactly, so overwriting a whole line
+    // leaves the oldest kept byte at a line start
+    const LINE: &str = "0123456789abcde\n";
+    const LINES: usize = DMESG_LEN / LINE.len();
+    ring.clear();
+    for _ in 0..=LINES {
+        ring.write_str(LINE).unwrap();
+    }
+    if ring.tail(DMESG_LEN).len() != DMESG_LEN {
+        return Err("wraparound right after a line end dropped a line");
+    }
+    ring.write_str("x").unwrap();
+    if ring.tail(DMESG_LEN).len() != (LINES - 1) * LINE.len() + 1 {
+        return Err("wraparound inside a line kept part of it");
+    }
+    Ok(())
+}
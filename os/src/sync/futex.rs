@@ -0,0 +1,60 @@
+use crate::sync::UPIntrFreeCell;
+use crate::task::{block_current_and_run_next, current_task, wakeup_task, TaskControlBlock};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use lazy_static::*;
+
+lazy_static! {
+    /// Tasks blocked in `futex_wait`, by the physical address of the futex
+    /// word, so that threads and processes sharing a frame meet in one queue.
+    static ref FUTEX_QUEUES: UPIntrFreeCell<BTreeMap<usize, VecDeque<Arc<TaskControlBlock>>>> =
+        unsafe { UPIntrFreeCell::new(BTreeMap::new()) };
+}
+
+/// Block the current task on the futex at physical address `key` if
+/// `should_wait` agrees, which runs with the queues locked so that no wake
+/// can slip in between. Returns false if it did not block.
+pub fn futex_wait<F: FnOnce() -> bool>(key: usize, should_wait: F) -> bool {
+    let mut queues = FUTEX_QUEUES.exclusive_access();
+    if !should_wait() {
+        return false;
+    }
+    queues
+        .entry(key)
+        .or_insert_with(VecDeque::new)
+        .push_back(current_task().unwrap());
+    drop(queues);
+    block_current_and_run_next();
+    true
+}
+
+/// Wake up to `n` tasks waiting on the futex at physical address `key`,
+/// longest waiting first. Returns how many were woken.
+pub fn futex_wake(key: usize, n: usize) -> usize {
+    let mut queues = FUTEX_QUEUES.exclusive_access();
+    let queue = match queues.get_mut(&key) {
+        Some(queue) => queue,
+        None => return 0,
+    };
+    let woken: VecDeque<_> = queue.drain(..n.min(queue.len())).collect();
+    if queue.is_empty() {
+        queues.remove(&key);
+    }
+    drop(queues);
+    let count = woken.len();
+    for task in woken {
+        wakeup_task(task);
+    }
+    count
+}
+
+/// Take every thread of process `pid` off the futex queues, when the process
+/// goes away. The queues outlive it and are keyed by physical address, so a
+/// wake on one of its freed frames, reused by anyone, would find dead tasks.
+pub fn futex_forget_process(pid: usize) {
+    let mut queues = FUTEX_QUEUES.exclusive_access();
+    for queue in queues.values_mut() {
+        queue.retain(|task| task.tgid != pid);
+    }
+    queues.retain(|_, queue| !queue.is_empty());
+}
@@ -1,9 +1,11 @@
 mod condvar;
+mod futex;
 mod mutex;
 mod semaphore;
 mod up;
 
 pub use condvar::Condvar;
+pub use futex::{futex_forget_process, futex_wait, futex_wake};
 pub use mutex::{Mutex, MutexBlocking, MutexSpin};
 pub use semaphore::Semaphore;
-pub use up::{UPIntrFreeCell, UPIntrRefMut};
+pub use up::{UPIntrFreeCell, UPIntrRefMut, UPSafeCellRaw};
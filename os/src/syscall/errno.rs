@@ -0,0 +1,33 @@
+use crate::mm::LoadError;
+
+/// Why a syscall failed, returned to user space as the negative Linux errno.
+/// Syscalls not converted yet still return -1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(isize)]
+pub enum KernelError {
+    /// the fd is not open, or not open for this kind of access
+    BadFd = -9,
+    /// the call would block, try again
+    Again = -11,
+    /// no memory left, or the range is not (entirely) mapped
+    NoMem = -12,
+    /// a user buffer is not mapped with the needed permission
+    Fault = -14,
+    /// an argument is malformed
+    Invalid = -22,
+}
+
+impl From<LoadError> for KernelError {
+    fn from(err: LoadError) -> Self {
+        match err {
+            LoadError::Invalid(_) => Self::Invalid,
+            LoadError::NoMem => Self::NoMem,
+        }
+    }
+}
+
+impl From<KernelError> for isize {
+    fn from(err: KernelError) -> Self {
+        err as isize
+    }
+}
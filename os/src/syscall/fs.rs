@@ -1,56 +1,146 @@
-use crate::fs::{make_pipe, open_file, OpenFlags};
-use crate::mm::{translated_byte_buffer, translated_refmut, translated_str, UserBuffer};
-use crate::task::{current_process, current_user_token};
+use super::errno::KernelError;
+use crate::drivers::flush_all;
+use crate::fs::{
+    app_id, app_names, flush_stdout, make_pipe, open_file, File, Null, OpenFlags, NULL_DEVICE,
+};
+use crate::mm::{
+    copy_from_user, copy_to_user, translated_byte_buffer, translated_str, user_buffer_valid,
+    UserBuffer,
+};
+use crate::task::{current_process, current_task};
+use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem::size_of;
 
+/// Longest prefix accepted by `sys_set_output_prefix`, in bytes.
+const OUTPUT_PREFIX_MAX: usize = 16;
+/// Modes of `sys_set_stdout_mode`.
+const STDOUT_UNBUFFERED: usize = 0;
+const STDOUT_LINE_BUFFERED: usize = 1;
+/// Most segments accepted by `sys_writev`, like Linux's `IOV_MAX`.
+const IOV_MAX: usize = 1024;
+
+/// One segment of a `sys_writev` call.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct IoVec {
+    base: usize,
+    len: usize,
+}
+
+/// Write `len` bytes from `buf` to `fd`. A zero `len` returns 0 at once
+/// once `fd` is known to be writable.
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
-    let token = current_user_token();
     let process = current_process();
-    let inner = process.inner_exclusive_access();
+    let mut inner = process.inner_exclusive_access();
+    if !user_buffer_valid(&mut inner.memory_set, buf, len, false) {
+        return KernelError::Fault.into();
+    }
+    let token = inner.memory_set.token();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return KernelError::BadFd.into();
     }
     if let Some(file) = &inner.fd_table[fd] {
         if !file.writable() {
-            return -1;
+            return KernelError::BadFd.into();
         }
         let file = file.clone();
         // release current task TCB manually to avoid multi-borrow
         drop(inner);
+        // nothing to write, don't wait for room in a full pipe
+        if len == 0 {
+            return 0;
+        }
         file.write(UserBuffer::new(translated_byte_buffer(token, buf, len))) as isize
     } else {
-        -1
+        KernelError::BadFd.into()
     }
 }
 
+/// Write the `iovcnt` segments described at `iov` to `fd`, in order. Nothing
+/// is written if any segment is not readable. Returns the total bytes written.
+pub fn sys_writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
+    if iovcnt > IOV_MAX {
+        return KernelError::Invalid.into();
+    }
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) if file.writable() => file.clone(),
+        _ => return KernelError::BadFd.into(),
+    };
+    let mut segments = Vec::new();
+    for i in 0..iovcnt {
+        let segment = match copy_from_user(&mut inner.memory_set, iov.wrapping_add(i)) {
+            Some(segment) => segment,
+            None => return KernelError::Fault.into(),
+        };
+        let base = segment.base as *const u8;
+        if !user_buffer_valid(&mut inner.memory_set, base, segment.len, false) {
+            return KernelError::Fault.into();
+        }
+        segments.push(segment);
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let mut written = 0;
+    for segment in segments {
+        written += file.write(UserBuffer::new(translated_byte_buffer(
+            token,
+            segment.base as *const u8,
+            segment.len,
+        )));
+    }
+    written as isize
+}
+
+/// Read up to `len` bytes from `fd` into `buf`. A zero `len` returns 0 at
+/// once, without waiting for data, once `fd` is known to be readable.
 pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
-    let token = current_user_token();
     let process = current_process();
-    let inner = process.inner_exclusive_access();
+    let mut inner = process.inner_exclusive_access();
+    if !user_buffer_valid(&mut inner.memory_set, buf, len, true) {
+        return KernelError::Fault.into();
+    }
+    let token = inner.memory_set.token();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return KernelError::BadFd.into();
     }
     if let Some(file) = &inner.fd_table[fd] {
         let file = file.clone();
         if !file.readable() {
-            return -1;
+            return KernelError::BadFd.into();
         }
         // release current task TCB manually to avoid multi-borrow
         drop(inner);
+        // nothing to read, don't wait for data in an empty pipe
+        if len == 0 {
+            return 0;
+        }
         file.read(UserBuffer::new(translated_byte_buffer(token, buf, len))) as isize
     } else {
-        -1
+        KernelError::BadFd.into()
     }
 }
 
+/// Open `path` in the root directory, or a `Null` for `NULL_DEVICE`.
 pub fn sys_open(path: *const u8, flags: u32) -> isize {
     let process = current_process();
-    let token = current_user_token();
-    let path = translated_str(token, path);
-    if let Some(inode) = open_file(path.as_str(), OpenFlags::from_bits(flags).unwrap()) {
+    let path = match translated_str(&mut process.inner_exclusive_access().memory_set, path) {
+        Some(path) => path,
+        None => return KernelError::Fault.into(),
+    };
+    let flags = OpenFlags::from_bits(flags).unwrap();
+    let file: Option<Arc<dyn File + Send + Sync>> = if path == NULL_DEVICE {
+        Some(Arc::new(Null::new(flags)))
+    } else {
+        open_file(path.as_str(), flags).map(|inode| inode as Arc<dyn File + Send + Sync>)
+    };
+    if let Some(file) = file {
         let mut inner = process.inner_exclusive_access();
         let fd = inner.alloc_fd();
-        inner.fd_table[fd] = Some(inode);
+        inner.fd_table[fd] = Some(file);
         fd as isize
     } else {
         -1
@@ -60,27 +150,76 @@ pub fn sys_open(path: *const u8, flags: u32) -> isize {
 pub fn sys_close(fd: usize) -> isize {
     let process = current_process();
     let mut inner = process.inner_exclusive_access();
-    if fd >= inner.fd_table.len() {
-        return -1;
-    }
-    if inner.fd_table[fd].is_none() {
-        return -1;
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return KernelError::BadFd.into();
     }
     inner.fd_table[fd].take();
     0
 }
 
+/// Write the names of the loadable apps into `buf`, one per line, cut off at
+/// `len` bytes. Returns the number of bytes written.
+pub fn sys_list_apps(buf: *mut u8, len: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !user_buffer_valid(&mut inner.memory_set, buf, len, true) {
+        return KernelError::Fault.into();
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let mut text = String::new();
+    for name in app_names() {
+        text.push_str(&name);
+        text.push('\n');
+    }
+    let bytes = &text.as_bytes()[..text.len().min(len)];
+    let mut written = 0;
+    for slice in translated_byte_buffer(token, buf, bytes.len()) {
+        slice.copy_from_slice(&bytes[written..written + slice.len()]);
+        written += slice.len();
+    }
+    written as isize
+}
+
+/// Id of the app called `name`, its position in the `sys_list_apps` list, or
+/// -1 if there is no such app. Lets a shell check a command before it forks.
+pub fn sys_lookup_app(name: *const u8) -> isize {
+    let process = current_process();
+    let name = match translated_str(&mut process.inner_exclusive_access().memory_set, name) {
+        Some(name) => name,
+        None => return KernelError::Fault.into(),
+    };
+    app_id(&name).map_or(-1, |id| id as isize)
+}
+
+/// Flush every subsystem registered as `Flushable`.
+pub fn sys_sync() -> isize {
+    flush_all();
+    0
+}
+
 pub fn sys_pipe(pipe: *mut usize) -> isize {
     let process = current_process();
-    let token = current_user_token();
     let mut inner = process.inner_exclusive_access();
+    // check the destination first, so a bad pointer leaks no fds
+    if !user_buffer_valid(
+        &mut inner.memory_set,
+        pipe as *const u8,
+        2 * size_of::<usize>(),
+        true,
+    ) {
+        return KernelError::Fault.into();
+    }
     let (pipe_read, pipe_write) = make_pipe();
     let read_fd = inner.alloc_fd();
     inner.fd_table[read_fd] = Some(pipe_read);
     let write_fd = inner.alloc_fd();
     inner.fd_table[write_fd] = Some(pipe_write);
-    *translated_refmut(token, pipe) = read_fd;
-    *translated_refmut(token, unsafe { pipe.add(1) }) = write_fd;
+    copy_to_user(
+        &mut inner.memory_set,
+        pipe as *mut [usize; 2],
+        &[read_fd, write_fd],
+    );
     0
 }
 
@@ -97,3 +236,53 @@ pub fn sys_dup(fd: usize) -> isize {
     inner.fd_table[new_fd] = Some(Arc::clone(inner.fd_table[fd].as_ref().unwrap()));
     new_fd as isize
 }
+
+/// Set the prefix written before each line the current thread prints to
+/// stdout. `len == 0` removes it.
+pub fn sys_set_output_prefix(ptr: *const u8, len: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if len > OUTPUT_PREFIX_MAX || !user_buffer_valid(&mut inner.memory_set, ptr, len, false) {
+        return -1;
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let mut bytes = Vec::new();
+    for slice in translated_byte_buffer(token, ptr, len) {
+        bytes.extend_from_slice(slice);
+    }
+    let prefix = match String::from_utf8(bytes) {
+        Ok(prefix) if prefix.is_empty() => None,
+        Ok(prefix) => Some(prefix),
+        Err(_) => return -1,
+    };
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .stdout_prefix = prefix;
+    0
+}
+
+/// Switch the current thread's stdout between unbuffered and line-buffered
+/// output, returning the previous mode. Leaving line-buffered mode prints
+/// the incomplete line held back.
+pub fn sys_set_stdout_mode(mode: usize) -> isize {
+    let line_buffered = match mode {
+        STDOUT_UNBUFFERED => false,
+        STDOUT_LINE_BUFFERED => true,
+        _ => return KernelError::Invalid.into(),
+    };
+    let task = current_task().unwrap();
+    let was_line_buffered = core::mem::replace(
+        &mut task.inner_exclusive_access().stdout_line_buffered,
+        line_buffered,
+    );
+    if !line_buffered {
+        flush_stdout(&task);
+    }
+    if was_line_buffered {
+        STDOUT_LINE_BUFFERED as isize
+    } else {
+        STDOUT_UNBUFFERED as isize
+    }
+}
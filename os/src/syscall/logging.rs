@@ -0,0 +1,68 @@
+use crate::fs::{reset_stdout_log, stdout_log};
+use crate::logging::{dmesg, reset_dmesg, set_level};
+use crate::mm::{translated_byte_buffer, user_buffer_valid};
+use crate::task::{current_process, current_task, reset_sched_trace};
+use alloc::vec::Vec;
+
+const BUFFER_DMESG: usize = 0;
+const BUFFER_SCHED_TRACE: usize = 1;
+const BUFFER_STDOUT_LOG: usize = 2;
+
+pub fn sys_set_log_level(level: usize) -> isize {
+    match set_level(level) {
+        Some(prev) => prev as isize,
+        None => -1,
+    }
+}
+
+/// Copy the most recent kernel log lines, at most `len` bytes, into `buf`.
+/// Returns the number of bytes copied.
+pub fn sys_dmesg(buf: *mut u8, len: usize) -> isize {
+    copy_log(buf, len, dmesg)
+}
+
+/// Copy the most recent lines apps wrote to the console, at most `len`
+/// bytes, into `buf`. Returns the number of bytes copied.
+pub fn sys_stdout_log(buf: *mut u8, len: usize) -> isize {
+    copy_log(buf, len, stdout_log)
+}
+
+/// Copy what `tail` returns for at most `len` bytes into `buf`.
+fn copy_log(buf: *mut u8, len: usize, tail: fn(usize) -> Vec<u8>) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !user_buffer_valid(&mut inner.memory_set, buf, len, true) {
+        return -1;
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let bytes = tail(len);
+    let mut written = 0;
+    for slice in translated_byte_buffer(token, buf, bytes.len()) {
+        slice.copy_from_slice(&bytes[written..written + slice.len()]);
+        written += slice.len();
+    }
+    written as isize
+}
+
+/// Empty one of the in-kernel debug buffers, so that a later read only sees
+/// what happened after this call.
+pub fn sys_reset_buffer(which: usize) -> isize {
+    match which {
+        BUFFER_DMESG => reset_dmesg(),
+        BUFFER_SCHED_TRACE => reset_sched_trace(),
+        BUFFER_STDOUT_LOG => reset_stdout_log(),
+        _ => return -1,
+    }
+    0
+}
+
+/// Log every syscall the calling thread makes from now on, with its
+/// arguments and return value, at info level. `enable == 0` stops it.
+pub fn sys_trace_syscalls(enable: usize) -> isize {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .trace_syscalls = enable != 0;
+    0
+}
@@ -0,0 +1,184 @@
+use super::errno::KernelError;
+use crate::config::{MEMBENCH_MAX_SIZE, MEMBENCH_RUNS};
+use crate::mm::{copy_to_user, translated_byte_buffer, user_buffer_valid};
+use crate::task::{
+    current_process, current_task, sched_trace_text, switch_count, tasks_created, Profile,
+    ProfileBucket, RUsage, SysInfo, SyscallLatency, TaskInfo, Tms,
+};
+use crate::timer::{clock_freq, get_time};
+use crate::trap::trap_stats;
+use alloc::vec;
+use core::arch::asm;
+use riscv::register::sstatus;
+
+pub fn sys_task_info(info: *mut TaskInfo) -> isize {
+    let process = current_process();
+    let task = current_task().unwrap();
+    let task_info = TaskInfo::from(&task.inner_exclusive_access().metric);
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, info, &task_info) {
+        return -1;
+    }
+    0
+}
+
+/// Copy the current thread's resource usage into `usage`.
+pub fn sys_getrusage(usage: *mut RUsage) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let max_rss_pages = process_inner.update_max_rss();
+    let working_set_pages = process_inner.working_set_pages;
+    drop(process_inner);
+    let task = current_task().unwrap();
+    let rusage = RUsage::new(
+        &task.inner_exclusive_access().metric,
+        max_rss_pages,
+        working_set_pages,
+    );
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, usage, &rusage) {
+        return -1;
+    }
+    0
+}
+
+/// Copy the current thread's user and kernel time, in `CLOCKS_PER_SEC`
+/// ticks, into `buf`.
+pub fn sys_times(buf: *mut Tms) -> isize {
+    let process = current_process();
+    let task = current_task().unwrap();
+    let tms = Tms::from(&task.inner_exclusive_access().metric);
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, buf, &tms) {
+        return -1;
+    }
+    0
+}
+
+pub fn sys_switch_count() -> isize {
+    switch_count() as isize
+}
+
+/// Copy the system-wide uptime, idle time and task counters into `info`.
+pub fn sys_sysinfo(info: *mut SysInfo) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, info, &SysInfo::current()) {
+        return -1;
+    }
+    0
+}
+
+pub fn sys_tasks_created() -> isize {
+    tasks_created() as isize
+}
+
+/// Copy up to `len` per-syscall latency entries of the current task, ordered
+/// by syscall id, into `buf`. Returns the number of entries copied, or -1 if
+/// `buf` is not writable.
+pub fn sys_syscall_latency(buf: *mut SyscallLatency, len: usize) -> isize {
+    let process = current_process();
+    let task = current_task().unwrap();
+    let task_inner = task.inner_exclusive_access();
+    let mut inner = process.inner_exclusive_access();
+    let mut copied = 0;
+    for latency in task_inner.metric.syscalls.values().take(len) {
+        if !copy_to_user(&mut inner.memory_set, unsafe { buf.add(copied) }, latency) {
+            return -1;
+        }
+        copied += 1;
+    }
+    copied as isize
+}
+
+/// Copy the text dump of recent scheduling decisions into `buf`, truncated
+/// to `len` bytes. Returns the number of bytes copied.
+pub fn sys_sched_trace(buf: *mut u8, len: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !user_buffer_valid(&mut inner.memory_set, buf, len, true) {
+        return -1;
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let text = sched_trace_text();
+    let bytes = &text.as_bytes()[..text.len().min(len)];
+    let mut written = 0;
+    for slice in translated_byte_buffer(token, buf, bytes.len()) {
+        slice.copy_from_slice(&bytes[written..written + slice.len()]);
+        written += slice.len();
+    }
+    written as isize
+}
+
+/// Copy up to `len` counters of traps from user space since boot, one per
+/// cause in the order of the `TRAP_*` slots, into `buf`. Returns the number
+/// of counters copied, or -1 if `buf` is not writable.
+pub fn sys_trap_stats(buf: *mut usize, len: usize) -> isize {
+    let process = current_process();
+    let stats = trap_stats();
+    let mut inner = process.inner_exclusive_access();
+    let mut copied = 0;
+    for count in stats.iter().take(len) {
+        if !copy_to_user(&mut inner.memory_set, unsafe { buf.add(copied) }, count) {
+            return -1;
+        }
+        copied += 1;
+    }
+    copied as isize
+}
+
+/// Sample the calling thread's user PC every `interval_ms`, or on every
+/// timer tick if that is longer. Starting again discards the samples so far.
+pub fn sys_profile_start(interval_ms: usize) -> isize {
+    current_task().unwrap().inner_exclusive_access().profile = Some(Profile::new(interval_ms));
+    0
+}
+
+/// Stop sampling and copy up to `len` buckets, most samples first, into
+/// `buf`. Returns the number of buckets copied, or -1 if the thread was not
+/// being profiled or `buf` is not writable.
+pub fn sys_profile_stop(buf: *mut ProfileBucket, len: usize) -> isize {
+    let process = current_process();
+    let task = current_task().unwrap();
+    let profile = match task.inner_exclusive_access().profile.take() {
+        Some(profile) => profile,
+        None => return -1,
+    };
+    let mut inner = process.inner_exclusive_access();
+    let mut copied = 0;
+    for bucket in profile.top().iter().take(len) {
+        if !copy_to_user(&mut inner.memory_set, unsafe { buf.add(copied) }, bucket) {
+            return -1;
+        }
+        copied += 1;
+    }
+    copied as isize
+}
+
+/// Time a copy of `size` bytes between two kernel heap buffers. Returns the
+/// bandwidth in MB/s of the fastest of `MEMBENCH_RUNS` copies, or `Invalid`
+/// unless `0 < size <= MEMBENCH_MAX_SIZE`.
+pub fn sys_membench(size: usize) -> isize {
+    if size == 0 || size > MEMBENCH_MAX_SIZE {
+        return KernelError::Invalid.into();
+    }
+    let src = vec![0x5au8; size];
+    let mut dst = vec![0u8; size];
+    let mut best = usize::MAX;
+    for _ in 0..MEMBENCH_RUNS {
+        // an interrupt inside the timed window would be counted as copying
+        let sie = sstatus::read().sie();
+        unsafe { sstatus::clear_sie() };
+        let start = get_time();
+        dst.copy_from_slice(&src);
+        // dst escapes here, so the copy cannot be optimized out
+        unsafe { asm!("", in(reg) dst.as_ptr(), options(nostack)) };
+        let ticks = get_time() - start;
+        if sie {
+            unsafe { sstatus::set_sie() };
+        }
+        best = best.min(ticks.max(1));
+    }
+    (size * clock_freq() / best / 1_000_000) as isize
+}
@@ -0,0 +1,234 @@
+use super::errno::KernelError;
+use crate::config::{MMAP_BASE, MMAP_TOP, PAGE_SIZE};
+use crate::fs::{open_file, OpenFlags};
+use crate::mm::{
+    copy_to_user, translated_byte_buffer, user_buffer_valid, MapPermission, MemInfo, PageTable,
+};
+use crate::task::current_process;
+use alloc::string::String;
+use alloc::vec;
+use core::arch::asm;
+use core::fmt::Write;
+
+const FENCE_RW: usize = 0;
+const FENCE_I: usize = 1;
+const FENCE_VMA: usize = 2;
+
+/// Not a protection bit: allocate all frames now instead of on first touch.
+const MMAP_POPULATE: usize = 1 << 3;
+
+/// `sys_madvise` advice: the range is not needed, free its frames.
+const MADV_DONTNEED: usize = 4;
+
+/// `sys_prlimit` resource: pages `mmap` may have added at a time.
+const RLIMIT_MMAP_PAGES: usize = 0;
+
+/// Whether `[start, start + len)` is a non-empty, page aligned range that
+/// user mappings may use, inside `[MMAP_BASE, MMAP_TOP)`.
+fn range_valid(start: usize, len: usize) -> bool {
+    len != 0
+        && start % PAGE_SIZE == 0
+        && start >= MMAP_BASE
+        && matches!(start.checked_add(len), Some(end) if end <= MMAP_TOP)
+}
+
+/// Whether mapping `len` more bytes keeps a process with `mapped` pages
+/// from `mmap` within `limit` pages.
+fn within_mmap_limit(mapped: usize, limit: usize, len: usize) -> bool {
+    let pages = len / PAGE_SIZE + (len % PAGE_SIZE != 0) as usize;
+    mapped.saturating_add(pages) <= limit
+}
+
+/// Map `len` bytes with `prot` (bit 0 R, bit 1 W, bit 2 X, plus
+/// `MMAP_POPULATE`) at `start`, or at an address picked by the kernel if
+/// `start` is 0. Returns the mapped address, or `NoMem` if there is no room
+/// for it or the mapping would exceed `RLIMIT_MMAP_PAGES`.
+pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
+    if prot & !(0x7 | MMAP_POPULATE) != 0 || prot & 0x7 == 0 {
+        return KernelError::Invalid.into();
+    }
+    if len == 0 || (start != 0 && !range_valid(start, len)) {
+        return KernelError::Invalid.into();
+    }
+    let perm = MapPermission::from_bits(((prot & 0x7) << 1) as u8).unwrap() | MapPermission::U;
+    let populate = prot & MMAP_POPULATE != 0;
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !within_mmap_limit(inner.memory_set.mmap_pages(), inner.mmap_page_limit, len) {
+        return KernelError::NoMem.into();
+    }
+    match inner.memory_set.mmap(start, len, perm, populate) {
+        Some(start_va) => {
+            inner.update_max_rss();
+            usize::from(start_va) as isize
+        }
+        None => KernelError::NoMem.into(),
+    }
+}
+
+/// Advise the kernel how `[start, start + len)` will be used. `MADV_DONTNEED`
+/// frees the frames behind the range but keeps it mapped, so it reads as
+/// zeroes afterwards; it returns `NoMem` if part of the range is not mapped.
+/// The other advices of Linux, up to `MADV_WILLNEED`, are accepted and
+/// ignored.
+pub fn sys_madvise(start: usize, len: usize, advice: usize) -> isize {
+    if !range_valid(start, len) || advice > MADV_DONTNEED {
+        return KernelError::Invalid.into();
+    }
+    if advice != MADV_DONTNEED {
+        return 0;
+    }
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if inner.memory_set.drop_frames(start, len) {
+        0
+    } else {
+        KernelError::NoMem.into()
+    }
+}
+
+/// Set the limit on `resource` to `new_limit`, `RLIM_INFINITY` for none, and
+/// write the previous one to `old_limit` unless it is null. Pages already
+/// mapped stay mapped when the limit drops below them.
+pub fn sys_prlimit(resource: usize, new_limit: usize, old_limit: *mut usize) -> isize {
+    if resource != RLIMIT_MMAP_PAGES {
+        return KernelError::Invalid.into();
+    }
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let old = inner.mmap_page_limit;
+    if !old_limit.is_null() && !copy_to_user(&mut inner.memory_set, old_limit, &old) {
+        return KernelError::Fault.into();
+    }
+    inner.mmap_page_limit = new_limit;
+    0
+}
+
+/// Map a private copy of `[offset, offset + len)` of the calling app's ELF
+/// file at an address picked by the kernel, with `prot` as for `sys_mmap`.
+/// `offset` must be page aligned. Returns the mapped address, `Invalid` if
+/// the range is not inside the file, or `NoMem` if there is no room for it.
+pub fn sys_mmap_data(offset: usize, len: usize, prot: usize) -> isize {
+    if prot & !0x7 != 0 || prot == 0 || len == 0 || offset % PAGE_SIZE != 0 {
+        return KernelError::Invalid.into();
+    }
+    let perm = MapPermission::from_bits((prot << 1) as u8).unwrap() | MapPermission::U;
+    let process = current_process();
+    // reading the file may block on the disk, so it happens unlocked
+    let app_name = process.inner_exclusive_access().app_name.clone();
+    let image = match open_file(app_name.as_str(), OpenFlags::RDONLY) {
+        Some(image) => image,
+        None => return KernelError::Invalid.into(),
+    };
+    // probe the last byte before allocating, so a huge len cannot exhaust
+    // the kernel heap
+    let last = match offset.checked_add(len - 1) {
+        Some(last) => last,
+        None => return KernelError::Invalid.into(),
+    };
+    if image.read_at(last, &mut [0u8; 1]) != 1 {
+        return KernelError::Invalid.into();
+    }
+    let mut data = vec![0u8; len];
+    if image.read_at(offset, &mut data) != len {
+        return KernelError::Invalid.into();
+    }
+    let mut inner = process.inner_exclusive_access();
+    if !within_mmap_limit(inner.memory_set.mmap_pages(), inner.mmap_page_limit, len) {
+        return KernelError::NoMem.into();
+    }
+    let start = match inner.memory_set.mmap(0, len, perm, true) {
+        Some(start_va) => usize::from(start_va),
+        None => return KernelError::NoMem.into(),
+    };
+    let mut copied = 0;
+    for slice in translated_byte_buffer(inner.memory_set.token(), start as *const u8, len) {
+        slice.copy_from_slice(&data[copied..copied + slice.len()]);
+        copied += slice.len();
+    }
+    inner.update_max_rss();
+    start as isize
+}
+
+/// Unmap `[start, start + len)`. Returns `NoMem` if part of it is not mapped.
+pub fn sys_munmap(start: usize, len: usize) -> isize {
+    if !range_valid(start, len) {
+        return KernelError::Invalid.into();
+    }
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if inner.memory_set.munmap(start, len) {
+        0
+    } else {
+        KernelError::NoMem.into()
+    }
+}
+
+/// Change the protection of `[start, start + len)` to `prot` (bit 0 R,
+/// bit 1 W, bit 2 X). Returns `NoMem` if any page in the range is not mapped,
+/// `Invalid` if `prot` asks for write without read, which RISC-V page tables
+/// reserve.
+pub fn sys_mprotect(start: usize, len: usize, prot: usize) -> isize {
+    if prot & !0x7 != 0 || prot == 0 || prot & 0x3 == 0x2 || !range_valid(start, len) {
+        return KernelError::Invalid.into();
+    }
+    let perm = MapPermission::from_bits((prot << 1) as u8).unwrap() | MapPermission::U;
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if inner.memory_set.mprotect(start, len, perm) {
+        0
+    } else {
+        KernelError::NoMem.into()
+    }
+}
+
+pub fn sys_meminfo(info: *mut MemInfo) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, info, &MemInfo::current()) {
+        return -1;
+    }
+    0
+}
+
+/// Run `fence rw, rw`, `fence.i` or `sfence.vma` on behalf of the caller.
+pub fn sys_fence(kind: usize) -> isize {
+    unsafe {
+        match kind {
+            FENCE_RW => asm!("fence rw, rw"),
+            FENCE_I => asm!("fence.i"),
+            FENCE_VMA => asm!("sfence.vma"),
+            _ => return -1,
+        }
+    }
+    0
+}
+
+/// Write the current page table into `buf`, one `vpn -> ppn flags` line per
+/// mapped page, flags as `VRWXUGAD` with `-` for unset bits. The listing is
+/// cut off at `len` bytes. Returns the number of bytes written.
+pub fn sys_dump_pagetable(buf: *mut u8, len: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !user_buffer_valid(&mut inner.memory_set, buf, len, true) {
+        return -1;
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let mut text = String::new();
+    for (vpn, pte) in PageTable::from_token(token).mappings() {
+        let flags: String = "VRWXUGAD"
+            .chars()
+            .enumerate()
+            .map(|(bit, c)| if pte.bits & (1 << bit) != 0 { c } else { '-' })
+            .collect();
+        writeln!(text, "{:#x} -> {:#x} {}", vpn.0, pte.ppn().0, flags).unwrap();
+    }
+    let bytes = &text.as_bytes()[..text.len().min(len)];
+    let mut written = 0;
+    for slice in translated_byte_buffer(token, buf, bytes.len()) {
+        slice.copy_from_slice(&bytes[written..written + slice.len()]);
+        written += slice.len();
+    }
+    written as isize
+}
@@ -7,14 +7,28 @@ const SYSCALL_CLOSE: usize = 57;
 const SYSCALL_PIPE: usize = 59;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_WRITEV: usize = 66;
+const SYSCALL_SYNC: usize = 81;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_EXIT_GROUP: usize = 94;
 const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_SETALARM: usize = 103;
+const SYSCALL_CLOCK_GETRES: usize = 114;
+const SYSCALL_SLEEP_UNTIL: usize = 115;
 const SYSCALL_YIELD: usize = 124;
 const SYSCALL_KILL: usize = 129;
+const SYSCALL_SIGRETURN: usize = 139;
+const SYSCALL_TIMES: usize = 153;
+const SYSCALL_GETRUSAGE: usize = 165;
 const SYSCALL_GET_TIME: usize = 169;
 const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
+const SYSCALL_MUNMAP: usize = 215;
 const SYSCALL_FORK: usize = 220;
 const SYSCALL_EXEC: usize = 221;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_MPROTECT: usize = 226;
+const SYSCALL_MADVISE: usize = 233;
 const SYSCALL_WAITPID: usize = 260;
 const SYSCALL_THREAD_CREATE: usize = 1000;
 const SYSCALL_GETTID: usize = 1001;
@@ -28,29 +42,97 @@ const SYSCALL_SEMAPHORE_DOWN: usize = 1022;
 const SYSCALL_CONDVAR_CREATE: usize = 1030;
 const SYSCALL_CONDVAR_SIGNAL: usize = 1031;
 const SYSCALL_CONDVAR_WAIT: usize = 1032;
+const SYSCALL_FUTEX_WAIT: usize = 1040;
+const SYSCALL_FUTEX_WAKE: usize = 1041;
 const SYSCALL_FRAMEBUFFER: usize = 2000;
 const SYSCALL_FRAMEBUFFER_FLUSH: usize = 2001;
 const SYSCALL_EVENT_GET: usize = 3000;
 const SYSCALL_KEY_PRESSED: usize = 3001;
+const SYSCALL_TASK_INFO: usize = 4000;
+const SYSCALL_SWITCH_COUNT: usize = 4001;
+const SYSCALL_SET_LOG_LEVEL: usize = 4002;
+const SYSCALL_CURRENT_APP_INFO: usize = 4003;
+const SYSCALL_MEMINFO: usize = 4004;
+const SYSCALL_SET_TIMESLICE: usize = 4005;
+const SYSCALL_TASKS_CREATED: usize = 4006;
+const SYSCALL_SYSCALL_LATENCY: usize = 4007;
+const SYSCALL_SCHED_TRACE: usize = 4008;
+const SYSCALL_FENCE: usize = 4009;
+const SYSCALL_EXIT_REASON: usize = 4010;
+const SYSCALL_DMESG: usize = 4011;
+const SYSCALL_SET_OUTPUT_PREFIX: usize = 4012;
+const SYSCALL_YIELD_DEADLINE: usize = 4013;
+const SYSCALL_RESET_BUFFER: usize = 4014;
+const SYSCALL_PAUSE: usize = 4016;
+const SYSCALL_YIELD_DONATE: usize = 4017;
+const SYSCALL_SPAWN: usize = 4018;
+const SYSCALL_SETENV: usize = 4019;
+const SYSCALL_GETENV: usize = 4020;
+const SYSCALL_DUMP_PAGETABLE: usize = 4021;
+const SYSCALL_POWEROFF: usize = 4022;
+const SYSCALL_REBOOT: usize = 4023;
+const SYSCALL_LIST_APPS: usize = 4024;
+const SYSCALL_SET_SCHEDULER: usize = 4025;
+const SYSCALL_SYSINFO: usize = 4026;
+const SYSCALL_PRLIMIT: usize = 4027;
+const SYSCALL_TRAP_STATS: usize = 4028;
+const SYSCALL_MMAP_DATA: usize = 4029;
+const SYSCALL_GETCPU: usize = 4030;
+const SYSCALL_CHECKPOINT: usize = 4031;
+const SYSCALL_RESTORE: usize = 4032;
+const SYSCALL_SET_STDOUT_MODE: usize = 4033;
+const SYSCALL_PROFILE_START: usize = 4034;
+const SYSCALL_PROFILE_STOP: usize = 4035;
+const SYSCALL_PREEMPT_DISABLE: usize = 4036;
+const SYSCALL_PREEMPT_ENABLE: usize = 4037;
+const SYSCALL_MEMBENCH: usize = 4038;
+const SYSCALL_TRACE_SYSCALLS: usize = 4039;
+const SYSCALL_SCHED_SETSCHEDULER: usize = 4040;
+const SYSCALL_LOOKUP_APP: usize = 4041;
+const SYSCALL_EXIT_PROCESS: usize = 4042;
+const SYSCALL_DROP_PRIVILEGE: usize = 4043;
+const SYSCALL_STDOUT_LOG: usize = 4044;
+#[cfg(feature = "testing")]
+const SYSCALL_FAULT_INJECT: usize = 4015;
 
+mod errno;
 mod fs;
 mod gui;
 mod input;
+mod logging;
+mod metric;
+mod mm;
 mod net;
 mod process;
 mod sync;
+#[cfg(feature = "testing")]
+mod testing;
 mod thread;
 
 use fs::*;
 use gui::*;
 use input::*;
+use logging::*;
+use metric::*;
+use mm::*;
 use net::*;
 use process::*;
 use sync::*;
+#[cfg(feature = "testing")]
+use testing::*;
 use thread::*;
 
+use crate::mm::MemInfo;
+use crate::task::{
+    current_add_signal, current_task, ProfileBucket, RUsage, SignalFlags, SysInfo, SyscallLatency,
+    TaskInfo, Tms,
+};
+use crate::timer::{get_time_us, TimeVal};
+use log::info;
+
 pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
-    match syscall_id {
+    let start_us = get_time_us();
+    let ret = match syscall_id {
         SYSCALL_DUP => sys_dup(args[0]),
         SYSCALL_CONNECT => sys_connect(args[0] as _, args[1] as _, args[2] as _),
         SYSCALL_LISTEN => sys_listen(args[0] as _),
@@ -60,15 +142,29 @@ pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
         SYSCALL_PIPE => sys_pipe(args[0] as *mut usize),
         SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
         SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_WRITEV => sys_writev(args[0], args[1] as *const IoVec, args[2]),
+        SYSCALL_SYNC => sys_sync(),
         SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_EXIT_GROUP => sys_exit_group(args[0] as i32),
         SYSCALL_SLEEP => sys_sleep(args[0]),
-        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_SETALARM => sys_setalarm(args[0], args[1]),
+        SYSCALL_SLEEP_UNTIL => sys_sleep_until(args[0] as *const TimeVal),
+        SYSCALL_YIELD => sys_yield(args[0] as *mut usize),
         SYSCALL_KILL => sys_kill(args[0], args[1] as u32),
+        SYSCALL_SIGRETURN => sys_sigreturn(),
+        SYSCALL_TIMES => sys_times(args[0] as *mut Tms),
+        SYSCALL_GETRUSAGE => sys_getrusage(args[0] as *mut RUsage),
         SYSCALL_GET_TIME => sys_get_time(),
+        SYSCALL_CLOCK_GETRES => sys_clock_getres(args[0] as *mut usize),
         SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_GETPPID => sys_getppid(),
+        SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
         SYSCALL_FORK => sys_fork(),
         SYSCALL_EXEC => sys_exec(args[0] as *const u8, args[1] as *const usize),
-        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
+        SYSCALL_MPROTECT => sys_mprotect(args[0], args[1], args[2]),
+        SYSCALL_MADVISE => sys_madvise(args[0], args[1], args[2]),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32, args[2]),
         SYSCALL_THREAD_CREATE => sys_thread_create(args[0], args[1]),
         SYSCALL_GETTID => sys_gettid(),
         SYSCALL_WAITTID => sys_waittid(args[0]) as isize,
@@ -81,10 +177,81 @@ pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
         SYSCALL_CONDVAR_CREATE => sys_condvar_create(),
         SYSCALL_CONDVAR_SIGNAL => sys_condvar_signal(args[0]),
         SYSCALL_CONDVAR_WAIT => sys_condvar_wait(args[0], args[1]),
+        SYSCALL_FUTEX_WAIT => sys_futex_wait(args[0] as *const u32, args[1] as u32),
+        SYSCALL_FUTEX_WAKE => sys_futex_wake(args[0] as *const u32, args[1]),
         SYSCALL_FRAMEBUFFER => sys_framebuffer(),
         SYSCALL_FRAMEBUFFER_FLUSH => sys_framebuffer_flush(),
         SYSCALL_EVENT_GET => sys_event_get(),
         SYSCALL_KEY_PRESSED => sys_key_pressed(),
-        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+        SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
+        SYSCALL_SWITCH_COUNT => sys_switch_count(),
+        SYSCALL_SET_LOG_LEVEL => sys_set_log_level(args[0]),
+        SYSCALL_CURRENT_APP_INFO => sys_current_app_info(args[0] as *mut u8, args[1]),
+        SYSCALL_MEMINFO => sys_meminfo(args[0] as *mut MemInfo),
+        SYSCALL_SET_TIMESLICE => sys_set_timeslice(args[0]),
+        SYSCALL_TASKS_CREATED => sys_tasks_created(),
+        SYSCALL_SYSCALL_LATENCY => sys_syscall_latency(args[0] as *mut SyscallLatency, args[1]),
+        SYSCALL_SCHED_TRACE => sys_sched_trace(args[0] as *mut u8, args[1]),
+        SYSCALL_FENCE => sys_fence(args[0]),
+        SYSCALL_EXIT_REASON => sys_exit_reason(args[0]),
+        SYSCALL_DMESG => sys_dmesg(args[0] as *mut u8, args[1]),
+        SYSCALL_SET_OUTPUT_PREFIX => sys_set_output_prefix(args[0] as *const u8, args[1]),
+        SYSCALL_YIELD_DEADLINE => sys_yield_deadline(args[0]),
+        SYSCALL_RESET_BUFFER => sys_reset_buffer(args[0]),
+        SYSCALL_PAUSE => sys_pause(),
+        SYSCALL_YIELD_DONATE => sys_yield_donate(args[0]),
+        SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
+        SYSCALL_SETENV => sys_setenv(args[0] as *const u8, args[1] as *const u8),
+        SYSCALL_GETENV => sys_getenv(args[0] as *const u8, args[1] as *mut u8, args[2]),
+        SYSCALL_DUMP_PAGETABLE => sys_dump_pagetable(args[0] as *mut u8, args[1]),
+        SYSCALL_POWEROFF => sys_poweroff(args[0]),
+        SYSCALL_REBOOT => sys_reboot(),
+        SYSCALL_LIST_APPS => sys_list_apps(args[0] as *mut u8, args[1]),
+        SYSCALL_SET_SCHEDULER => sys_set_scheduler(args[0]),
+        SYSCALL_SYSINFO => sys_sysinfo(args[0] as *mut SysInfo),
+        SYSCALL_PRLIMIT => sys_prlimit(args[0], args[1], args[2] as *mut usize),
+        SYSCALL_TRAP_STATS => sys_trap_stats(args[0] as *mut usize, args[1]),
+        SYSCALL_MMAP_DATA => sys_mmap_data(args[0], args[1], args[2]),
+        SYSCALL_GETCPU => sys_getcpu(),
+        SYSCALL_CHECKPOINT => sys_checkpoint(),
+        SYSCALL_RESTORE => sys_restore(),
+        SYSCALL_SET_STDOUT_MODE => sys_set_stdout_mode(args[0]),
+        SYSCALL_PROFILE_START => sys_profile_start(args[0]),
+        SYSCALL_PROFILE_STOP => sys_profile_stop(args[0] as *mut ProfileBucket, args[1]),
+        SYSCALL_PREEMPT_DISABLE => sys_preempt_disable(),
+        SYSCALL_PREEMPT_ENABLE => sys_preempt_enable(),
+        SYSCALL_MEMBENCH => sys_membench(args[0]),
+        SYSCALL_TRACE_SYSCALLS => sys_trace_syscalls(args[0]),
+        SYSCALL_SCHED_SETSCHEDULER => sys_sched_setscheduler(args[0], args[1], args[2]),
+        SYSCALL_LOOKUP_APP => sys_lookup_app(args[0] as *const u8),
+        SYSCALL_EXIT_PROCESS => sys_exit_process(args[0] as i32),
+        SYSCALL_DROP_PRIVILEGE => sys_drop_privilege(),
+        SYSCALL_STDOUT_LOG => sys_stdout_log(args[0] as *mut u8, args[1]),
+        #[cfg(feature = "testing")]
+        SYSCALL_FAULT_INJECT => sys_fault_inject(args[0]),
+        _ => {
+            // kill the caller instead of the kernel
+            println!("[kernel] Unsupported syscall_id: {}", syscall_id);
+            current_add_signal(SignalFlags::SIGSYS);
+            -1
+        }
+    };
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner
+        .metric
+        .record_syscall(syscall_id, get_time_us() - start_us);
+    if task_inner.trace_syscalls {
+        info!(
+            "[strace] pid {} tid {}: syscall {}({:#x}, {:#x}, {:#x}) = {}",
+            task.tgid,
+            task_inner.res.as_ref().map_or(0, |res| res.tid),
+            syscall_id,
+            args[0],
+            args[1],
+            args[2],
+            ret
+        );
     }
+    ret
 }
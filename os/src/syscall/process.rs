@@ -1,35 +1,224 @@
+use super::errno::KernelError;
+use crate::drivers::flush_all;
 use crate::fs::{open_file, OpenFlags};
-use crate::mm::{translated_ref, translated_refmut, translated_str};
+use crate::mm::{
+    copy_from_user, copy_to_user, translated_byte_buffer, translated_str, user_buffer_valid,
+};
+use crate::sbi::{reboot, shutdown};
 use crate::task::{
-    current_process, current_task, current_user_token, exit_current_and_run_next, pid2process,
-    suspend_current_and_run_next, SignalFlags,
+    block_current_and_run_next, current_hart_id, current_parent_id, current_process, current_task,
+    exit_current_and_run_next, exit_group_current_and_run_next, exit_process_current_and_run_next,
+    pid2process, run_next, set_scheduler, suspend_current_and_run_next, Alarm, ExitReason,
+    SchedPolicy, SchedulerKind, SignalFlags,
 };
-use crate::timer::get_time_ms;
+use crate::timer::{clock_resolution_ns, get_time_ms, remaining_slice_us, set_timeslice};
+use alloc::format;
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::mem::size_of;
 
+/// Exit the calling thread. The last thread to exit ends the process with
+/// its `exit_code`.
 pub fn sys_exit(exit_code: i32) -> ! {
     exit_current_and_run_next(exit_code);
     panic!("Unreachable in sys_exit!");
 }
 
-pub fn sys_yield() -> isize {
+/// Exit the current process, killing its other threads but not its children.
+pub fn sys_exit_process(exit_code: i32) -> ! {
+    exit_process_current_and_run_next(exit_code);
+    panic!("Unreachable in sys_exit_process!");
+}
+
+/// Exit the current process together with all of its descendants.
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    exit_group_current_and_run_next(exit_code);
+    panic!("Unreachable in sys_exit_group!");
+}
+
+/// Give up the CPU. Unless `off_cpu_us` is null, store there how many
+/// microseconds passed before the task ran again. The store happens after
+/// the yield, as another thread may have unmapped the buffer meanwhile.
+pub fn sys_yield(off_cpu_us: *mut usize) -> isize {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().metric.voluntary_switches += 1;
     suspend_current_and_run_next();
+    if off_cpu_us.is_null() {
+        return 0;
+    }
+    let off_cpu = task.inner_exclusive_access().metric.off_cpu_us();
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, off_cpu_us, &off_cpu) {
+        return KernelError::Fault.into();
+    }
     0
 }
 
+/// Yield, then hold a soft deadline `ms` from the time this task runs again.
+/// The next time it waits to run, it is ordered ahead of later deadlines;
+/// being picked uses the deadline up. `ms == 0` clears it.
+pub fn sys_yield_deadline(ms: usize) -> isize {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().metric.voluntary_switches += 1;
+    suspend_current_and_run_next();
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner.deadline_ms = if ms == 0 {
+        None
+    } else {
+        Some(get_time_ms() + ms)
+    };
+    task_inner.deadline_settled = false;
+    0
+}
+
+/// Yield to thread `tid` of the calling process, which must be ready. It runs
+/// next, for a full time slice plus what was left of the caller's. Returns the
+/// donated microseconds, or -1 if `tid` is not another ready thread.
+pub fn sys_yield_donate(tid: usize) -> isize {
+    let task = current_task().unwrap();
+    let target = match current_process()
+        .inner_exclusive_access()
+        .tasks
+        .get(tid)
+        .cloned()
+        .flatten()
+    {
+        Some(target) if !Arc::ptr_eq(&target, &task) => target,
+        _ => return -1,
+    };
+    if !run_next(&target) {
+        return -1;
+    }
+    let donated_us = remaining_slice_us();
+    target.inner_exclusive_access().donated_us += donated_us;
+    task.inner_exclusive_access().metric.voluntary_switches += 1;
+    drop(task);
+    suspend_current_and_run_next();
+    donated_us as isize
+}
+
 pub fn sys_get_time() -> isize {
     get_time_ms() as isize
 }
 
+/// Copy the resolution of the clock behind the time syscalls, in
+/// nanoseconds, into `res`.
+pub fn sys_clock_getres(res: *mut usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !copy_to_user(&mut inner.memory_set, res, &clock_resolution_ns()) {
+        return KernelError::Fault.into();
+    }
+    0
+}
+
+/// Keep the timer from preempting the calling thread, for at most
+/// `PREEMPT_DISABLE_MAX_MS` in total each time it gets the CPU. Blocking and
+/// yielding still switch.
+pub fn sys_preempt_disable() -> isize {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner.preempt_disabled = true;
+    if task_inner.preempt_off_since_ms.is_none() {
+        task_inner.preempt_off_since_ms = Some(get_time_ms());
+    }
+    0
+}
+
+/// Let the timer preempt the calling thread again. Returns -1 if preemption
+/// was not off, also when it was turned back on for running too long.
+pub fn sys_preempt_enable() -> isize {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    if core::mem::take(&mut task_inner.preempt_disabled) {
+        0
+    } else {
+        -1
+    }
+}
+
+pub fn sys_set_timeslice(us: usize) -> isize {
+    set_timeslice(us) as isize
+}
+
+/// Switch the scheduler to the `SchedulerKind` numbered `kind`, which
+/// affects every task, so only a privileged process may. Returns the
+/// previous kind, or -1 if `kind` is unknown or the caller may not do so.
+pub fn sys_set_scheduler(kind: usize) -> isize {
+    if !is_privileged() {
+        return -1;
+    }
+    match SchedulerKind::from_id(kind) {
+        Some(kind) => set_scheduler(kind) as isize,
+        None => -1,
+    }
+}
+
+/// Move thread `tid` of the calling process to the `SchedPolicy` numbered
+/// `policy`, with `param` its priority or relative deadline in ms. Moving a
+/// thread other than the caller, or into a class that runs before
+/// `SchedPolicy::RoundRobin`, needs a privileged process. Returns `Invalid`
+/// for an unknown policy or a zero EDF deadline, -1 if `tid` is not a thread
+/// of the caller or the caller may not change it.
+pub fn sys_sched_setscheduler(tid: usize, policy: usize, param: usize) -> isize {
+    let policy = match SchedPolicy::from_id(policy) {
+        Some(SchedPolicy::Edf) if param == 0 => return KernelError::Invalid.into(),
+        Some(policy) => policy,
+        None => return KernelError::Invalid.into(),
+    };
+    let task = current_task().unwrap();
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let target = match process_inner.tasks.get(tid).cloned().flatten() {
+        Some(target) => target,
+        None => return -1,
+    };
+    let needs_privilege = !Arc::ptr_eq(&target, &task) || policy != SchedPolicy::RoundRobin;
+    if needs_privilege && !process_inner.privileged {
+        return -1;
+    }
+    drop(process_inner);
+    let mut target_inner = target.inner_exclusive_access();
+    if target_inner.sched_policy == SchedPolicy::Edf {
+        target_inner.deadline_ms = None;
+    }
+    target_inner.sched_policy = policy;
+    target_inner.sched_param = param;
+    0
+}
+
+/// Give up the privilege inherited from initproc, for this process and any
+/// child it starts from now on. There is no way to get it back.
+pub fn sys_drop_privilege() -> isize {
+    current_process().inner_exclusive_access().privileged = false;
+    0
+}
+
+/// Id of the hart the caller is running on.
+pub fn sys_getcpu() -> isize {
+    current_hart_id() as isize
+}
+
+/// The thread group id, the same for every thread of a process. See
+/// `sys_gettid` for the id of the calling thread.
 pub fn sys_getpid() -> isize {
-    current_task().unwrap().process.upgrade().unwrap().getpid() as isize
+    current_task().unwrap().tgid as isize
+}
+
+pub fn sys_getppid() -> isize {
+    current_parent_id() as isize
 }
 
+/// Fork the calling process. Returns `NoMem` if copying its memory would
+/// take frames from `RESERVED_FRAMES`.
 pub fn sys_fork() -> isize {
     let current_process = current_process();
-    let new_process = current_process.fork();
+    let new_process = match current_process.fork() {
+        Some(new_process) => new_process,
+        None => return KernelError::NoMem.into(),
+    };
     let new_pid = new_process.getpid();
     // modify trap context of new_task, because it returns immediately after switching
     let new_process_inner = new_process.inner_exclusive_access();
@@ -42,24 +231,36 @@ pub fn sys_fork() -> isize {
 }
 
 pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
-    let token = current_user_token();
-    let path = translated_str(token, path);
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let path = match translated_str(&mut inner.memory_set, path) {
+        Some(path) => path,
+        None => return KernelError::Fault.into(),
+    };
     let mut args_vec: Vec<String> = Vec::new();
     loop {
-        let arg_str_ptr = *translated_ref(token, args);
+        let arg_str_ptr = match copy_from_user(&mut inner.memory_set, args) {
+            Some(arg_str_ptr) => arg_str_ptr,
+            None => return KernelError::Fault.into(),
+        };
         if arg_str_ptr == 0 {
             break;
         }
-        args_vec.push(translated_str(token, arg_str_ptr as *const u8));
+        match translated_str(&mut inner.memory_set, arg_str_ptr as *const u8) {
+            Some(arg) => args_vec.push(arg),
+            None => return KernelError::Fault.into(),
+        }
         unsafe {
             args = args.add(1);
         }
     }
+    drop(inner);
     if let Some(app_inode) = open_file(path.as_str(), OpenFlags::RDONLY) {
         let all_data = app_inode.read_all();
-        let process = current_process();
         let argc = args_vec.len();
-        process.exec(all_data.as_slice(), args_vec);
+        if let Err(err) = process.exec(path.as_str(), all_data.as_slice(), args_vec) {
+            return KernelError::from(err).into();
+        }
         // return argc because cx.x[10] will be covered with it later
         argc as isize
     } else {
@@ -67,9 +268,119 @@ pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
     }
 }
 
-/// If there is not a child process whose pid is same as given, return -1.
-/// Else if there is a child process but it is still running, return -2.
-pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+/// Start app `path` as a child process. Returns its pid, -1 if there is no
+/// such app, `Invalid` if its image is broken, or `NoMem` if it does not fit
+/// in the frames outside the reserve.
+pub fn sys_spawn(path: *const u8) -> isize {
+    let process = current_process();
+    let path = match translated_str(&mut process.inner_exclusive_access().memory_set, path) {
+        Some(path) => path,
+        None => return KernelError::Fault.into(),
+    };
+    if let Some(app_inode) = open_file(path.as_str(), OpenFlags::RDONLY) {
+        let all_data = app_inode.read_all();
+        match process.spawn(path.as_str(), all_data.as_slice()) {
+            Ok(child) => child.getpid() as isize,
+            Err(err) => KernelError::from(err).into(),
+        }
+    } else {
+        -1
+    }
+}
+
+/// Set environment variable `key` to `value`. Fails if either string is too
+/// long or every slot is taken.
+pub fn sys_setenv(key: *const u8, value: *const u8) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let (key, value) = match (
+        translated_str(&mut inner.memory_set, key),
+        translated_str(&mut inner.memory_set, value),
+    ) {
+        (Some(key), Some(value)) => (key, value),
+        _ => return KernelError::Fault.into(),
+    };
+    if inner.environ.set(key, value) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Copy the value of `key` and a terminating NUL into `buf`. Returns the length
+/// of the value, or -1 if `key` is not set or the value does not fit.
+pub fn sys_getenv(key: *const u8, buf: *mut u8, len: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let key = match translated_str(&mut inner.memory_set, key) {
+        Some(key) => key,
+        None => return KernelError::Fault.into(),
+    };
+    let value = match inner.environ.get(&key).map(String::from) {
+        Some(value) if value.len() < len => value,
+        _ => return -1,
+    };
+    if !user_buffer_valid(&mut inner.memory_set, buf, value.len() + 1, true) {
+        return -1;
+    }
+    let token = inner.memory_set.token();
+    drop(inner);
+    let mut bytes = Vec::from(value.as_bytes());
+    bytes.push(0);
+    let mut written = 0;
+    for slice in translated_byte_buffer(token, buf, bytes.len()) {
+        slice.copy_from_slice(&bytes[written..written + slice.len()]);
+        written += slice.len();
+    }
+    value.len() as isize
+}
+
+/// Whether the calling process still has the privilege inherited from
+/// initproc, see `sys_drop_privilege`.
+fn is_privileged() -> bool {
+    current_process().inner_exclusive_access().privileged
+}
+
+/// Flush and power off the machine, reporting failure to the host if `code`
+/// is not 0. Only returns, with -1, if the caller may not do so.
+pub fn sys_poweroff(code: usize) -> isize {
+    if !is_privileged() {
+        return -1;
+    }
+    println!("[kernel] poweroff requested with code {}", code);
+    flush_all();
+    shutdown(code != 0)
+}
+
+/// Flush and reset the machine to a cold boot. Only returns, with -1, if the
+/// caller may not do so.
+pub fn sys_reboot() -> isize {
+    if !is_privileged() {
+        return -1;
+    }
+    println!("[kernel] reboot requested");
+    flush_all();
+    reboot()
+}
+
+/// Status `sys_waitpid` reports for a child that ended with `exit_code`: the
+/// low 8 bits of a normal exit code, like wait(2), or the negated signal
+/// number it was killed with.
+fn wait_status(exit_code: i32, reason: ExitReason) -> i32 {
+    match reason {
+        ExitReason::Normal => exit_code & 0xff,
+        ExitReason::Killed(_) => exit_code,
+    }
+}
+
+/// `sys_waitpid` option: return -2 at once instead of blocking.
+const WNOHANG: usize = 1;
+
+/// Reap the exited child `pid`, or any child if `pid` is -1. Returns -1 if
+/// there is no such child. Otherwise, if none has exited yet, blocks until a
+/// child exits or a signal arrives unless `options` has `WNOHANG`, then
+/// returns -2 so that the caller tries again.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32, options: usize) -> isize {
     let process = current_process();
     // find a child process
 
@@ -88,25 +399,60 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
         // ++++ release child PCB
     });
     if let Some((idx, _)) = pair {
+        // check where the status goes before the child is gone for good
+        let status_ptr = exit_code_ptr as *const u8;
+        if !user_buffer_valid(&mut inner.memory_set, status_ptr, size_of::<i32>(), true) {
+            return KernelError::Fault.into();
+        }
         let child = inner.children.remove(idx);
         // confirm that child will be deallocated after being removed from children list
         assert_eq!(Arc::strong_count(&child), 1);
         let found_pid = child.getpid();
         // ++++ temporarily access child PCB exclusively
-        let exit_code = child.inner_exclusive_access().exit_code;
+        let child_inner = child.inner_exclusive_access();
+        let exit_code = wait_status(child_inner.exit_code, child_inner.exit_reason);
+        drop(child_inner);
         // ++++ release child PCB
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
+        copy_to_user(&mut inner.memory_set, exit_code_ptr, &exit_code);
         found_pid as isize
     } else {
+        // a parent polling with yield_ would keep the ready queue busy forever,
+        // so the system could never go idle while anyone waits for a child
+        if options & WNOHANG == 0 && inner.signals.is_empty() {
+            inner.wait_waiters.push(current_task().unwrap());
+            drop(inner);
+            block_current_and_run_next();
+        }
         -2
     }
     // ---- release current PCB automatically
 }
 
+/// Why the child `pid` ended, without reaping it: 0 for a normal exit, or the
+/// number of the signal that killed it. -1 if there is no such child, -2 if
+/// it is still running.
+pub fn sys_exit_reason(pid: usize) -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    match inner.children.iter().find(|p| p.getpid() == pid) {
+        Some(child) => {
+            let child_inner = child.inner_exclusive_access();
+            if !child_inner.is_zombie {
+                return -2;
+            }
+            match child_inner.exit_reason {
+                ExitReason::Normal => 0,
+                ExitReason::Killed(signal) => signal as isize,
+            }
+        }
+        None => -1,
+    }
+}
+
 pub fn sys_kill(pid: usize, signal: u32) -> isize {
     if let Some(process) = pid2process(pid) {
         if let Some(flag) = SignalFlags::from_bits(signal) {
-            process.inner_exclusive_access().signals |= flag;
+            process.add_signal(flag);
             0
         } else {
             -1
@@ -115,3 +461,99 @@ pub fn sys_kill(pid: usize, signal: u32) -> isize {
         -1
     }
 }
+
+/// Block until a signal is pending for the calling process. Every signal in
+/// this kernel is fatal, so the caller is killed on its way back to user mode.
+pub fn sys_pause() -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    if process_inner.signals.is_empty() {
+        process_inner.pause_waiters.push(current_task().unwrap());
+        drop(process_inner);
+        block_current_and_run_next();
+    }
+    0
+}
+
+/// Arm a one-shot alarm that diverts the current thread to `handler` after `ms`
+/// milliseconds. `ms == 0` cancels a pending alarm.
+pub fn sys_setalarm(ms: usize, handler: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    task_inner.alarm = if ms == 0 {
+        None
+    } else {
+        Some(Alarm {
+            expire_ms: get_time_ms() + ms,
+            handler,
+        })
+    };
+    0
+}
+
+pub fn sys_sigreturn() -> isize {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    if let Some(backup) = task_inner.trap_cx_backup.take() {
+        let trap_cx = task_inner.get_trap_cx();
+        *trap_cx = backup;
+        // keep a0 of the interrupted context, trap_handler writes it back
+        trap_cx.x[10] as isize
+    } else {
+        -1
+    }
+}
+
+/// Save the caller's registers, like `setjmp`. Returns 0 now, and 1 each time
+/// `sys_restore` goes back to this point.
+pub fn sys_checkpoint() -> isize {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let mut checkpoint = task_inner.get_trap_cx().clone();
+    // sepc already points past the ecall
+    checkpoint.x[10] = 1;
+    task_inner.checkpoint = Some(checkpoint);
+    0
+}
+
+/// Reload the registers saved by the last `sys_checkpoint` of this thread,
+/// like `longjmp`. Memory is left as it is. Returns -1 if there is no
+/// checkpoint, and does not return otherwise.
+pub fn sys_restore() -> isize {
+    let task = current_task().unwrap();
+    let task_inner = task.inner_exclusive_access();
+    match task_inner.checkpoint.clone() {
+        Some(checkpoint) => {
+            *task_inner.get_trap_cx() = checkpoint;
+            // trap_handler writes the return value back to a0
+            1
+        }
+        None => -1,
+    }
+}
+
+/// Write "<name> <start>-<end>" of the current app image into `buf`,
+/// truncated to `len` bytes. Returns the number of bytes written.
+pub fn sys_current_app_info(buf: *mut u8, len: usize) -> isize {
+    if buf.is_null() {
+        return -1;
+    }
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let info = format!(
+        "{} {:#x}-{:#x}",
+        inner.app_name, inner.app_range.start, inner.app_range.end
+    );
+    let bytes = &info.as_bytes()[..info.len().min(len)];
+    if !user_buffer_valid(&mut inner.memory_set, buf, bytes.len(), true) {
+        return KernelError::Fault.into();
+    }
+    let token = inner.get_user_token();
+    drop(inner);
+    let mut written = 0;
+    for slice in translated_byte_buffer(token, buf, bytes.len()) {
+        slice.copy_from_slice(&bytes[written..written + slice.len()]);
+        written += slice.len();
+    }
+    written as isize
+}
@@ -1,6 +1,8 @@
-use crate::sync::{Condvar, Mutex, MutexBlocking, MutexSpin, Semaphore};
+use super::errno::KernelError;
+use crate::mm::{copy_from_user, MemorySet, PageTable, VirtAddr};
+use crate::sync::{futex_wait, futex_wake, Condvar, Mutex, MutexBlocking, MutexSpin, Semaphore};
 use crate::task::{block_current_and_run_next, current_process, current_task};
-use crate::timer::{add_timer, get_time_ms};
+use crate::timer::{add_timer, get_time_ms, TimeVal};
 use alloc::sync::Arc;
 
 pub fn sys_sleep(ms: usize) -> isize {
@@ -11,6 +13,30 @@ pub fn sys_sleep(ms: usize) -> isize {
     0
 }
 
+/// Sleep until the absolute time `*tv` (since boot), returning at once if it
+/// has already passed. `tv` must be aligned for `TimeVal`.
+pub fn sys_sleep_until(tv: *const TimeVal) -> isize {
+    if tv as usize % core::mem::align_of::<TimeVal>() != 0 {
+        return -1;
+    }
+    let process = current_process();
+    let tv = match copy_from_user(&mut process.inner_exclusive_access().memory_set, tv) {
+        Some(tv) => tv,
+        None => return -1,
+    };
+    if tv.usec >= 1_000_000 {
+        return -1;
+    }
+    let wake_ms = tv.to_ms();
+    if wake_ms <= get_time_ms() {
+        return 0;
+    }
+    let task = current_task().unwrap();
+    add_timer(wake_ms, task);
+    block_current_and_run_next();
+    0
+}
+
 pub fn sys_mutex_create(blocking: bool) -> isize {
     let process = current_process();
     let mutex: Option<Arc<dyn Mutex>> = if !blocking {
@@ -132,3 +158,48 @@ pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
     condvar.wait_with_mutex(mutex);
     0
 }
+
+/// Physical address of the futex word at `addr`, which identifies it across
+/// address spaces. `Invalid` if `addr` is not aligned, `Fault` if it is not
+/// mapped readable for the user.
+fn futex_key(memory_set: &mut MemorySet, addr: *const u32) -> Result<usize, KernelError> {
+    if addr as usize % core::mem::align_of::<u32>() != 0 {
+        return Err(KernelError::Invalid);
+    }
+    if copy_from_user(memory_set, addr).is_none() {
+        return Err(KernelError::Fault);
+    }
+    let pa = PageTable::from_token(memory_set.token())
+        .translate_va(VirtAddr::from(addr as usize))
+        .unwrap();
+    Ok(pa.into())
+}
+
+/// Block until woken by `sys_futex_wake` on `addr`, if `*addr == expected`.
+/// The value is checked and the thread queued in one step, so a wake after
+/// the user changed the value cannot be missed. Returns `Again` right away
+/// if the value differs.
+pub fn sys_futex_wait(addr: *const u32, expected: u32) -> isize {
+    let process = current_process();
+    let key = match futex_key(&mut process.inner_exclusive_access().memory_set, addr) {
+        Ok(key) => key,
+        Err(err) => return err.into(),
+    };
+    if futex_wait(key, || {
+        copy_from_user(&mut process.inner_exclusive_access().memory_set, addr) == Some(expected)
+    }) {
+        0
+    } else {
+        KernelError::Again.into()
+    }
+}
+
+/// Wake up to `n` threads waiting on `addr`. Returns how many were woken.
+pub fn sys_futex_wake(addr: *const u32, n: usize) -> isize {
+    let process = current_process();
+    let key = futex_key(&mut process.inner_exclusive_access().memory_set, addr);
+    match key {
+        Ok(key) => futex_wake(key, n) as isize,
+        Err(err) => err.into(),
+    }
+}
@@ -0,0 +1,38 @@
+//! Syscalls only built with the `testing` feature.
+
+use crate::mm::{copy_from_user, inject_frame_exhaustion};
+use crate::task::{current_process, current_task};
+
+/// Read a user pointer that is never mapped.
+const FAULT_USER_READ: usize = 0;
+/// The next free frame check reports none left.
+const FAULT_OUT_OF_FRAMES: usize = 1;
+/// The caller's CPU time mark is left in the future.
+const FAULT_STALE_METRIC: usize = 2;
+/// The kernel panics, and then does what `PANIC_ACTION` says.
+const FAULT_KERNEL_PANIC: usize = 3;
+
+/// Trigger the condition `kind` on a path that is expected to handle it.
+/// Each kind affects at most the next check it targets and then goes away,
+/// except `FAULT_KERNEL_PANIC`, which does not return.
+/// Returns -1 for an unknown kind, or if the user read is not refused.
+pub fn sys_fault_inject(kind: usize) -> isize {
+    match kind {
+        FAULT_USER_READ => {
+            let process = current_process();
+            let mut inner = process.inner_exclusive_access();
+            if copy_from_user(&mut inner.memory_set, core::ptr::null::<usize>()).is_some() {
+                return -1;
+            }
+        }
+        FAULT_OUT_OF_FRAMES => inject_frame_exhaustion(),
+        FAULT_STALE_METRIC => current_task()
+            .unwrap()
+            .inner_exclusive_access()
+            .metric
+            .inject_stale_mark(),
+        FAULT_KERNEL_PANIC => panic!("panic injected by sys_fault_inject"),
+        _ => return -1,
+    }
+    0
+}
@@ -1,3 +1,4 @@
+use super::errno::KernelError;
 use crate::{
     mm::kernel_token,
     task::{add_task, current_task, TaskControlBlock},
@@ -5,19 +6,22 @@ use crate::{
 };
 use alloc::sync::Arc;
 
+/// Start a thread at `entry` with `arg` in a0. Returns its tid, or `NoMem`
+/// if there are no frames outside the reserve for its stack and trap context.
 pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
     let task = current_task().unwrap();
     let process = task.process.upgrade().unwrap();
     // create a new thread
-    let new_task = Arc::new(TaskControlBlock::new(
-        Arc::clone(&process),
-        task.inner_exclusive_access()
-            .res
-            .as_ref()
-            .unwrap()
-            .ustack_base,
-        true,
-    ));
+    let ustack_base = task
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .unwrap()
+        .ustack_base;
+    let new_task = match TaskControlBlock::new(Arc::clone(&process), ustack_base, true) {
+        Some(new_task) => Arc::new(new_task),
+        None => return KernelError::NoMem.into(),
+    };
     // add new task to scheduler
     add_task(Arc::clone(&new_task));
     let new_task_inner = new_task.inner_exclusive_access();
@@ -42,6 +46,7 @@ pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
     new_task_tid as isize
 }
 
+/// Id of the calling thread within its process, 0 for the main thread.
 pub fn sys_gettid() -> isize {
     current_task()
         .unwrap()
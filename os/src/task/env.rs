@@ -0,0 +1,41 @@
+use crate::config::{ENV_MAX_LEN, ENV_MAX_VARS};
+use alloc::string::String;
+
+/// Environment variables of a process, `ENV_MAX_VARS` slots of key/value
+/// pairs of at most `ENV_MAX_LEN` bytes each.
+#[derive(Clone, Default)]
+pub struct Environ {
+    vars: [Option<(String, String)>; ENV_MAX_VARS],
+}
+
+impl Environ {
+    /// Set `key` to `value`, replacing an old value. Fails if either is too
+    /// long, `key` is empty or contains `=`, or all slots are taken.
+    pub fn set(&mut self, key: String, value: String) -> bool {
+        if key.is_empty()
+            || key.contains('=')
+            || key.len() > ENV_MAX_LEN
+            || value.len() > ENV_MAX_LEN
+        {
+            return false;
+        }
+        if let Some((_, old)) = self.vars.iter_mut().flatten().find(|(k, _)| *k == key) {
+            *old = value;
+            return true;
+        }
+        match self.vars.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some((key, value));
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars
+            .iter()
+            .flatten()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
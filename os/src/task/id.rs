@@ -1,6 +1,8 @@
 use super::ProcessControlBlock;
-use crate::config::{KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT_BASE, USER_STACK_SIZE};
-use crate::mm::{MapPermission, PhysPageNum, VirtAddr, KERNEL_SPACE};
+use crate::config::{
+    KERNEL_STACK_RED_ZONE, KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT_BASE,
+};
+use crate::mm::{MapPermission, MemorySet, PhysPageNum, VirtAddr, KERNEL_SPACE};
 use crate::sync::UPIntrFreeCell;
 use alloc::{
     sync::{Arc, Weak},
@@ -67,6 +69,16 @@ pub fn kernel_stack_position(kstack_id: usize) -> (usize, usize) {
     (bottom, top)
 }
 
+/// Fills the red zone of every kernel stack.
+const KERNEL_STACK_CANARY: usize = 0x5ca1_ab1e_dead_beef;
+
+/// Words of the red zone, lowest first.
+fn red_zone(kstack_bottom: usize) -> impl Iterator<Item = *mut usize> {
+    (kstack_bottom..kstack_bottom + KERNEL_STACK_RED_ZONE)
+        .step_by(core::mem::size_of::<usize>())
+        .map(|addr| addr as *mut usize)
+}
+
 pub struct KernelStack(pub usize);
 
 pub fn kstack_alloc() -> KernelStack {
@@ -77,6 +89,9 @@ pub fn kstack_alloc() -> KernelStack {
         kstack_top.into(),
         MapPermission::R | MapPermission::W,
     );
+    for word in red_zone(kstack_bottom) {
+        unsafe { word.write_volatile(KERNEL_STACK_CANARY) };
+    }
     KernelStack(kstack_id)
 }
 
@@ -108,11 +123,21 @@ impl KernelStack {
         let (_, kernel_stack_top) = kernel_stack_position(self.0);
         kernel_stack_top
     }
+    pub fn get_bottom(&self) -> usize {
+        let (kernel_stack_bottom, _) = kernel_stack_position(self.0);
+        kernel_stack_bottom
+    }
+    /// Whether something has written into the red zone at the bottom.
+    pub fn overflowed(&self) -> bool {
+        red_zone(self.get_bottom())
+            .any(|word| unsafe { word.read_volatile() } != KERNEL_STACK_CANARY)
+    }
 }
 
 pub struct TaskUserRes {
     pub tid: usize,
     pub ustack_base: usize,
+    pub ustack_size: usize,
     pub process: Weak<ProcessControlBlock>,
 }
 
@@ -120,47 +145,79 @@ fn trap_cx_bottom_from_tid(tid: usize) -> usize {
     TRAP_CONTEXT_BASE - tid * PAGE_SIZE
 }
 
-fn ustack_bottom_from_tid(ustack_base: usize, tid: usize) -> usize {
-    ustack_base + tid * (PAGE_SIZE + USER_STACK_SIZE)
+pub fn ustack_bottom_from_tid(ustack_base: usize, ustack_size: usize, tid: usize) -> usize {
+    ustack_base + tid * (PAGE_SIZE + ustack_size)
+}
+
+/// Map the user stack and the trap context of thread `tid` into
+/// `memory_set`, with frames from outside the reserve. Returns false, with
+/// nothing mapped, if there are not enough.
+pub fn map_user_res(
+    memory_set: &mut MemorySet,
+    ustack_base: usize,
+    ustack_size: usize,
+    tid: usize,
+) -> bool {
+    // alloc user stack, which grows on demand up to `ustack_size`
+    let ustack_bottom = ustack_bottom_from_tid(ustack_base, ustack_size, tid);
+    let ustack_top = ustack_bottom + ustack_size;
+    if !memory_set.insert_stack_area(
+        ustack_bottom.into(),
+        ustack_top.into(),
+        MapPermission::R | MapPermission::W | MapPermission::U,
+    ) {
+        return false;
+    }
+    // alloc trap_cx
+    let trap_cx_bottom = trap_cx_bottom_from_tid(tid);
+    let trap_cx_top = trap_cx_bottom + PAGE_SIZE;
+    if !memory_set.try_insert_framed_area(
+        trap_cx_bottom.into(),
+        trap_cx_top.into(),
+        MapPermission::R | MapPermission::W,
+    ) {
+        let ustack_bottom_va: VirtAddr = ustack_bottom.into();
+        memory_set.remove_area_with_start_vpn(ustack_bottom_va.into());
+        return false;
+    }
+    true
 }
 
 impl TaskUserRes {
+    /// `None` if `alloc_user_res` is set but there are not enough frames for
+    /// the user stack and trap context.
     pub fn new(
         process: Arc<ProcessControlBlock>,
         ustack_base: usize,
         alloc_user_res: bool,
-    ) -> Self {
-        let tid = process.inner_exclusive_access().alloc_tid();
+    ) -> Option<Self> {
+        let mut process_inner = process.inner_exclusive_access();
+        let tid = process_inner.alloc_tid();
+        let ustack_size = process_inner.ustack_size;
+        drop(process_inner);
         let task_user_res = Self {
             tid,
             ustack_base,
+            ustack_size,
             process: Arc::downgrade(&process),
         };
-        if alloc_user_res {
-            task_user_res.alloc_user_res();
+        // dropping it on failure gives the tid back
+        if alloc_user_res && !task_user_res.alloc_user_res() {
+            return None;
         }
-        task_user_res
+        Some(task_user_res)
     }
 
-    pub fn alloc_user_res(&self) {
+    /// See `map_user_res`.
+    fn alloc_user_res(&self) -> bool {
         let process = self.process.upgrade().unwrap();
         let mut process_inner = process.inner_exclusive_access();
-        // alloc user stack
-        let ustack_bottom = ustack_bottom_from_tid(self.ustack_base, self.tid);
-        let ustack_top = ustack_bottom + USER_STACK_SIZE;
-        process_inner.memory_set.insert_framed_area(
-            ustack_bottom.into(),
-            ustack_top.into(),
-            MapPermission::R | MapPermission::W | MapPermission::U,
-        );
-        // alloc trap_cx
-        let trap_cx_bottom = trap_cx_bottom_from_tid(self.tid);
-        let trap_cx_top = trap_cx_bottom + PAGE_SIZE;
-        process_inner.memory_set.insert_framed_area(
-            trap_cx_bottom.into(),
-            trap_cx_top.into(),
-            MapPermission::R | MapPermission::W,
-        );
+        map_user_res(
+            &mut process_inner.memory_set,
+            self.ustack_base,
+            self.ustack_size,
+            self.tid,
+        )
     }
 
     fn dealloc_user_res(&self) {
@@ -168,7 +225,8 @@ impl TaskUserRes {
         let process = self.process.upgrade().unwrap();
         let mut process_inner = process.inner_exclusive_access();
         // dealloc ustack manually
-        let ustack_bottom_va: VirtAddr = ustack_bottom_from_tid(self.ustack_base, self.tid).into();
+        let ustack_bottom_va: VirtAddr =
+            ustack_bottom_from_tid(self.ustack_base, self.ustack_size, self.tid).into();
         process_inner
             .memory_set
             .remove_area_with_start_vpn(ustack_bottom_va.into());
@@ -213,8 +271,12 @@ impl TaskUserRes {
     pub fn ustack_base(&self) -> usize {
         self.ustack_base
     }
+    /// Lowest address the user stack may grow down to.
+    pub fn ustack_bottom(&self) -> usize {
+        ustack_bottom_from_tid(self.ustack_base, self.ustack_size, self.tid)
+    }
     pub fn ustack_top(&self) -> usize {
-        ustack_bottom_from_tid(self.ustack_base, self.tid) + USER_STACK_SIZE
+        ustack_bottom_from_tid(self.ustack_base, self.ustack_size, self.tid) + self.ustack_size
     }
 }
 
@@ -1,25 +1,98 @@
+use super::sched::{SchedPolicy, Scheduler, SchedulerKind};
 use super::{ProcessControlBlock, TaskControlBlock, TaskStatus};
+use crate::config::SCHEDULER;
 use crate::sync::UPIntrFreeCell;
+use crate::timer::{get_time_ms, get_time_us};
+use alloc::boxed::Box;
 use alloc::collections::{BTreeMap, VecDeque};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
 use lazy_static::*;
+use log::info;
 
 pub struct TaskManager {
     ready_queue: VecDeque<Arc<TaskControlBlock>>,
+    scheduler: Box<dyn Scheduler>,
+    scheduler_kind: SchedulerKind,
 }
 
-/// A simple FIFO scheduler.
+/// Ready tasks with a deadline run first, earliest deadline first, then
+/// `SchedPolicy::Priority` tasks, highest priority first. The others are
+/// picked by the scheduler chosen with `SCHEDULER`.
 impl TaskManager {
     pub fn new() -> Self {
         Self {
             ready_queue: VecDeque::new(),
+            scheduler: SCHEDULER.build(),
+            scheduler_kind: SCHEDULER,
         }
     }
     pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        let mut task_inner = task.inner_exclusive_access();
+        task_inner.metric.mark_ready();
+        if task_inner.sched_policy == SchedPolicy::Edf {
+            task_inner.deadline_ms = Some(get_time_ms() + task_inner.sched_param);
+            task_inner.deadline_settled = false;
+        }
+        drop(task_inner);
         self.ready_queue.push_back(task);
     }
     pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
-        self.ready_queue.pop_front()
+        let idx = self
+            .earliest_deadline()
+            .or_else(|| self.highest_priority())
+            .or_else(|| self.scheduler.pick(&self.ready_queue))?;
+        self.ready_queue.remove(idx)
+    }
+    /// Switch to another scheduler. Returns the previous kind.
+    pub fn set_scheduler(&mut self, kind: SchedulerKind) -> SchedulerKind {
+        self.scheduler = kind.build();
+        core::mem::replace(&mut self.scheduler_kind, kind)
+    }
+    /// Take `task` off the ready queue, if it is there.
+    pub fn remove(&mut self, task: &Arc<TaskControlBlock>) {
+        self.ready_queue.retain(|t| !Arc::ptr_eq(t, task));
+    }
+    /// Move `task` to the front of the ready queue. Returns false if it is not
+    /// in the queue.
+    pub fn move_to_front(&mut self, task: &Arc<TaskControlBlock>) -> bool {
+        match self.ready_queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            Some(idx) => {
+                let task = self.ready_queue.remove(idx).unwrap();
+                self.ready_queue.push_front(task);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Index of the ready task with the earliest deadline, the first one on a
+    /// tie. `None` if no ready task has a deadline.
+    fn earliest_deadline(&self) -> Option<usize> {
+        self.ready_queue
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, task)| {
+                task.inner_exclusive_access()
+                    .deadline_ms
+                    .map(|deadline| (deadline, idx))
+            })
+            .min()
+            .map(|(_, idx)| idx)
+    }
+    /// Index of the `SchedPolicy::Priority` task with the highest priority,
+    /// the first one on a tie. `None` if there is no such task.
+    fn highest_priority(&self) -> Option<usize> {
+        self.ready_queue
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, task)| {
+                let task_inner = task.inner_exclusive_access();
+                (task_inner.sched_policy == SchedPolicy::Priority)
+                    .then(|| (task_inner.sched_param, Reverse(idx)))
+            })
+            .max()
+            .map(|(_, Reverse(idx))| idx)
     }
 }
 
@@ -37,12 +110,56 @@ pub fn add_task(task: Arc<TaskControlBlock>) {
 pub fn wakeup_task(task: Arc<TaskControlBlock>) {
     let mut task_inner = task.inner_exclusive_access();
     task_inner.task_status = TaskStatus::Ready;
+    task_inner.metric.mark_woken();
     drop(task_inner);
     add_task(task);
 }
 
+/// Take `task` off the ready queue, if it is there.
+pub fn remove_task(task: &Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().remove(task);
+}
+
+/// Let `task` be picked next. Returns false if it is not ready.
+pub fn run_next(task: &Arc<TaskControlBlock>) -> bool {
+    TASK_MANAGER.exclusive_access().move_to_front(task)
+}
+
+/// Replace the scheduler. Returns the previous kind.
+pub fn set_scheduler(kind: SchedulerKind) -> SchedulerKind {
+    TASK_MANAGER.exclusive_access().set_scheduler(kind)
+}
+
+/// Count the deadlines that passed while their tasks were still ready.
+pub fn check_deadlines() {
+    let now_us = get_time_us();
+    let missed: Vec<Arc<TaskControlBlock>> = TASK_MANAGER
+        .exclusive_access()
+        .ready_queue
+        .iter()
+        .filter(|task| task.inner_exclusive_access().check_deadline(now_us, false))
+        .cloned()
+        .collect();
+    for task in missed {
+        let pid = task.tgid;
+        let tid = task
+            .inner_exclusive_access()
+            .res
+            .as_ref()
+            .map_or(0, |res| res.tid);
+        info!("[kernel] pid {} tid {} missed its deadline", pid, tid);
+    }
+}
+
+/// Let the scheduler account a timer tick.
+pub fn scheduler_tick() {
+    TASK_MANAGER.exclusive_access().scheduler.on_tick();
+}
+
 pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
-    TASK_MANAGER.exclusive_access().fetch()
+    let task = TASK_MANAGER.exclusive_access().fetch()?;
+    task.inner_exclusive_access().metric.mark_picked();
+    Some(task)
 }
 
 pub fn pid2process(pid: usize) -> Option<Arc<ProcessControlBlock>> {
@@ -0,0 +1,282 @@
+use crate::config::CLOCKS_PER_SEC;
+use crate::sync::UPIntrFreeCell;
+use crate::timer::{clock_freq, get_time_us};
+use alloc::collections::BTreeMap;
+use lazy_static::*;
+use log::debug;
+
+/// Per-task statistics collected by the kernel.
+///
+/// CPU time is charged from `mark` to the next transition: the span up to a
+/// trap entry goes to user time, the span up to `trap_return` or to leaving
+/// the CPU goes to kernel time. A `mark` in the future charges nothing. Time is charged when `__switch` leaves the
+/// task, not when control comes back to it, so a task never pays for the
+/// tasks that ran in between.
+pub struct TaskMetric {
+    /// times this task has been switched in
+    pub switches: usize,
+    /// times this task gave up the CPU by `sys_yield`
+    pub voluntary_switches: usize,
+    /// times this task was preempted by the timer
+    pub involuntary_switches: usize,
+    pub user_time_us: usize,
+    pub kernel_time_us: usize,
+    /// page faults taken in user mode, including first touches of lazy pages
+    pub page_faults: usize,
+    /// time spent blocked: sleeping, or waiting for a lock or a device
+    pub blocked_time_us: usize,
+    /// time spent in the ready queue waiting for the CPU
+    pub ready_wait_us: usize,
+    /// deadlines set by `sys_yield_deadline` that passed while this task was
+    /// ready but not running
+    pub deadline_misses: usize,
+    /// latency of each syscall id this task has made
+    pub syscalls: BTreeMap<usize, SyscallLatency>,
+    /// when the task last left the CPU
+    descheduled_at: usize,
+    /// when the task was last given the CPU
+    scheduled_at: usize,
+    /// start of the span not yet charged
+    mark: usize,
+    /// when the task last blocked or became ready
+    wait_mark: usize,
+}
+
+impl TaskMetric {
+    pub fn new() -> Self {
+        Self {
+            switches: 0,
+            voluntary_switches: 0,
+            involuntary_switches: 0,
+            user_time_us: 0,
+            kernel_time_us: 0,
+            page_faults: 0,
+            blocked_time_us: 0,
+            ready_wait_us: 0,
+            deadline_misses: 0,
+            syscalls: BTreeMap::new(),
+            descheduled_at: 0,
+            scheduled_at: 0,
+            mark: 0,
+            wait_mark: 0,
+        }
+    }
+    pub fn record_syscall(&mut self, syscall_id: usize, latency_us: usize) {
+        let entry = self.syscalls.entry(syscall_id).or_insert(SyscallLatency {
+            id: syscall_id,
+            count: 0,
+            total_us: 0,
+        });
+        entry.count += 1;
+        entry.total_us += latency_us;
+    }
+    /// The task is given the CPU, in kernel mode.
+    pub fn switch_in(&mut self) {
+        self.switches += 1;
+        self.mark = get_time_us();
+        self.scheduled_at = self.mark;
+    }
+    /// The task is about to `__switch` away.
+    pub fn switch_out(&mut self) {
+        let now = get_time_us();
+        self.kernel_time_us += now.saturating_sub(self.mark);
+        self.descheduled_at = now;
+    }
+    /// Time since the task was last given the CPU. While it is running, how
+    /// long it has been running.
+    pub fn on_cpu_us(&self) -> usize {
+        get_time_us().saturating_sub(self.scheduled_at)
+    }
+    /// Time since the task last left the CPU. Right after it is switched
+    /// back in, how long it was away.
+    pub fn off_cpu_us(&self) -> usize {
+        get_time_us().saturating_sub(self.descheduled_at)
+    }
+    /// The task trapped from user mode into the kernel.
+    pub fn trap_enter(&mut self) {
+        let now = get_time_us();
+        self.user_time_us += now.saturating_sub(self.mark);
+        self.mark = now;
+    }
+    /// The task is about to return to user mode.
+    pub fn trap_return(&mut self) {
+        let now = get_time_us();
+        self.kernel_time_us += now.saturating_sub(self.mark);
+        self.mark = now;
+    }
+    /// The task was put in the ready queue.
+    pub fn mark_ready(&mut self) {
+        self.wait_mark = get_time_us();
+    }
+    /// When the task became ready, while it is in the ready queue or has just
+    /// been picked from it.
+    pub fn ready_since_us(&self) -> usize {
+        self.wait_mark
+    }
+    /// The scheduler took the task out of the ready queue.
+    pub fn mark_picked(&mut self) {
+        self.ready_wait_us += get_time_us().saturating_sub(self.wait_mark);
+    }
+    /// The task blocked.
+    pub fn mark_blocked(&mut self) {
+        self.wait_mark = get_time_us();
+    }
+    /// The blocked task was woken up.
+    pub fn mark_woken(&mut self) {
+        self.blocked_time_us += get_time_us().saturating_sub(self.wait_mark);
+    }
+    /// Move `mark` a second into the future, as if it had not been updated
+    /// correctly. The next transition must charge nothing rather than wrap.
+    #[cfg(feature = "testing")]
+    pub fn inject_stale_mark(&mut self) {
+        self.mark = get_time_us() + 1_000_000;
+    }
+}
+
+/// Calls to one syscall id and the time spent in them, as copied to user
+/// space by `sys_syscall_latency`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct SyscallLatency {
+    pub id: usize,
+    pub count: usize,
+    pub total_us: usize,
+}
+
+/// Snapshot of a task's statistics copied to user space by `sys_task_info`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct TaskInfo {
+    pub switches: usize,
+    pub voluntary_switches: usize,
+    pub involuntary_switches: usize,
+    pub user_time_us: usize,
+    pub kernel_time_us: usize,
+    pub blocked_time_us: usize,
+    pub ready_wait_us: usize,
+}
+
+impl From<&TaskMetric> for TaskInfo {
+    fn from(metric: &TaskMetric) -> Self {
+        Self {
+            switches: metric.switches,
+            voluntary_switches: metric.voluntary_switches,
+            involuntary_switches: metric.involuntary_switches,
+            user_time_us: metric.user_time_us,
+            kernel_time_us: metric.kernel_time_us,
+            blocked_time_us: metric.blocked_time_us,
+            ready_wait_us: metric.ready_wait_us,
+        }
+    }
+}
+
+/// Resource usage of a task copied to user space by `sys_getrusage`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct RUsage {
+    pub user_time_us: usize,
+    pub kernel_time_us: usize,
+    pub voluntary_switches: usize,
+    pub involuntary_switches: usize,
+    pub page_faults: usize,
+    /// peak number of frames backing the process's user memory
+    pub max_rss_pages: usize,
+    pub blocked_time_us: usize,
+    pub ready_wait_us: usize,
+    pub deadline_misses: usize,
+    pub working_set_pages: usize,
+}
+
+impl RUsage {
+    pub fn new(metric: &TaskMetric, max_rss_pages: usize, working_set_pages: usize) -> Self {
+        Self {
+            user_time_us: metric.user_time_us,
+            kernel_time_us: metric.kernel_time_us,
+            voluntary_switches: metric.voluntary_switches,
+            involuntary_switches: metric.involuntary_switches,
+            page_faults: metric.page_faults,
+            max_rss_pages,
+            blocked_time_us: metric.blocked_time_us,
+            ready_wait_us: metric.ready_wait_us,
+            deadline_misses: metric.deadline_misses,
+            working_set_pages,
+        }
+    }
+}
+
+/// CPU time of a task in clock ticks, copied to user space by `sys_times`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Tms {
+    pub utime_ticks: usize,
+    pub stime_ticks: usize,
+}
+
+impl From<&TaskMetric> for Tms {
+    fn from(metric: &TaskMetric) -> Self {
+        let ticks = |us: usize| us * CLOCKS_PER_SEC / 1_000_000;
+        Self {
+            utime_ticks: ticks(metric.user_time_us),
+            stime_ticks: ticks(metric.kernel_time_us),
+        }
+    }
+}
+
+lazy_static! {
+    /// Total number of context switches into any task since boot.
+    static ref SWITCH_COUNT: UPIntrFreeCell<usize> = unsafe { UPIntrFreeCell::new(0) };
+    /// Total number of tasks (threads) created since boot.
+    static ref TASKS_CREATED: UPIntrFreeCell<usize> = unsafe { UPIntrFreeCell::new(0) };
+    /// Time spent with no task to run since boot, in microseconds.
+    static ref IDLE_TIME_US: UPIntrFreeCell<usize> = unsafe { UPIntrFreeCell::new(0) };
+}
+
+/// Called once for every new task, right when it becomes `Ready`.
+pub fn on_task_create(pid: usize, tid: usize) {
+    *TASKS_CREATED.exclusive_access() += 1;
+    debug!("[kernel] task created: pid {} tid {}", pid, tid);
+}
+
+pub fn tasks_created() -> usize {
+    *TASKS_CREATED.exclusive_access()
+}
+
+pub fn inc_switch_count() {
+    *SWITCH_COUNT.exclusive_access() += 1;
+}
+
+pub fn switch_count() -> usize {
+    *SWITCH_COUNT.exclusive_access()
+}
+
+/// Charge `us` microseconds the processor spent waiting for a task to wake.
+pub fn add_idle_time(us: usize) {
+    *IDLE_TIME_US.exclusive_access() += us;
+}
+
+pub fn idle_time_us() -> usize {
+    *IDLE_TIME_US.exclusive_access()
+}
+
+/// System-wide counters copied to user space by `sys_sysinfo`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct SysInfo {
+    pub uptime_ms: usize,
+    pub idle_time_ms: usize,
+    pub switches: usize,
+    pub tasks_created: usize,
+    pub timebase_hz: usize,
+}
+
+impl SysInfo {
+    pub fn current() -> Self {
+        Self {
+            uptime_ms: get_time_us() / 1000,
+            idle_time_ms: idle_time_us() / 1000,
+            switches: switch_count(),
+            tasks_created: tasks_created(),
+            timebase_hz: clock_freq(),
+        }
+    }
+}
@@ -1,30 +1,52 @@
 mod context;
+mod env;
 mod id;
 mod manager;
+mod metric;
 mod process;
 mod processor;
+mod profile;
+mod sched;
+mod sched_trace;
 mod signal;
 mod switch;
 #[allow(clippy::module_inception)]
 mod task;
 
 use self::id::TaskUserRes;
-use crate::fs::{open_file, OpenFlags};
+use crate::config::{INIT_APP_NAME, PREEMPT_DISABLE_MAX_MS};
+use crate::fs::{app_names, flush_stdout, open_file, OpenFlags};
 use crate::sbi::shutdown;
-use alloc::{sync::Arc, vec::Vec};
+use crate::sync::futex_forget_process;
+use crate::timer::{get_time_ms, remove_timer};
+use alloc::{string::String, sync::Arc, vec::Vec};
 use lazy_static::*;
+use log::{debug, info, warn};
 use manager::fetch_task;
 use process::ProcessControlBlock;
+use sched_trace::{trace_switch_out, SwitchReason};
 use switch::__switch;
 
 pub use context::TaskContext;
+pub use env::Environ;
 pub use id::{kstack_alloc, pid_alloc, KernelStack, PidHandle, IDLE_PID};
-pub use manager::{add_task, pid2process, remove_from_pid2process, wakeup_task};
+pub use manager::{
+    add_task, check_deadlines, pid2process, remove_from_pid2process, remove_task, run_next,
+    scheduler_tick, set_scheduler, wakeup_task,
+};
+pub use metric::{
+    idle_time_us, switch_count, tasks_created, RUsage, SysInfo, SyscallLatency, TaskInfo,
+    TaskMetric, Tms,
+};
 pub use processor::{
-    current_kstack_top, current_process, current_task, current_trap_cx, current_trap_cx_user_va,
-    current_user_token, run_tasks, schedule, take_current_task,
+    check_kernel_stack_of_current, current_hart_id, current_kstack_top, current_parent_id,
+    current_process, current_task, current_trap_cx, current_trap_cx_user_va, current_user_token,
+    idle, run_tasks, schedule, set_boot_hart_id, take_current_task,
 };
-pub use signal::SignalFlags;
+pub use profile::{Profile, ProfileBucket};
+pub use sched::{SchedPolicy, Scheduler, SchedulerKind, Vruntime};
+pub use sched_trace::{reset_sched_trace, sched_trace_text};
+pub use signal::{Alarm, ExitReason, SignalFlags};
 pub use task::{TaskControlBlock, TaskStatus};
 
 pub fn suspend_current_and_run_next() {
@@ -36,6 +58,12 @@ pub fn suspend_current_and_run_next() {
     let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
     // Change status to Ready
     task_inner.task_status = TaskStatus::Ready;
+    task_inner.switch_out();
+    trace_switch_out(
+        task.tgid,
+        task_inner.res.as_ref().unwrap().tid,
+        SwitchReason::Ready,
+    );
     drop(task_inner);
     // ---- release current TCB
 
@@ -50,6 +78,13 @@ pub fn block_current_task() -> *mut TaskContext {
     let task = take_current_task().unwrap();
     let mut task_inner = task.inner_exclusive_access();
     task_inner.task_status = TaskStatus::Blocked;
+    task_inner.switch_out();
+    task_inner.metric.mark_blocked();
+    trace_switch_out(
+        task.tgid,
+        task_inner.res.as_ref().unwrap().tid,
+        SwitchReason::Blocked,
+    );
     &mut task_inner.task_cx as *mut TaskContext
 }
 
@@ -58,12 +93,30 @@ pub fn block_current_and_run_next() {
     schedule(task_cx_ptr);
 }
 
-/// Exit the current 'Running' task and run the next task in task list.
+/// Exit the current 'Running' task and run the next task in task list. The
+/// last thread of a process to exit takes the whole process down.
 pub fn exit_current_and_run_next(exit_code: i32) {
+    exit_current_thread(exit_code, ExitReason::Normal);
+}
+
+/// Exit the current thread; if it is the last one, end the process for
+/// `reason`.
+fn exit_current_thread(exit_code: i32, reason: ExitReason) {
+    flush_stdout(&current_task().unwrap());
     let task = take_current_task().unwrap();
     let mut task_inner = task.inner_exclusive_access();
     let process = task.process.upgrade().unwrap();
     let tid = task_inner.res.as_ref().unwrap().tid;
+    trace_switch_out(process.getpid(), tid, SwitchReason::Exited);
+    // close the last span, also when killed straight from trap_handler
+    task_inner.switch_out();
+    debug!(
+        "[kernel] pid {} tid {} cpu time: user {}us, kernel {}us",
+        process.getpid(),
+        tid,
+        task_inner.metric.user_time_us,
+        task_inner.metric.kernel_time_us
+    );
     // record exit code
     task_inner.exit_code = Some(exit_code);
     task_inner.res = None;
@@ -71,62 +124,15 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     // it will be deallocated when sys_waittid is called
     drop(task_inner);
     drop(task);
-    // however, if this is the main thread of current process
-    // the process should terminate at once
-    if tid == 0 {
-        let pid = process.getpid();
-        if pid == IDLE_PID {
-            println!(
-                "[kernel] Idle process exit with exit_code {} ...",
-                exit_code
-            );
-            if exit_code != 0 {
-                //crate::sbi::shutdown(255); //255 == -1 for err hint
-                shutdown(true);
-            } else {
-                //crate::sbi::shutdown(0); //0 for success hint
-                shutdown(false);
-            }
-        }
-        remove_from_pid2process(pid);
-        let mut process_inner = process.inner_exclusive_access();
-        // mark this process as a zombie process
-        process_inner.is_zombie = true;
-        // record exit code of main process
-        process_inner.exit_code = exit_code;
-
-        {
-            // move all child processes under init process
-            let mut initproc_inner = INITPROC.inner_exclusive_access();
-            for child in process_inner.children.iter() {
-                child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
-                initproc_inner.children.push(child.clone());
-            }
-        }
-
-        // deallocate user res (including tid/trap_cx/ustack) of all threads
-        // it has to be done before we dealloc the whole memory_set
-        // otherwise they will be deallocated twice
-        let mut recycle_res = Vec::<TaskUserRes>::new();
-        for task in process_inner.tasks.iter().filter(|t| t.is_some()) {
-            let task = task.as_ref().unwrap();
-            let mut task_inner = task.inner_exclusive_access();
-            if let Some(res) = task_inner.res.take() {
-                recycle_res.push(res);
-            }
-        }
-        // dealloc_tid and dealloc_user_res require access to PCB inner, so we
-        // need to collect those user res first, then release process_inner
-        // for now to avoid deadlock/double borrow problem.
-        drop(process_inner);
-        recycle_res.clear();
-
-        let mut process_inner = process.inner_exclusive_access();
-        process_inner.children.clear();
-        // deallocate other data in user space i.e. program code/data section
-        process_inner.memory_set.recycle_data_pages();
-        // drop file descriptors
-        process_inner.fd_table.clear();
+    // the shared address space goes with the last thread, whichever it is
+    let last_thread = process
+        .inner_exclusive_access()
+        .tasks
+        .iter()
+        .flatten()
+        .all(|task| task.inner_exclusive_access().res.is_none());
+    if last_thread {
+        exit_process(&process, exit_code, reason);
     }
     drop(process);
     // we do not have to save task context
@@ -134,11 +140,172 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     schedule(&mut _unused as *mut _);
 }
 
+/// Tear down `process` once none of its threads is left, recording why it
+/// ended.
+fn exit_process(process: &Arc<ProcessControlBlock>, exit_code: i32, reason: ExitReason) {
+    let pid = process.getpid();
+    if pid == IDLE_PID {
+        println!(
+            "[kernel] Idle process exit with exit_code {} ...",
+            exit_code
+        );
+        if exit_code != 0 {
+            //crate::sbi::shutdown(255); //255 == -1 for err hint
+            shutdown(true);
+        } else {
+            //crate::sbi::shutdown(0); //0 for success hint
+            shutdown(false);
+        }
+    }
+    remove_from_pid2process(pid);
+    let mut process_inner = process.inner_exclusive_access();
+    // mark this process as a zombie process
+    process_inner.is_zombie = true;
+    // record exit code of the last thread
+    process_inner.exit_code = exit_code;
+    process_inner.exit_reason = reason;
+    match process_inner.exit_reason {
+        ExitReason::Normal => info!("[kernel] pid {} exited with code {}", pid, exit_code),
+        ExitReason::Killed(signal) => info!("[kernel] pid {} killed by signal {}", pid, signal),
+    }
+
+    {
+        // move all child processes under init process
+        let mut initproc_inner = INITPROC.inner_exclusive_access();
+        for child in process_inner.children.iter() {
+            child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+            initproc_inner.children.push(child.clone());
+        }
+    }
+
+    // threads blocked on a futex must not be woken once their res is gone
+    futex_forget_process(pid);
+    // deallocate user res (including tid/trap_cx/ustack) of all threads
+    // it has to be done before we dealloc the whole memory_set
+    // otherwise they will be deallocated twice
+    let mut recycle_res = Vec::<TaskUserRes>::new();
+    for task in process_inner.tasks.iter().filter(|t| t.is_some()) {
+        let task = task.as_ref().unwrap();
+        let mut task_inner = task.inner_exclusive_access();
+        if let Some(res) = task_inner.res.take() {
+            recycle_res.push(res);
+        }
+    }
+    // dealloc_tid and dealloc_user_res require access to PCB inner, so we
+    // need to collect those user res first, then release process_inner
+    // for now to avoid deadlock/double borrow problem.
+    drop(process_inner);
+    recycle_res.clear();
+
+    let mut process_inner = process.inner_exclusive_access();
+    process_inner.children.clear();
+    // deallocate other data in user space i.e. program code/data section
+    process_inner.memory_set.recycle_data_pages();
+    // drop file descriptors
+    process_inner.fd_table.clear();
+    // and the wait queues still holding killed threads
+    process_inner.mutex_list.clear();
+    process_inner.semaphore_list.clear();
+    process_inner.condvar_list.clear();
+    process_inner.pause_waiters.clear();
+    process_inner.wait_waiters.clear();
+    let parent = process_inner
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade());
+    drop(process_inner);
+    // initproc may wait on the zombies it just inherited
+    if let Some(parent) = parent {
+        parent.wake_wait_waiters();
+    }
+    INITPROC.wake_wait_waiters();
+}
+
+/// Exit the current process with `exit_code`, but not its children. The
+/// other threads are killed wherever they are: ready, sleeping on a timer or
+/// blocked.
+pub fn exit_process_current_and_run_next(exit_code: i32) {
+    exit_process_for(exit_code, ExitReason::Normal);
+}
+
+/// Kill the other threads of the current process, then exit the current one
+/// as the last, ending the process for `reason`.
+fn exit_process_for(exit_code: i32, reason: ExitReason) {
+    let current = current_task().unwrap();
+    let others: Vec<Arc<TaskControlBlock>> = current_process()
+        .inner_exclusive_access()
+        .tasks
+        .iter()
+        .flatten()
+        .filter(|task| !Arc::ptr_eq(task, &current))
+        .cloned()
+        .collect();
+    drop(current);
+    let mut recycle_res = Vec::<TaskUserRes>::new();
+    for task in others {
+        // once off the ready queue and the timers, a thread can only be
+        // woken through the process, which the teardown clears
+        remove_task(&task);
+        remove_timer(&task);
+        let mut task_inner = task.inner_exclusive_access();
+        if let Some(res) = task_inner.res.take() {
+            task_inner.exit_code = Some(exit_code);
+            recycle_res.push(res);
+        }
+    }
+    // dropping the res needs the process inner, which is free again
+    recycle_res.clear();
+    exit_current_thread(exit_code, reason);
+}
+
+/// Exit the current process, asking every descendant process to exit with
+/// `exit_code` too. They leave at their next trap, see
+/// [`check_group_exit_of_current`].
+pub fn exit_group_current_and_run_next(exit_code: i32) {
+    fn mark(process: &Arc<ProcessControlBlock>, exit_code: i32) {
+        let mut inner = process.inner_exclusive_access();
+        inner.group_exit.get_or_insert(exit_code);
+        let children = inner.children.clone();
+        drop(inner);
+        for child in children.iter() {
+            mark(child, exit_code);
+        }
+    }
+    mark(&current_process(), exit_code);
+    exit_process_current_and_run_next(exit_code);
+}
+
+/// Exit the current process because of a fatal signal, remembering it as
+/// the reason the process ended.
+pub fn kill_current_and_run_next(errno: i32) {
+    exit_process_for(errno, ExitReason::Killed(-errno));
+}
+
+pub fn check_group_exit_of_current() -> Option<i32> {
+    current_process().inner_exclusive_access().group_exit
+}
+
+/// `INIT_APP_NAME` if there is such an app, otherwise the first app found.
+pub fn init_app_name() -> String {
+    if open_file(INIT_APP_NAME, OpenFlags::RDONLY).is_some() {
+        return String::from(INIT_APP_NAME);
+    }
+    let first = app_names().into_iter().next().expect("no app to run");
+    println!(
+        "[kernel] init app {} not found, starting {}",
+        INIT_APP_NAME, first
+    );
+    first
+}
+
 lazy_static! {
     pub static ref INITPROC: Arc<ProcessControlBlock> = {
-        let inode = open_file("initproc", OpenFlags::RDONLY).unwrap();
+        let name = init_app_name();
+        let inode = open_file(&name, OpenFlags::RDONLY).unwrap();
         let v = inode.read_all();
-        ProcessControlBlock::new(v.as_slice())
+        let process = ProcessControlBlock::new(&name, v.as_slice()).unwrap();
+        process.inner_exclusive_access().privileged = true;
+        process
     };
 }
 
@@ -153,7 +320,44 @@ pub fn check_signals_of_current() -> Option<(i32, &'static str)> {
 }
 
 pub fn current_add_signal(signal: SignalFlags) {
-    let process = current_process();
-    let mut process_inner = process.inner_exclusive_access();
-    process_inner.signals |= signal;
+    current_process().add_signal(signal);
+}
+
+/// Whether the timer may preempt the current task. Preemption that was first
+/// turned off `PREEMPT_DISABLE_MAX_MS` ago, since the task got the CPU, is
+/// turned back on.
+pub fn check_preempt_of_current() -> bool {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    if !task_inner.preempt_disabled {
+        return true;
+    }
+    match task_inner.preempt_off_since_ms {
+        Some(since_ms) if get_time_ms() - since_ms >= PREEMPT_DISABLE_MAX_MS => {
+            warn!(
+                "[kernel] pid {} kept preemption off for over {}ms, turned it back on",
+                task.tgid, PREEMPT_DISABLE_MAX_MS
+            );
+            task_inner.preempt_disabled = false;
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn check_alarm_of_current() {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    // the handler is still running, wait for it to sigreturn
+    if task_inner.trap_cx_backup.is_some() {
+        return;
+    }
+    if let Some(alarm) = task_inner.alarm {
+        if alarm.expire_ms <= get_time_ms() {
+            task_inner.alarm = None;
+            let trap_cx = task_inner.get_trap_cx();
+            task_inner.trap_cx_backup = Some(trap_cx.clone());
+            trap_cx.sepc = alarm.handler;
+        }
+    }
 }
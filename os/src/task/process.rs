@@ -1,16 +1,19 @@
-use super::id::RecycleAllocator;
+use super::id::{map_user_res, ustack_bottom_from_tid, RecycleAllocator};
 use super::manager::insert_into_pid2process;
 use super::TaskControlBlock;
-use super::{add_task, SignalFlags};
+use super::{add_task, wakeup_task, Environ, ExitReason, SignalFlags};
 use super::{pid_alloc, PidHandle};
+use crate::config::{APP_STACK_SIZES, USER_STACK_SIZE, WORKING_SET_WINDOW_MS};
 use crate::fs::{File, Stdin, Stdout};
-use crate::mm::{translated_refmut, MemorySet, KERNEL_SPACE};
+use crate::mm::{translated_refmut, user_buffer_valid, LoadError, MemorySet, KERNEL_SPACE};
 use crate::sync::{Condvar, Mutex, Semaphore, UPIntrFreeCell, UPIntrRefMut};
+use crate::timer::get_time_ms;
 use crate::trap::{trap_handler, TrapContext};
 use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use alloc::vec;
 use alloc::vec::Vec;
+use core::ops::Range;
 
 pub struct ProcessControlBlock {
     // immutable
@@ -32,6 +35,46 @@ pub struct ProcessControlBlockInner {
     pub mutex_list: Vec<Option<Arc<dyn Mutex>>>,
     pub semaphore_list: Vec<Option<Arc<Semaphore>>>,
     pub condvar_list: Vec<Option<Arc<Condvar>>>,
+    /// name of the app this process runs
+    pub app_name: String,
+    /// user space range occupied by the app image
+    pub app_range: Range<usize>,
+    /// exit code requested by an exit_group of this process or an ancestor
+    pub group_exit: Option<i32>,
+    pub exit_reason: ExitReason,
+    /// largest `rss_pages` seen so far, see `update_max_rss`
+    pub max_rss_pages: usize,
+    /// user pages accessed in the last working set window, see
+    /// `sample_working_set`
+    pub working_set_pages: usize,
+    /// end of the current working set window
+    pub working_set_window_end_ms: usize,
+    /// size of each thread's user stack, from `app_stack_size`
+    pub ustack_size: usize,
+    /// threads blocked in `sys_pause` until a signal arrives
+    pub pause_waiters: Vec<Arc<TaskControlBlock>>,
+    /// threads blocked in `sys_waitpid` until a child exits
+    pub wait_waiters: Vec<Arc<TaskControlBlock>>,
+    /// environment variables, copied by fork and spawn and kept across exec
+    pub environ: Environ,
+    /// most pages `mmap` may have added at a time, `RLIM_INFINITY` for no
+    /// limit; inherited like `environ`
+    pub mmap_page_limit: usize,
+    /// may use the privileged syscalls; only initproc starts with it, fork
+    /// and spawn pass it on and `sys_drop_privilege` clears it for good
+    pub privileged: bool,
+}
+
+/// No limit, for the resource limits kept in the PCB.
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// User stack size for `app_name`, `USER_STACK_SIZE` unless listed in
+/// `APP_STACK_SIZES`.
+fn app_stack_size(app_name: &str) -> usize {
+    APP_STACK_SIZES
+        .iter()
+        .find(|(name, _)| *name == app_name)
+        .map_or(USER_STACK_SIZE, |(_, size)| *size)
 }
 
 impl ProcessControlBlockInner {
@@ -40,6 +83,25 @@ impl ProcessControlBlockInner {
         self.memory_set.token()
     }
 
+    /// Fold the current resident set into `max_rss_pages` and return it. Called
+    /// whenever user memory grows.
+    pub fn update_max_rss(&mut self) -> usize {
+        self.max_rss_pages = self.max_rss_pages.max(self.memory_set.rss_pages());
+        self.max_rss_pages
+    }
+
+    /// Estimate `working_set_pages` anew if the window has ended. Called on
+    /// the timer ticks of profiling threads; the accessed bits belong to the
+    /// address space, so all threads share one window and one estimate.
+    pub fn sample_working_set(&mut self) {
+        let now_ms = get_time_ms();
+        if now_ms < self.working_set_window_end_ms {
+            return;
+        }
+        self.working_set_window_end_ms = now_ms + WORKING_SET_WINDOW_MS;
+        self.working_set_pages = self.memory_set.sample_working_set();
+    }
+
     pub fn alloc_fd(&mut self) -> usize {
         if let Some(fd) = (0..self.fd_table.len()).find(|fd| self.fd_table[*fd].is_none()) {
             fd
@@ -71,9 +133,12 @@ impl ProcessControlBlock {
         self.inner.exclusive_access()
     }
 
-    pub fn new(elf_data: &[u8]) -> Arc<Self> {
+    /// Fails if `elf_data` is not a valid app image, or if there are not
+    /// enough frames to load it.
+    pub fn new(app_name: &str, elf_data: &[u8]) -> Result<Arc<Self>, LoadError> {
         // memory_set with elf program headers/trampoline/trap context/user stack
-        let (memory_set, ustack_base, entry_point) = MemorySet::from_elf(elf_data);
+        let (memory_set, app_range, ustack_base, entry_point) =
+            MemorySet::from_elf(app_name, elf_data)?;
         // allocate a pid
         let pid_handle = pid_alloc();
         let process = Arc::new(Self {
@@ -99,15 +164,27 @@ impl ProcessControlBlock {
                     mutex_list: Vec::new(),
                     semaphore_list: Vec::new(),
                     condvar_list: Vec::new(),
+                    app_name: String::from(app_name),
+                    app_range,
+                    group_exit: None,
+                    exit_reason: ExitReason::Normal,
+                    max_rss_pages: 0,
+                    working_set_pages: 0,
+                    working_set_window_end_ms: 0,
+                    ustack_size: app_stack_size(app_name),
+                    pause_waiters: Vec::new(),
+                    wait_waiters: Vec::new(),
+                    environ: Environ::default(),
+                    mmap_page_limit: RLIM_INFINITY,
+                    privileged: false,
                 })
             },
         });
         // create a main thread, we should allocate ustack and trap_cx here
-        let task = Arc::new(TaskControlBlock::new(
-            Arc::clone(&process),
-            ustack_base,
-            true,
-        ));
+        let task = Arc::new(
+            TaskControlBlock::new(Arc::clone(&process), ustack_base, true)
+                .ok_or(LoadError::NoMem)?,
+        );
         // prepare trap_cx of main thread
         let task_inner = task.inner_exclusive_access();
         let trap_cx = task_inner.get_trap_cx();
@@ -128,26 +205,61 @@ impl ProcessControlBlock {
         insert_into_pid2process(process.getpid(), Arc::clone(&process));
         // add main thread to scheduler
         add_task(task);
-        process
+        Ok(process)
     }
 
-    /// Only support processes with a single thread.
-    pub fn exec(self: &Arc<Self>, elf_data: &[u8], args: Vec<String>) {
+    /// Only support processes with a single thread. Fails, leaving the old
+    /// image in place, if `elf_data` is not a valid app image.
+    pub fn exec(
+        self: &Arc<Self>,
+        app_name: &str,
+        elf_data: &[u8],
+        args: Vec<String>,
+    ) -> Result<(), LoadError> {
         assert_eq!(self.inner_exclusive_access().thread_count(), 1);
         // memory_set with elf program headers/trampoline/trap context/user stack
-        let (memory_set, ustack_base, entry_point) = MemorySet::from_elf(elf_data);
+        let (mut memory_set, app_range, ustack_base, entry_point) =
+            MemorySet::from_elf(app_name, elf_data)?;
         let new_token = memory_set.token();
-        // substitute memory_set
-        self.inner_exclusive_access().memory_set = memory_set;
-        // then we alloc user resource for main thread again
-        // since memory_set has been changed
+        let ustack_size = app_stack_size(app_name);
+        // then we alloc user resource for main thread again, in the new
+        // memory_set, so that running out of frames leaves the old image
         let task = self.inner_exclusive_access().get_task(0);
         let mut task_inner = task.inner_exclusive_access();
-        task_inner.res.as_mut().unwrap().ustack_base = ustack_base;
-        task_inner.res.as_mut().unwrap().alloc_user_res();
-        task_inner.trap_cx_ppn = task_inner.res.as_mut().unwrap().trap_cx_ppn();
-        // push arguments on user stack
-        let mut user_sp = task_inner.res.as_mut().unwrap().ustack_top();
+        let tid = task_inner.res.as_ref().unwrap().tid;
+        if !map_user_res(&mut memory_set, ustack_base, ustack_size, tid) {
+            return Err(LoadError::NoMem);
+        }
+        // arguments go on the user stack, whose lower pages are not mapped yet
+        let mut user_sp = ustack_bottom_from_tid(ustack_base, ustack_size, tid) + ustack_size;
+        let args_size = (args.len() + 1) * core::mem::size_of::<usize>()
+            + args.iter().map(|arg| arg.len() + 1).sum::<usize>()
+            + core::mem::size_of::<usize>();
+        if !user_buffer_valid(
+            &mut memory_set,
+            (user_sp - args_size) as *const u8,
+            args_size,
+            true,
+        ) {
+            return Err(LoadError::NoMem);
+        }
+        // substitute memory_set
+        let mut inner = self.inner_exclusive_access();
+        inner.memory_set = memory_set;
+        inner.app_name = String::from(app_name);
+        inner.app_range = app_range;
+        inner.ustack_size = ustack_size;
+        drop(inner);
+        let res = task_inner.res.as_mut().unwrap();
+        res.ustack_base = ustack_base;
+        res.ustack_size = ustack_size;
+        task_inner.trap_cx_ppn = task_inner.res.as_ref().unwrap().trap_cx_ppn();
+        // the saved registers, the alarm handler and the sampled PCs all
+        // belong to the old image
+        task_inner.checkpoint = None;
+        task_inner.trap_cx_backup = None;
+        task_inner.alarm = None;
+        task_inner.profile = None;
         user_sp -= (args.len() + 1) * core::mem::size_of::<usize>();
         let argv_base = user_sp;
         let mut argv: Vec<_> = (0..=args.len())
@@ -182,14 +294,16 @@ impl ProcessControlBlock {
         trap_cx.x[10] = args.len();
         trap_cx.x[11] = argv_base;
         *task_inner.get_trap_cx() = trap_cx;
+        Ok(())
     }
 
-    /// Only support processes with a single thread.
-    pub fn fork(self: &Arc<Self>) -> Arc<Self> {
+    /// Only support processes with a single thread. `None` if there are not
+    /// enough frames outside the reserve to copy its memory.
+    pub fn fork(self: &Arc<Self>) -> Option<Arc<Self>> {
         let mut parent = self.inner_exclusive_access();
         assert_eq!(parent.thread_count(), 1);
         // clone parent's memory_set completely including trampoline/ustacks/trap_cxs
-        let memory_set = MemorySet::from_existed_user(&parent.memory_set);
+        let memory_set = MemorySet::from_existed_user(&parent.memory_set)?;
         // alloc a pid
         let pid = pid_alloc();
         // copy fd table
@@ -218,25 +332,42 @@ impl ProcessControlBlock {
                     mutex_list: Vec::new(),
                     semaphore_list: Vec::new(),
                     condvar_list: Vec::new(),
+                    app_name: parent.app_name.clone(),
+                    app_range: parent.app_range.clone(),
+                    group_exit: None,
+                    exit_reason: ExitReason::Normal,
+                    max_rss_pages: 0,
+                    working_set_pages: 0,
+                    working_set_window_end_ms: 0,
+                    ustack_size: parent.ustack_size,
+                    pause_waiters: Vec::new(),
+                    wait_waiters: Vec::new(),
+                    environ: parent.environ.clone(),
+                    mmap_page_limit: parent.mmap_page_limit,
+                    privileged: parent.privileged,
                 })
             },
         });
         // add child
         parent.children.push(Arc::clone(&child));
         // create main thread of child process
-        let task = Arc::new(TaskControlBlock::new(
-            Arc::clone(&child),
-            parent
-                .get_task(0)
-                .inner_exclusive_access()
-                .res
-                .as_ref()
-                .unwrap()
-                .ustack_base(),
-            // here we do not allocate trap_cx or ustack again
-            // but mention that we allocate a new kstack here
-            false,
-        ));
+        let task = Arc::new(
+            TaskControlBlock::new(
+                Arc::clone(&child),
+                parent
+                    .get_task(0)
+                    .inner_exclusive_access()
+                    .res
+                    .as_ref()
+                    .unwrap()
+                    .ustack_base(),
+                // here we do not allocate trap_cx or ustack again
+                // but mention that we allocate a new kstack here
+                false,
+            )
+            // with no user res to allocate, this cannot fail
+            .unwrap(),
+        );
         // attach task to child process
         let mut child_inner = child.inner_exclusive_access();
         child_inner.tasks.push(Some(Arc::clone(&task)));
@@ -249,10 +380,52 @@ impl ProcessControlBlock {
         insert_into_pid2process(child.getpid(), Arc::clone(&child));
         // add this thread to scheduler
         add_task(task);
-        child
+        Some(child)
+    }
+
+    /// Start `app_name` as a new child process without copying our address
+    /// space. The child inherits our file descriptors and environment, like
+    /// fork + exec.
+    pub fn spawn(
+        self: &Arc<Self>,
+        app_name: &str,
+        elf_data: &[u8],
+    ) -> Result<Arc<Self>, LoadError> {
+        let child = Self::new(app_name, elf_data)?;
+        let mut parent = self.inner_exclusive_access();
+        let mut child_inner = child.inner_exclusive_access();
+        child_inner.parent = Some(Arc::downgrade(self));
+        child_inner.fd_table = parent.fd_table.clone();
+        child_inner.environ = parent.environ.clone();
+        child_inner.mmap_page_limit = parent.mmap_page_limit;
+        child_inner.privileged = parent.privileged;
+        drop(child_inner);
+        parent.children.push(Arc::clone(&child));
+        Ok(child)
     }
 
     pub fn getpid(&self) -> usize {
         self.pid.0
     }
+
+    /// Raise `signal` and wake the threads waiting for one in `sys_pause`, as
+    /// well as those blocked in `sys_waitpid` so that they can be killed.
+    pub fn add_signal(&self, signal: SignalFlags) {
+        let mut inner = self.inner_exclusive_access();
+        inner.signals |= signal;
+        let mut waiters = core::mem::take(&mut inner.pause_waiters);
+        waiters.append(&mut inner.wait_waiters);
+        drop(inner);
+        for task in waiters {
+            wakeup_task(task);
+        }
+    }
+
+    /// Wake the threads blocked in `sys_waitpid`, after a child has exited.
+    pub fn wake_wait_waiters(&self) {
+        let waiters = core::mem::take(&mut self.inner_exclusive_access().wait_waiters);
+        for task in waiters {
+            wakeup_task(task);
+        }
+    }
 }
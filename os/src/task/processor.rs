@@ -1,10 +1,32 @@
 use super::__switch;
+use super::metric::{add_idle_time, inc_switch_count};
+use super::sched_trace::trace_switch_in;
 use super::{fetch_task, TaskStatus};
 use super::{ProcessControlBlock, TaskContext, TaskControlBlock};
+use crate::config::SHUTDOWN_WHEN_IDLE;
+use crate::sbi::shutdown;
 use crate::sync::UPIntrFreeCell;
+use crate::timer::{get_time_ms, get_time_us, start_slice};
 use crate::trap::TrapContext;
 use alloc::sync::Arc;
+use core::arch::asm;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use lazy_static::*;
+use log::{info, trace};
+use riscv::register::sstatus;
+
+/// Id of the hart the kernel booted on, as passed in `a0` by the SBI.
+static BOOT_HART_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_boot_hart_id(hart_id: usize) {
+    BOOT_HART_ID.store(hart_id, Ordering::Relaxed);
+}
+
+/// Id of the hart running this code. There is a single hart for now, the one
+/// the kernel booted on.
+pub fn current_hart_id() -> usize {
+    BOOT_HART_ID.load(Ordering::Relaxed)
+}
 
 pub struct Processor {
     current: Option<Arc<TaskControlBlock>>,
@@ -39,23 +61,53 @@ pub fn run_tasks() {
         let mut processor = PROCESSOR.exclusive_access();
         if let Some(task) = fetch_task() {
             let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+            let pid = task.tgid;
+            trace!("[kernel] switch to pid {}", pid);
             // access coming task TCB exclusively
             let next_task_cx_ptr = task.inner.exclusive_session(|task_inner| {
                 task_inner.task_status = TaskStatus::Running;
+                task_inner.metric.switch_in();
+                let tid = task_inner.res.as_ref().unwrap().tid;
+                trace_switch_in(pid, tid);
+                if task_inner.check_deadline(get_time_us(), true) {
+                    info!("[kernel] pid {} tid {} missed its deadline", pid, tid);
+                }
+                // a fresh budget for keeping preemption off
+                task_inner.preempt_off_since_ms = task_inner.preempt_disabled.then(get_time_ms);
+                let donated_us = core::mem::take(&mut task_inner.donated_us);
+                if donated_us > 0 {
+                    start_slice(donated_us);
+                }
                 &task_inner.task_cx as *const TaskContext
             });
             processor.current = Some(task);
+            inc_switch_count();
             // release processor manually
             drop(processor);
             unsafe {
                 __switch(idle_task_cx_ptr, next_task_cx_ptr);
             }
+        } else if SHUTDOWN_WHEN_IDLE {
+            println!("[kernel] No tasks available, shutting down...");
+            shutdown(false);
         } else {
-            println!("no tasks available in run_tasks");
+            drop(processor);
+            idle();
         }
     }
 }
 
+/// Wait for the next interrupt, which may wake up a blocked task, and charge
+/// the wait to the system idle time.
+pub fn idle() {
+    let start_us = get_time_us();
+    unsafe {
+        sstatus::set_sie();
+        asm!("wfi");
+    }
+    add_idle_time(get_time_us() - start_us);
+}
+
 pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
     PROCESSOR.exclusive_access().take_current()
 }
@@ -68,6 +120,16 @@ pub fn current_process() -> Arc<ProcessControlBlock> {
     current_task().unwrap().process.upgrade().unwrap()
 }
 
+/// Pid of the current process's parent, 0 for processes without one.
+pub fn current_parent_id() -> usize {
+    current_process()
+        .inner_exclusive_access()
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map_or(0, |parent| parent.getpid())
+}
+
 pub fn current_user_token() -> usize {
     let task = current_task().unwrap();
     task.get_user_token()
@@ -94,6 +156,21 @@ pub fn current_kstack_top() -> usize {
     current_task().unwrap().kstack.get_top()
 }
 
+/// Panic if the current task's kernel stack has run into its red zone,
+/// before the corruption spreads any further.
+pub fn check_kernel_stack_of_current() {
+    let task = current_task().unwrap();
+    if task.kstack.overflowed() {
+        let pid = task.tgid;
+        let tid = task
+            .inner_exclusive_access()
+            .res
+            .as_ref()
+            .map_or(0, |res| res.tid);
+        panic!("kernel stack overflow in task {} (pid {})", tid, pid);
+    }
+}
+
 pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
     let idle_task_cx_ptr =
         PROCESSOR.exclusive_session(|processor| processor.get_idle_task_cx_ptr());
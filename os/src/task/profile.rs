@@ -0,0 +1,59 @@
+use crate::config::{PROFILE_BUCKET_BYTES, PROFILE_MAX_BUCKETS};
+use crate::timer::get_time_ms;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Samples of one bucket of user code, as copied to user space by
+/// `sys_profile_stop`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ProfileBucket {
+    /// lowest address of the bucket
+    pub pc: usize,
+    pub samples: usize,
+}
+
+/// Histogram of the user `sepc` seen on timer interrupts, started by
+/// `sys_profile_start`.
+pub struct Profile {
+    interval_ms: usize,
+    next_sample_ms: usize,
+    /// samples by bucket start, at most `PROFILE_MAX_BUCKETS` of them;
+    /// once all are taken, samples in new buckets are not counted
+    buckets: BTreeMap<usize, usize>,
+}
+
+impl Profile {
+    pub fn new(interval_ms: usize) -> Self {
+        Self {
+            interval_ms,
+            next_sample_ms: get_time_ms(),
+            buckets: BTreeMap::new(),
+        }
+    }
+    /// Count `pc` if `interval_ms` has passed since the last sample. Ticks
+    /// come once per time slice, so shorter intervals sample every tick.
+    pub fn tick(&mut self, pc: usize) {
+        let now_ms = get_time_ms();
+        if now_ms < self.next_sample_ms {
+            return;
+        }
+        self.next_sample_ms = now_ms + self.interval_ms;
+        let bucket = pc & !(PROFILE_BUCKET_BYTES - 1);
+        if let Some(samples) = self.buckets.get_mut(&bucket) {
+            *samples += 1;
+        } else if self.buckets.len() < PROFILE_MAX_BUCKETS {
+            self.buckets.insert(bucket, 1);
+        }
+    }
+    /// Buckets with the most samples first, lower addresses first on a tie.
+    pub fn top(&self) -> Vec<ProfileBucket> {
+        let mut top: Vec<ProfileBucket> = self
+            .buckets
+            .iter()
+            .map(|(&pc, &samples)| ProfileBucket { pc, samples })
+            .collect();
+        top.sort_by(|a, b| b.samples.cmp(&a.samples));
+        top
+    }
+}
@@ -0,0 +1,186 @@
+use super::TaskControlBlock;
+use crate::config::{MEMORY_PRESSURE_FRAMES, MEMORY_PRESSURE_MAX_SKIPS, VRUNTIME_HALF_LIFE_MS};
+use crate::mm::frames_free;
+use crate::timer::get_time_ms;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+
+/// Scheduling policies `TaskManager` can run with.
+#[allow(unused)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SchedulerKind {
+    /// first come, first served; preemption sends a task to the back
+    RoundRobin = 0,
+    /// round robin, but while fewer than `MEMORY_PRESSURE_FRAMES` frames are
+    /// free the ready task with the largest resident set waits behind the
+    /// others, at most `MEMORY_PRESSURE_MAX_SKIPS` picks in a row
+    MemoryAware = 1,
+    /// the task that became ready last runs first
+    Lifo = 2,
+    /// the ready task with the least recent CPU time, see `Vruntime`, runs
+    /// first; the first in the queue on a tie
+    Fair = 3,
+}
+
+impl SchedulerKind {
+    pub fn from_id(id: usize) -> Option<Self> {
+        match id {
+            0 => Some(Self::RoundRobin),
+            1 => Some(Self::MemoryAware),
+            2 => Some(Self::Lifo),
+            3 => Some(Self::Fair),
+            _ => None,
+        }
+    }
+    pub fn build(self) -> Box<dyn Scheduler> {
+        match self {
+            Self::RoundRobin => Box::new(RrScheduler),
+            Self::MemoryAware => Box::new(MemoryAwareScheduler { pressure_skips: 0 }),
+            Self::Lifo => Box::new(LifoScheduler),
+            Self::Fair => Box::new(FairScheduler),
+        }
+    }
+}
+
+/// Scheduling class of a single task, set by `sys_sched_setscheduler`.
+/// Ready `Edf` tasks run before `Priority` ones, which run before the
+/// `RoundRobin` ones the `Scheduler` picks from.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SchedPolicy {
+    /// picked by the system-wide `Scheduler`
+    RoundRobin = 0,
+    /// the highest `param` runs first, the first in the queue on a tie; a
+    /// busy task starves all `RoundRobin` ones
+    Priority = 1,
+    /// each time the task becomes ready its deadline is set `param`
+    /// milliseconds ahead; the earliest deadline runs first
+    Edf = 2,
+}
+
+impl SchedPolicy {
+    pub fn from_id(id: usize) -> Option<Self> {
+        match id {
+            0 => Some(Self::RoundRobin),
+            1 => Some(Self::Priority),
+            2 => Some(Self::Edf),
+            _ => None,
+        }
+    }
+}
+
+/// A scheduling policy. New tasks are appended to the ready queue, the
+/// scheduler only decides which of them runs next.
+pub trait Scheduler: Send {
+    /// Index in `ready` of the task to run next, `None` if `ready` is empty.
+    fn pick(&mut self, ready: &VecDeque<Arc<TaskControlBlock>>) -> Option<usize>;
+    /// Called on every timer interrupt.
+    fn on_tick(&mut self) {}
+}
+
+pub struct RrScheduler;
+
+impl Scheduler for RrScheduler {
+    fn pick(&mut self, ready: &VecDeque<Arc<TaskControlBlock>>) -> Option<usize> {
+        if ready.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+}
+
+pub struct MemoryAwareScheduler {
+    /// picks in a row that passed over the largest task under memory pressure
+    pressure_skips: usize,
+}
+
+impl MemoryAwareScheduler {
+    /// Whether the front task should wait because memory is short and it has
+    /// the largest resident set of all ready tasks.
+    fn should_skip_front(&self, ready: &VecDeque<Arc<TaskControlBlock>>) -> bool {
+        if ready.len() < 2
+            || self.pressure_skips >= MEMORY_PRESSURE_MAX_SKIPS
+            || frames_free() >= MEMORY_PRESSURE_FRAMES
+        {
+            return false;
+        }
+        let rss = |task: &Arc<TaskControlBlock>| {
+            task.process.upgrade().map_or(0, |process| {
+                process.inner_exclusive_access().memory_set.rss_pages()
+            })
+        };
+        let front = rss(&ready[0]);
+        ready.iter().skip(1).all(|task| rss(task) < front)
+    }
+}
+
+impl Scheduler for MemoryAwareScheduler {
+    fn pick(&mut self, ready: &VecDeque<Arc<TaskControlBlock>>) -> Option<usize> {
+        if self.should_skip_front(ready) {
+            self.pressure_skips += 1;
+            return Some(1);
+        }
+        self.pressure_skips = 0;
+        if ready.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+}
+
+pub struct LifoScheduler;
+
+impl Scheduler for LifoScheduler {
+    fn pick(&mut self, ready: &VecDeque<Arc<TaskControlBlock>>) -> Option<usize> {
+        ready.len().checked_sub(1)
+    }
+}
+
+/// CPU time a task used recently, halved every `VRUNTIME_HALF_LIFE_MS`.
+#[derive(Copy, Clone)]
+pub struct Vruntime {
+    us: usize,
+    /// when `us` was last decayed
+    at_ms: usize,
+}
+
+impl Vruntime {
+    pub fn new() -> Self {
+        Self {
+            us: 0,
+            at_ms: get_time_ms(),
+        }
+    }
+    fn halvings(&self, now_ms: usize) -> usize {
+        now_ms.saturating_sub(self.at_ms) / VRUNTIME_HALF_LIFE_MS
+    }
+    /// The decayed value at `now_ms`.
+    pub fn value(&self, now_ms: usize) -> usize {
+        self.us
+            .checked_shr(self.halvings(now_ms) as u32)
+            .unwrap_or(0)
+    }
+    /// Add `ran_us` of CPU time used up to now.
+    pub fn charge(&mut self, ran_us: usize) {
+        let now_ms = get_time_ms();
+        let halvings = self.halvings(now_ms);
+        self.us = self.value(now_ms) + ran_us;
+        // partial half-lives carry over to the next decay
+        self.at_ms += halvings * VRUNTIME_HALF_LIFE_MS;
+    }
+}
+
+pub struct FairScheduler;
+
+impl Scheduler for FairScheduler {
+    fn pick(&mut self, ready: &VecDeque<Arc<TaskControlBlock>>) -> Option<usize> {
+        let now_ms = get_time_ms();
+        ready
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, task)| task.inner_exclusive_access().vruntime.value(now_ms))
+            .map(|(idx, _)| idx)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::sync::UPIntrFreeCell;
+use crate::timer::get_time_us;
+use alloc::string::String;
+use core::fmt::Write;
+use lazy_static::*;
+
+/// Number of scheduling decisions kept.
+const SCHED_TRACE_LEN: usize = 32;
+
+/// Why a task left the CPU.
+#[derive(Copy, Clone)]
+pub enum SwitchReason {
+    /// yielded or preempted, still ready
+    Ready,
+    Blocked,
+    Exited,
+}
+
+impl SwitchReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ready => "ready",
+            Self::Blocked => "blocked",
+            Self::Exited => "exited",
+        }
+    }
+}
+
+/// (pid, tid) of a task.
+type TaskId = (usize, usize);
+
+#[derive(Copy, Clone)]
+struct SchedEvent {
+    time_us: usize,
+    from: Option<(TaskId, SwitchReason)>,
+    to: TaskId,
+}
+
+struct SchedTrace {
+    events: [Option<SchedEvent>; SCHED_TRACE_LEN],
+    /// slot the next event goes to
+    next: usize,
+    /// task that left the CPU, waiting to be paired with its successor
+    switched_out: Option<(TaskId, SwitchReason)>,
+}
+
+lazy_static! {
+    static ref SCHED_TRACE: UPIntrFreeCell<SchedTrace> = unsafe {
+        UPIntrFreeCell::new(SchedTrace {
+            events: [None; SCHED_TRACE_LEN],
+            next: 0,
+            switched_out: None,
+        })
+    };
+}
+
+pub fn trace_switch_out(pid: usize, tid: usize, reason: SwitchReason) {
+    SCHED_TRACE.exclusive_access().switched_out = Some(((pid, tid), reason));
+}
+
+pub fn trace_switch_in(pid: usize, tid: usize) {
+    let mut trace = SCHED_TRACE.exclusive_access();
+    let event = SchedEvent {
+        time_us: get_time_us(),
+        from: trace.switched_out.take(),
+        to: (pid, tid),
+    };
+    let next = trace.next;
+    trace.events[next] = Some(event);
+    trace.next = (next + 1) % SCHED_TRACE_LEN;
+}
+
+/// Forget all recorded decisions.
+pub fn reset_sched_trace() {
+    let mut trace = SCHED_TRACE.exclusive_access();
+    trace.events = [None; SCHED_TRACE_LEN];
+    trace.next = 0;
+    trace.switched_out = None;
+}
+
+/// Recorded decisions as text, oldest first, one per line:
+/// `<time>us <pid>:<tid> -> <pid>:<tid> (<reason>)`.
+pub fn sched_trace_text() -> String {
+    let trace = SCHED_TRACE.exclusive_access();
+    let mut text = String::new();
+    for i in 0..SCHED_TRACE_LEN {
+        if let Some(event) = trace.events[(trace.next + i) % SCHED_TRACE_LEN] {
+            let (to_pid, to_tid) = event.to;
+            match event.from {
+                Some(((pid, tid), reason)) => writeln!(
+                    text,
+                    "{}us {}:{} -> {}:{} ({})",
+                    event.time_us,
+                    pid,
+                    tid,
+                    to_pid,
+                    to_tid,
+                    reason.as_str()
+                ),
+                None => writeln!(text, "{}us - -> {}:{}", event.time_us, to_pid, to_tid),
+            }
+            .unwrap();
+        }
+    }
+    text
+}
@@ -6,7 +6,9 @@ bitflags! {
         const SIGILL    = 1 << 4;
         const SIGABRT   = 1 << 6;
         const SIGFPE    = 1 << 8;
+        const SIGKILL   = 1 << 9;
         const SIGSEGV   = 1 << 11;
+        const SIGSYS    = 1 << 31;
     }
 }
 
@@ -20,10 +22,30 @@ impl SignalFlags {
             Some((-6, "Aborted, SIGABRT=6"))
         } else if self.contains(Self::SIGFPE) {
             Some((-8, "Erroneous Arithmetic Operation, SIGFPE=8"))
+        } else if self.contains(Self::SIGKILL) {
+            Some((-9, "Killed, SIGKILL=9"))
         } else if self.contains(Self::SIGSEGV) {
             Some((-11, "Segmentation Fault, SIGSEGV=11"))
+        } else if self.contains(Self::SIGSYS) {
+            Some((-31, "Bad System Call, SIGSYS=31"))
         } else {
             None
         }
     }
 }
+
+/// A one-shot alarm armed by `sys_setalarm`.
+#[derive(Copy, Clone)]
+pub struct Alarm {
+    pub expire_ms: usize,
+    pub handler: usize,
+}
+
+/// How a process ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExitReason {
+    /// called `sys_exit` (or `sys_exit_group`)
+    Normal,
+    /// killed by the signal with this number
+    Killed(i32),
+}
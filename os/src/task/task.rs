@@ -1,15 +1,23 @@
 use super::id::TaskUserRes;
-use super::{kstack_alloc, KernelStack, ProcessControlBlock, TaskContext};
+use super::metric::on_task_create;
+use super::{
+    kstack_alloc, Alarm, KernelStack, ProcessControlBlock, Profile, SchedPolicy, TaskContext,
+    TaskMetric, Vruntime,
+};
 use crate::trap::TrapContext;
 use crate::{
     mm::PhysPageNum,
     sync::{UPIntrFreeCell, UPIntrRefMut},
 };
+use alloc::string::String;
 use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
 
 pub struct TaskControlBlock {
     // immutable
     pub process: Weak<ProcessControlBlock>,
+    /// Thread group id: the pid of the process, shared by all its threads.
+    pub tgid: usize,
     pub kstack: KernelStack,
     // mutable
     pub inner: UPIntrFreeCell<TaskControlBlockInner>,
@@ -33,6 +41,45 @@ pub struct TaskControlBlockInner {
     pub task_cx: TaskContext,
     pub task_status: TaskStatus,
     pub exit_code: Option<i32>,
+    pub metric: TaskMetric,
+    pub alarm: Option<Alarm>,
+    /// Trap context saved while the alarm handler runs, restored by `sys_sigreturn`.
+    pub trap_cx_backup: Option<TrapContext>,
+    /// Trap context saved by `sys_checkpoint`, reloaded by `sys_restore`.
+    pub checkpoint: Option<TrapContext>,
+    /// Written before each line this task prints to stdout, as `[prefix] `.
+    pub stdout_prefix: Option<String>,
+    /// Whether this task's stdout output so far ends with a newline.
+    pub stdout_at_line_start: bool,
+    /// Set by `sys_set_stdout_mode`: hold stdout output back until a line
+    /// is complete.
+    pub stdout_line_buffered: bool,
+    /// The incomplete line held back in line-buffered mode.
+    pub stdout_buffer: Vec<u8>,
+    /// Soft deadline set by `sys_yield_deadline`. Ready tasks with the earliest
+    /// deadline are picked first, tasks without one after all others. Cleared
+    /// when the task is picked, so it only counts for one wait.
+    pub deadline_ms: Option<usize>,
+    /// Whether `deadline_ms` was already met or counted as missed.
+    pub deadline_settled: bool,
+    /// Quantum handed over by `sys_yield_donate`, added to this task's next
+    /// time slice.
+    pub donated_us: usize,
+    /// Recent CPU time, what `SchedulerKind::Fair` picks by.
+    pub vruntime: Vruntime,
+    /// PC samples taken on timer interrupts since `sys_profile_start`.
+    pub profile: Option<Profile>,
+    /// Whether `sys_preempt_disable` turned preemption off.
+    pub preempt_disabled: bool,
+    /// When preemption was first off since the task last got the CPU. Turning
+    /// it back on keeps this, so that toggling does not renew the
+    /// `PREEMPT_DISABLE_MAX_MS` budget.
+    pub preempt_off_since_ms: Option<usize>,
+    /// Set by `sys_trace_syscalls`: log each syscall this task makes.
+    pub trace_syscalls: bool,
+    /// Scheduling class, and its priority or relative deadline in ms.
+    pub sched_policy: SchedPolicy,
+    pub sched_param: usize,
 }
 
 impl TaskControlBlockInner {
@@ -40,6 +87,34 @@ impl TaskControlBlockInner {
         self.trap_cx_ppn.get_mut()
     }
 
+    /// Whether the deadline passed, at `now_us`, while this task was waiting in
+    /// the ready queue. A miss is counted once; after that, or once the task
+    /// got the CPU (`running`), the deadline is settled. Getting the CPU also
+    /// clears it, so that a busy task cannot keep running ahead of the others.
+    pub fn check_deadline(&mut self, now_us: usize, running: bool) -> bool {
+        let deadline_ms = if running {
+            self.deadline_ms.take()
+        } else {
+            self.deadline_ms
+        };
+        let deadline_us = match deadline_ms {
+            Some(ms) if !self.deadline_settled => ms * 1000,
+            _ => return false,
+        };
+        let missed = now_us > deadline_us && self.metric.ready_since_us() <= deadline_us;
+        if missed {
+            self.metric.deadline_misses += 1;
+        }
+        self.deadline_settled = missed || running;
+        missed
+    }
+
+    /// The task is about to `__switch` away: close its time accounting.
+    pub fn switch_out(&mut self) {
+        self.vruntime.charge(self.metric.on_cpu_us());
+        self.metric.switch_out();
+    }
+
     #[allow(unused)]
     fn get_status(&self) -> TaskStatus {
         self.task_status
@@ -47,17 +122,21 @@ impl TaskControlBlockInner {
 }
 
 impl TaskControlBlock {
+    /// `None` if `alloc_user_res` is set but there are not enough frames for
+    /// the user stack and trap context.
     pub fn new(
         process: Arc<ProcessControlBlock>,
         ustack_base: usize,
         alloc_user_res: bool,
-    ) -> Self {
-        let res = TaskUserRes::new(Arc::clone(&process), ustack_base, alloc_user_res);
+    ) -> Option<Self> {
+        let res = TaskUserRes::new(Arc::clone(&process), ustack_base, alloc_user_res)?;
+        let (pid, tid) = (process.getpid(), res.tid);
         let trap_cx_ppn = res.trap_cx_ppn();
         let kstack = kstack_alloc();
         let kstack_top = kstack.get_top();
-        Self {
+        let task = Self {
             process: Arc::downgrade(&process),
+            tgid: pid,
             kstack,
             inner: unsafe {
                 UPIntrFreeCell::new(TaskControlBlockInner {
@@ -66,9 +145,29 @@ impl TaskControlBlock {
                     task_cx: TaskContext::goto_trap_return(kstack_top),
                     task_status: TaskStatus::Ready,
                     exit_code: None,
+                    metric: TaskMetric::new(),
+                    alarm: None,
+                    trap_cx_backup: None,
+                    checkpoint: None,
+                    stdout_prefix: None,
+                    stdout_at_line_start: true,
+                    stdout_line_buffered: false,
+                    stdout_buffer: Vec::new(),
+                    deadline_ms: None,
+                    deadline_settled: true,
+                    donated_us: 0,
+                    vruntime: Vruntime::new(),
+                    profile: None,
+                    preempt_disabled: false,
+                    preempt_off_since_ms: None,
+                    trace_syscalls: false,
+                    sched_policy: SchedPolicy::RoundRobin,
+                    sched_param: 0,
                 })
             },
-        }
+        };
+        on_task_create(pid, tid);
+        Some(task)
     }
 }
 
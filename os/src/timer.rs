@@ -1,6 +1,8 @@
 use core::cmp::Ordering;
+use core::sync::atomic::{self, AtomicUsize};
 
 use crate::config::CLOCK_FREQ;
+use crate::fdt;
 use crate::sbi::set_timer;
 use crate::sync::UPIntrFreeCell;
 use crate::task::{wakeup_task, TaskControlBlock};
@@ -11,17 +13,95 @@ use riscv::register::time;
 
 const TICKS_PER_SEC: usize = 100;
 const MSEC_PER_SEC: usize = 1000;
+const USEC_PER_SEC: usize = 1_000_000;
+const NSEC_PER_SEC: usize = 1_000_000_000;
+/// Bounds for the preemption quantum set by `sys_set_timeslice`.
+const MIN_TIMESLICE_US: usize = 1_000;
+const MAX_TIMESLICE_US: usize = 1_000_000;
 
 pub fn get_time() -> usize {
     time::read()
 }
 
+/// `time` ticks per second: the timebase frequency of the device tree, or
+/// the board's `CLOCK_FREQ` if there is none.
+static CLOCK_FREQ_HZ: AtomicUsize = AtomicUsize::new(CLOCK_FREQ);
+
+/// Take the timebase frequency from the device tree at `dtb_pa`, if there is
+/// a valid one. Must run before `mm::init`.
+pub fn init_clock_freq(dtb_pa: usize) {
+    let hz = unsafe { fdt::blob_at(dtb_pa) }
+        .and_then(|blob| fdt::property(blob, "timebase-frequency"))
+        .and_then(fdt::cell_value)
+        .filter(|&hz| hz >= USEC_PER_SEC);
+    if let Some(hz) = hz {
+        CLOCK_FREQ_HZ.store(hz, atomic::Ordering::Relaxed);
+    }
+}
+
+pub fn clock_freq() -> usize {
+    CLOCK_FREQ_HZ.load(atomic::Ordering::Relaxed)
+}
+
+/// Length of one `time` tick in nanoseconds, rounded up.
+pub fn clock_resolution_ns() -> usize {
+    let hz = clock_freq();
+    (NSEC_PER_SEC + hz - 1) / hz
+}
+
 pub fn get_time_ms() -> usize {
-    time::read() / (CLOCK_FREQ / MSEC_PER_SEC)
+    time::read() / (clock_freq() / MSEC_PER_SEC)
+}
+
+pub fn get_time_us() -> usize {
+    time::read() * USEC_PER_SEC / clock_freq()
+}
+
+/// Time since boot as exchanged with user space.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct TimeVal {
+    pub sec: usize,
+    pub usec: usize,
+}
+
+impl TimeVal {
+    pub fn to_ms(self) -> usize {
+        self.sec * MSEC_PER_SEC + self.usec / (USEC_PER_SEC / MSEC_PER_SEC)
+    }
+}
+
+lazy_static! {
+    static ref TIMESLICE_US: UPIntrFreeCell<usize> =
+        unsafe { UPIntrFreeCell::new(USEC_PER_SEC / TICKS_PER_SEC) };
+    /// `time` value the pending timer interrupt is set for.
+    static ref NEXT_TRIGGER: UPIntrFreeCell<usize> = unsafe { UPIntrFreeCell::new(0) };
+}
+
+/// Set the preemption quantum, clamped to a sane range. It takes effect
+/// from the next `set_next_trigger`. Returns the quantum actually used.
+pub fn set_timeslice(us: usize) -> usize {
+    let us = us.clamp(MIN_TIMESLICE_US, MAX_TIMESLICE_US);
+    *TIMESLICE_US.exclusive_access() = us;
+    us
 }
 
 pub fn set_next_trigger() {
-    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
+    start_slice(0);
+}
+
+/// Restart the quantum with `extra_us` on top of a full time slice.
+pub fn start_slice(extra_us: usize) {
+    let slice_us = *TIMESLICE_US.exclusive_access() + extra_us;
+    let next = get_time() + clock_freq() * slice_us / USEC_PER_SEC;
+    *NEXT_TRIGGER.exclusive_access() = next;
+    set_timer(next);
+}
+
+/// Time left until the pending timer interrupt ends the current quantum.
+pub fn remaining_slice_us() -> usize {
+    let next = *NEXT_TRIGGER.exclusive_access();
+    next.saturating_sub(get_time()) * USEC_PER_SEC / clock_freq()
 }
 
 pub struct TimerCondVar {
@@ -59,6 +139,17 @@ pub fn add_timer(expire_ms: usize, task: Arc<TaskControlBlock>) {
     timers.push(TimerCondVar { expire_ms, task });
 }
 
+/// Drop the pending timers of `task`, which will not be woken any more.
+pub fn remove_timer(task: &Arc<TaskControlBlock>) {
+    TIMERS.exclusive_session(|timers| {
+        let kept: BinaryHeap<TimerCondVar> = core::mem::take(timers)
+            .into_iter()
+            .filter(|timer| !Arc::ptr_eq(&timer.task, task))
+            .collect();
+        *timers = kept;
+    });
+}
+
 pub fn check_timer() {
     let current_ms = get_time_ms();
     TIMERS.exclusive_session(|timers| {
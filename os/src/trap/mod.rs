@@ -1,21 +1,57 @@
 mod context;
+mod stats;
 
-use crate::config::TRAMPOLINE;
+use crate::config::{
+    DETERMINISTIC_SCHED, MAX_KERNEL_TRAP_DEPTH, NULL_FAULT_PANICS, PAGE_SIZE, TRAMPOLINE,
+};
+use crate::sync::UPSafeCellRaw;
 use crate::syscall::syscall;
 use crate::task::{
-    check_signals_of_current, current_add_signal, current_trap_cx, current_trap_cx_user_va,
-    current_user_token, exit_current_and_run_next, suspend_current_and_run_next, SignalFlags,
+    check_alarm_of_current, check_deadlines, check_group_exit_of_current,
+    check_kernel_stack_of_current, check_preempt_of_current, check_signals_of_current,
+    current_add_signal, current_process, current_task, current_trap_cx, current_trap_cx_user_va,
+    current_user_token, exit_process_current_and_run_next, kill_current_and_run_next,
+    scheduler_tick, suspend_current_and_run_next, SignalFlags,
 };
 use crate::timer::{check_timer, set_next_trigger};
 use core::arch::{asm, global_asm};
+use lazy_static::*;
+use log::warn;
 use riscv::register::{
     mtvec::TrapMode,
     scause::{self, Exception, Interrupt, Trap},
     sie, sip, sscratch, sstatus, stval, stvec,
 };
+use stats::count_trap;
 
 global_asm!(include_str!("trap.S"));
 
+lazy_static! {
+    /// How many `trap_from_kernel` calls are in progress.
+    static ref KERNEL_TRAP_DEPTH: UPSafeCellRaw<usize> = unsafe { UPSafeCellRaw::new(0) };
+}
+
+/// Counts one level of kernel trap nesting while alive.
+pub struct KernelTrapGuard;
+
+impl KernelTrapGuard {
+    /// `None` if this trap would nest deeper than `MAX_KERNEL_TRAP_DEPTH`.
+    pub fn enter() -> Option<Self> {
+        let depth = KERNEL_TRAP_DEPTH.get_mut();
+        if *depth >= MAX_KERNEL_TRAP_DEPTH {
+            return None;
+        }
+        *depth += 1;
+        Some(Self)
+    }
+}
+
+impl Drop for KernelTrapGuard {
+    fn drop(&mut self) {
+        *KERNEL_TRAP_DEPTH.get_mut() -= 1;
+    }
+}
+
 pub fn init() {
     set_kernel_trap_entry();
 }
@@ -59,9 +95,16 @@ fn disable_supervisor_interrupt() {
 #[no_mangle]
 pub fn trap_handler() -> ! {
     set_kernel_trap_entry();
+    check_kernel_stack_of_current();
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .metric
+        .trap_enter();
     let scause = scause::read();
     let stval = stval::read();
     // println!("into {:?}", scause.cause());
+    count_trap(scause.cause());
     match scause.cause() {
         Trap::Exception(Exception::UserEnvCall) => {
             // jump to next instruction anyway
@@ -76,6 +119,24 @@ pub fn trap_handler() -> ! {
             cx = current_trap_cx();
             cx.x[10] = result as usize;
         }
+        Trap::Exception(Exception::StoreFault) | Trap::Exception(Exception::StorePageFault)
+            if current_process()
+                .inner_exclusive_access()
+                .memory_set
+                .is_read_only(stval.into()) =>
+        {
+            current_task()
+                .unwrap()
+                .inner_exclusive_access()
+                .metric
+                .page_faults += 1;
+            println!(
+                "[kernel] write to read-only mapping, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+                stval,
+                current_trap_cx().sepc,
+            );
+            current_add_signal(SignalFlags::SIGSEGV);
+        }
         Trap::Exception(Exception::StoreFault)
         | Trap::Exception(Exception::StorePageFault)
         | Trap::Exception(Exception::InstructionFault)
@@ -90,7 +151,55 @@ pub fn trap_handler() -> ! {
                 current_trap_cx().sepc,
             );
             */
-            current_add_signal(SignalFlags::SIGSEGV);
+            current_task()
+                .unwrap()
+                .inner_exclusive_access()
+                .metric
+                .page_faults += 1;
+            let process = current_process();
+            let mut process_inner = process.inner_exclusive_access();
+            // pages of lazy mmap areas are backed on first touch
+            if process_inner.memory_set.handle_lazy_fault(stval.into()) {
+                process_inner.update_max_rss();
+            } else {
+                let ustack_bottom = current_task()
+                    .unwrap()
+                    .inner_exclusive_access()
+                    .res
+                    .as_ref()
+                    .map_or(0, |res| res.ustack_bottom());
+                // page 0 is never mapped
+                if stval < PAGE_SIZE {
+                    if NULL_FAULT_PANICS {
+                        panic!(
+                            "null pointer dereference in user space, bad addr = {:#x}, bad instruction = {:#x}",
+                            stval,
+                            current_trap_cx().sepc,
+                        );
+                    }
+                    warn!(
+                        "[kernel] null pointer dereference, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+                        stval,
+                        current_trap_cx().sepc,
+                    );
+                } else if (ustack_bottom.saturating_sub(PAGE_SIZE)..ustack_bottom).contains(&stval)
+                {
+                    // the guard page below the stack
+                    warn!(
+                        "[kernel] stack limit exceeded, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+                        stval,
+                        current_trap_cx().sepc,
+                    );
+                } else if process_inner.memory_set.recently_unmapped(stval.into()) {
+                    warn!(
+                        "[kernel] use after unmap, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+                        stval,
+                        current_trap_cx().sepc,
+                    );
+                }
+                drop(process_inner);
+                current_add_signal(SignalFlags::SIGSEGV);
+            }
         }
         Trap::Exception(Exception::IllegalInstruction) => {
             current_add_signal(SignalFlags::SIGILL);
@@ -98,7 +207,33 @@ pub fn trap_handler() -> ! {
         Trap::Interrupt(Interrupt::SupervisorTimer) => {
             set_next_trigger();
             check_timer();
-            suspend_current_and_run_next();
+            check_deadlines();
+            let sepc = current_trap_cx().sepc;
+            let task = current_task().unwrap();
+            let mut task_inner = task.inner_exclusive_access();
+            let profiling = match task_inner.profile.as_mut() {
+                Some(profile) => {
+                    profile.tick(sepc);
+                    true
+                }
+                None => false,
+            };
+            drop(task_inner);
+            drop(task);
+            if profiling {
+                current_process()
+                    .inner_exclusive_access()
+                    .sample_working_set();
+            }
+            scheduler_tick();
+            if !DETERMINISTIC_SCHED && check_preempt_of_current() {
+                current_task()
+                    .unwrap()
+                    .inner_exclusive_access()
+                    .metric
+                    .involuntary_switches += 1;
+                suspend_current_and_run_next();
+            }
         }
         Trap::Interrupt(Interrupt::SupervisorExternal) => {
             crate::board::irq_handler();
@@ -114,14 +249,25 @@ pub fn trap_handler() -> ! {
     // check signals
     if let Some((errno, msg)) = check_signals_of_current() {
         println!("[kernel] {}", msg);
-        exit_current_and_run_next(errno);
+        kill_current_and_run_next(errno);
+    }
+    // a process in our group has called exit_group
+    if let Some(exit_code) = check_group_exit_of_current() {
+        exit_process_current_and_run_next(exit_code);
     }
+    check_alarm_of_current();
     trap_return();
 }
 
 #[no_mangle]
 pub fn trap_return() -> ! {
     disable_supervisor_interrupt();
+    check_kernel_stack_of_current();
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .metric
+        .trap_return();
     set_user_trap_entry();
     let trap_cx_user_va = current_trap_cx_user_va();
     let user_satp = current_user_token();
@@ -147,6 +293,14 @@ pub fn trap_return() -> ! {
 pub fn trap_from_kernel(_trap_cx: &TrapContext) {
     let scause = scause::read();
     let stval = stval::read();
+    // traps run with interrupts off; nesting means someone turned them on
+    let _guard = KernelTrapGuard::enter().unwrap_or_else(|| {
+        panic!(
+            "Kernel trap nested deeper than {} ({:?}), are interrupts enabled in a trap handler?",
+            MAX_KERNEL_TRAP_DEPTH,
+            scause.cause()
+        )
+    });
     match scause.cause() {
         Trap::Interrupt(Interrupt::SupervisorExternal) => {
             crate::board::irq_handler();
@@ -167,3 +321,4 @@ pub fn trap_from_kernel(_trap_cx: &TrapContext) {
 }
 
 pub use context::TrapContext;
+pub use stats::trap_stats;
@@ -0,0 +1,48 @@
+use crate::sync::UPIntrFreeCell;
+use lazy_static::*;
+use riscv::register::scause::{Exception, Interrupt, Trap};
+
+/// Slots of the trap statistics table, one per trap cause `trap_handler`
+/// knows about plus one for everything else.
+pub const TRAP_USER_ENV_CALL: usize = 0;
+pub const TRAP_INSTRUCTION_FAULT: usize = 1;
+pub const TRAP_INSTRUCTION_PAGE_FAULT: usize = 2;
+pub const TRAP_LOAD_FAULT: usize = 3;
+pub const TRAP_LOAD_PAGE_FAULT: usize = 4;
+pub const TRAP_STORE_FAULT: usize = 5;
+pub const TRAP_STORE_PAGE_FAULT: usize = 6;
+pub const TRAP_ILLEGAL_INSTRUCTION: usize = 7;
+pub const TRAP_SUPERVISOR_TIMER: usize = 8;
+pub const TRAP_SUPERVISOR_EXTERNAL: usize = 9;
+pub const TRAP_OTHER: usize = 10;
+pub const TRAP_SLOTS: usize = 11;
+
+lazy_static! {
+    /// Traps from user space handled since boot, by cause.
+    static ref TRAP_STATS: UPIntrFreeCell<[usize; TRAP_SLOTS]> =
+        unsafe { UPIntrFreeCell::new([0; TRAP_SLOTS]) };
+}
+
+fn slot(cause: Trap) -> usize {
+    match cause {
+        Trap::Exception(Exception::UserEnvCall) => TRAP_USER_ENV_CALL,
+        Trap::Exception(Exception::InstructionFault) => TRAP_INSTRUCTION_FAULT,
+        Trap::Exception(Exception::InstructionPageFault) => TRAP_INSTRUCTION_PAGE_FAULT,
+        Trap::Exception(Exception::LoadFault) => TRAP_LOAD_FAULT,
+        Trap::Exception(Exception::LoadPageFault) => TRAP_LOAD_PAGE_FAULT,
+        Trap::Exception(Exception::StoreFault) => TRAP_STORE_FAULT,
+        Trap::Exception(Exception::StorePageFault) => TRAP_STORE_PAGE_FAULT,
+        Trap::Exception(Exception::IllegalInstruction) => TRAP_ILLEGAL_INSTRUCTION,
+        Trap::Interrupt(Interrupt::SupervisorTimer) => TRAP_SUPERVISOR_TIMER,
+        Trap::Interrupt(Interrupt::SupervisorExternal) => TRAP_SUPERVISOR_EXTERNAL,
+        _ => TRAP_OTHER,
+    }
+}
+
+pub fn count_trap(cause: Trap) {
+    TRAP_STATS.exclusive_access()[slot(cause)] += 1;
+}
+
+pub fn trap_stats() -> [usize; TRAP_SLOTS] {
+    *TRAP_STATS.exclusive_access()
+}
@@ -0,0 +1,46 @@
+#![no_std]
+#![no_main]
+#![feature(core_intrinsics)]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, setalarm, sleep};
+
+static mut COUNTER: usize = 0;
+
+fn handler() {
+    unsafe {
+        COUNTER += 1;
+    }
+}
+
+fn counter() -> usize {
+    vload!(&COUNTER)
+}
+
+fn wait_for(count: usize) {
+    let start = get_time();
+    let mut spins: usize = 0;
+    while counter() < count {
+        spins += 1;
+        assert!(get_time() - start < 1000, "alarm did not fire in time");
+    }
+    // the interrupted loop keeps its state across the handler
+    assert!(spins > 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(setalarm(20, handler), 0);
+    wait_for(1);
+    assert_eq!(setalarm(20, handler), 0);
+    wait_for(2);
+    // a cancelled alarm never fires
+    assert_eq!(setalarm(10, handler), 0);
+    assert_eq!(setalarm(0, handler), 0);
+    sleep(30);
+    assert_eq!(counter(), 2);
+    println!("alarm passed!");
+    0
+}
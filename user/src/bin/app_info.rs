@@ -0,0 +1,23 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::current_app_info;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [0u8; 64];
+    let len = current_app_info(&mut buf);
+    assert!(len > 0);
+    let info = core::str::from_utf8(&buf[..len as usize]).unwrap();
+    println!("app info: {}", info);
+    assert!(info.starts_with("app_info 0x"));
+    // a short buffer gets a truncated prefix
+    let mut short = [0u8; 4];
+    assert_eq!(current_app_info(&mut short), 4);
+    assert_eq!(&short, b"app_");
+    println!("app_info passed!");
+    0
+}
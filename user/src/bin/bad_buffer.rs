@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, mmap, munmap, pipe, read, write, KernelError, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+const STDOUT: usize = 1;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // a mapped page followed by an unmapped one
+    let start = mmap(0, 2 * PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    let start = start as usize;
+    assert_eq!(munmap(start + PAGE_SIZE, PAGE_SIZE), 0);
+    let straddling =
+        unsafe { core::slice::from_raw_parts_mut((start + PAGE_SIZE - 4) as *mut u8, 8) };
+    let inside = unsafe { core::slice::from_raw_parts_mut(start as *mut u8, 8) };
+    inside.copy_from_slice(b"mapped!\n");
+    assert_eq!(write(STDOUT, inside), 8);
+    assert_eq!(write(STDOUT, straddling), KernelError::Fault as isize);
+    // reading into a partly unmapped buffer is refused as well
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    assert_eq!(write(pipe_fd[1], b"12345678"), 8);
+    assert_eq!(read(pipe_fd[0], straddling), KernelError::Fault as isize);
+    assert_eq!(read(pipe_fd[0], inside), 8);
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+    println!("bad_buffer passed!");
+    0
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use user_lib::{fork, waitpid};
+
+const BAD_SYSCALL_ID: usize = 9999;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        let ret: isize;
+        unsafe {
+            asm!(
+                "ecall",
+                inlateout("x10") 0isize => ret,
+                in("x17") BAD_SYSCALL_ID
+            );
+        }
+        panic!("unsupported syscall returned {}", ret);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    // the child got SIGSYS, the kernel and this task keep running
+    assert_eq!(exit_code, -31);
+    println!("bad_syscall passed!");
+    0
+}
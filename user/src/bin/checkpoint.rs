@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use user_lib::{exit, restore, thread_create, waittid};
+
+/// ids of `checkpoint` and `restore`, called from assembly below so that the
+/// compiler keeps nothing of interest on the stack in between
+const SYSCALL_CHECKPOINT: usize = 4031;
+const SYSCALL_RESTORE: usize = 4032;
+
+fn thread_restore() -> ! {
+    // checkpoints are per thread
+    exit(restore() as i32)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(restore(), -1);
+
+    let value: usize;
+    let ret: isize;
+    let mut mutated = 0usize;
+    unsafe {
+        asm!(
+            "li t3, 42",
+            "mv a7, {checkpoint}",
+            "ecall",
+            // a0 is 1 when we get here again through restore
+            "bnez a0, 2f",
+            "li t4, 10",
+            "1:",
+            "addi t3, t3, 1",
+            "addi t4, t4, -1",
+            "bnez t4, 1b",
+            "sd t3, 0({mutated})",
+            "mv a7, {restore}",
+            "ecall",
+            "2:",
+            "mv {value}, t3",
+            checkpoint = in(reg) SYSCALL_CHECKPOINT,
+            restore = in(reg) SYSCALL_RESTORE,
+            mutated = in(reg) &mut mutated as *mut usize,
+            value = out(reg) value,
+            out("a0") ret,
+            out("a7") _,
+            out("t3") _,
+            out("t4") _,
+        );
+    }
+    println!("mutated {}, restored {}", mutated, value);
+    assert_eq!(mutated, 52);
+    assert_eq!(value, 42);
+    assert_eq!(ret, 1);
+
+    let tid = thread_create(thread_restore as usize, 0);
+    assert_eq!(waittid(tid as usize), -1);
+    println!("checkpoint passed!");
+    0
+}
@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_getres, get_time, sysinfo, SysInfo};
+
+const NSEC_PER_MSEC: usize = 1_000_000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut res_ns = 0;
+    assert_eq!(clock_getres(&mut res_ns), 0);
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    println!("clock resolution: {} ns at {} Hz", res_ns, info.timebase_hz);
+    assert!(res_ns > 0);
+    assert_eq!(
+        res_ns,
+        (1_000_000_000 + info.timebase_hz - 1) / info.timebase_hz
+    );
+    // no observed step of get_time can be finer than the clock itself
+    for _ in 0..5 {
+        let start = get_time();
+        let mut now = start;
+        while now == start {
+            now = get_time();
+        }
+        let step_ns = (now - start) as usize * NSEC_PER_MSEC;
+        assert!(step_ns >= res_ns);
+    }
+    println!("clock_getres passed!");
+    0
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, exit, fork, waitpid, write, KernelError};
+
+const STDOUT: usize = 1;
+const STDERR: usize = 2;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(close(STDOUT), 0);
+    assert_eq!(write(STDOUT, b"lost\n"), KernelError::BadFd as isize);
+    // the closed slot is inherited by a forked child
+    let pid = fork();
+    if pid == 0 {
+        exit(if write(STDOUT, b"lost\n") == KernelError::BadFd as isize {
+            0
+        } else {
+            1
+        });
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // stderr is still open, restore stdout from it
+    assert_eq!(dup(STDERR), STDOUT as isize);
+    println!("close_stdout passed!");
+    0
+}
@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, mmap, munmap, sleep_until, task_info, MmapProt, TaskInfo, TimeVal};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap(0, 2 * PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    let boundary = start as usize + PAGE_SIZE;
+    // a TaskInfo whose first two fields sit on the first page
+    let info = unsafe { &mut *((boundary - 16) as *mut TaskInfo) };
+    *info = TaskInfo::default();
+    assert_eq!(task_info(info), 0);
+    println!("task info across pages: {:?}", info);
+    assert!(info.switches > 0);
+    assert!(info.user_time_us + info.kernel_time_us > 0);
+    // a TimeVal with `sec` and `usec` on different pages
+    let tv = unsafe { &mut *((boundary - 8) as *mut TimeVal) };
+    *tv = TimeVal::from_ms(get_time() as usize + 10);
+    assert_eq!(sleep_until(tv), 0);
+    assert!(get_time() as usize >= tv.sec * 1000 + tv.usec / 1000);
+    // with the second page gone both copies are rejected
+    assert_eq!(munmap(boundary, PAGE_SIZE), 0);
+    assert_eq!(task_info(info), -1);
+    assert_eq!(sleep_until(tv), -1);
+    println!("copy_user passed!");
+    0
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, task_info, waitpid, yield_, TaskInfo};
+
+const RUN_MS: isize = 100;
+
+/// Exit with the CPU time (ms) charged to this task.
+fn exit_with_cpu_time(name: &str) -> ! {
+    let mut info = TaskInfo::default();
+    task_info(&mut info);
+    println!(
+        "{}: user {}us, kernel {}us",
+        name, info.user_time_us, info.kernel_time_us
+    );
+    exit(((info.user_time_us + info.kernel_time_us) / 1000) as i32);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    let yielder = fork();
+    if yielder == 0 {
+        while get_time() - start < RUN_MS {
+            yield_();
+        }
+        exit_with_cpu_time("yielder");
+    }
+    let spinner = fork();
+    if spinner == 0 {
+        while get_time() - start < RUN_MS {}
+        exit_with_cpu_time("spinner");
+    }
+    let mut yielder_ms: i32 = 0;
+    let mut spinner_ms: i32 = 0;
+    assert_eq!(waitpid(yielder as usize, &mut yielder_ms), yielder);
+    assert_eq!(waitpid(spinner as usize, &mut spinner_ms), spinner);
+    let wall_ms = get_time() - start;
+    println!(
+        "yielder {}ms + spinner {}ms within {}ms",
+        yielder_ms, spinner_ms, wall_ms
+    );
+    // one CPU: if a task were charged for its successor's run, the sum
+    // of both would exceed the wall time
+    assert!((yielder_ms + spinner_ms) as isize <= wall_ms);
+    assert!(spinner_ms > 0);
+    println!("cpu_time passed!");
+    0
+}
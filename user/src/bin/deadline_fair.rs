@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use user_lib::{exit, get_time, thread_create, waittid, yield_deadline};
+
+/// several time slices, so the spinner is preempted while holding a deadline
+const SPIN_MS: isize = 100;
+
+static PROGRESS: AtomicUsize = AtomicUsize::new(0);
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// Never asks for a deadline.
+fn counter() -> ! {
+    while !STOP.load(Ordering::SeqCst) {
+        PROGRESS.fetch_add(1, Ordering::SeqCst);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tid = thread_create(counter as usize, 0);
+    // a tight deadline once, then keep the CPU: it only covers one wait
+    yield_deadline(1);
+    let before = PROGRESS.load(Ordering::SeqCst);
+    let start = get_time();
+    while get_time() - start < SPIN_MS {}
+    let after = PROGRESS.load(Ordering::SeqCst);
+    STOP.store(true, Ordering::SeqCst);
+    assert_eq!(waittid(tid as usize), 0);
+    println!("counter advanced {} while main spun", after - before);
+    assert!(after > before);
+    println!("deadline_fair passed!");
+    0
+}
@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, get_time, getrusage, thread_create, waittid, yield_deadline, RUsage};
+
+const ROUNDS: usize = 5;
+/// longer than a time slice, so a hog is preempted and, holding the
+/// earliest deadline, picked again
+const SPIN_MS: isize = 20;
+
+static VICTIM_MISSES: AtomicUsize = AtomicUsize::new(0);
+
+fn misses() -> usize {
+    let mut usage = RUsage::default();
+    assert_eq!(getrusage(&mut usage), 0);
+    usage.deadline_misses
+}
+
+/// Asks for a tight deadline, then keeps the CPU.
+fn hog() -> ! {
+    for _ in 0..ROUNDS {
+        yield_deadline(1);
+        let start = get_time();
+        while get_time() - start < SPIN_MS {}
+    }
+    exit(0)
+}
+
+/// Asks for a looser deadline, which the hogs keep it from meeting.
+fn victim() -> ! {
+    assert_eq!(misses(), 0);
+    for _ in 0..ROUNDS {
+        yield_deadline(5);
+    }
+    VICTIM_MISSES.store(misses(), Ordering::SeqCst);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tids = [
+        thread_create(hog as usize, 0),
+        thread_create(hog as usize, 0),
+        thread_create(victim as usize, 0),
+    ];
+    for tid in tids {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    let victim_misses = VICTIM_MISSES.load(Ordering::SeqCst);
+    println!("victim missed {} deadlines", victim_misses);
+    assert!(victim_misses > 0);
+    // main never asked for a deadline
+    assert_eq!(misses(), 0);
+    println!("deadline_miss passed!");
+    0
+}
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use core::str::from_utf8;
+use user_lib::{dmesg, exit, fork, set_log_level, waitpid};
+
+const LEVEL_INFO: usize = 3;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // process exits are logged at info level
+    let prev = set_log_level(LEVEL_INFO);
+    assert!(prev >= 0);
+    let pid = fork();
+    if pid == 0 {
+        exit(42);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    set_log_level(prev as usize);
+    let mut buf = [0u8; 1024];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    println!("{}", text);
+    let expected = format!("pid {} exited with code 42", pid);
+    assert!(text.lines().any(|line| line.contains(&expected)));
+    // a short read still starts at a line boundary
+    let mut short = [0u8; 64];
+    let len = dmesg(&mut short) as usize;
+    assert!(len <= short.len());
+    assert!(len == 0 || short[..len].starts_with(b"["));
+    // a read exactly as long as the last line returns that whole line
+    let last_len = match text.trim_end_matches('\n').rfind('\n') {
+        Some(pos) => text.len() - pos - 1,
+        None => text.len(),
+    };
+    let mut exact = [0u8; 1024];
+    let len = dmesg(&mut exact[..last_len]) as usize;
+    assert_eq!(&exact[..len], &text.as_bytes()[text.len() - last_len..]);
+    println!("dmesg passed!");
+    0
+}
@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec;
+use core::str::from_utf8;
+use user_lib::{dump_pagetable, mmap, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap(0, PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    // mmap pages get a frame on first touch
+    unsafe {
+        (start as *mut u8).write_volatile(1);
+    }
+    let mut buf = vec![0u8; 16 * 1024];
+    let len = dump_pagetable(&mut buf) as usize;
+    assert!(len > 0 && len < buf.len());
+    let text = from_utf8(&buf[..len]).unwrap();
+    let prefix = format!("{:#x} -> ", start as usize / PAGE_SIZE);
+    let line = text
+        .lines()
+        .find(|line| line.starts_with(&prefix))
+        .expect("mmap page missing from the dump");
+    println!("{}", line);
+    // A and D are up to the hardware
+    let flags = line.rsplit(' ').next().unwrap();
+    assert!(flags.starts_with("VRW-U"));
+    // a short buffer gets a truncated listing
+    let mut short = [0u8; 16];
+    assert_eq!(dump_pagetable(&mut short), short.len() as isize);
+    assert_eq!(&short[..], &buf[..short.len()]);
+    println!("dump_pagetable passed!");
+    0
+}
@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, write, KernelError};
+
+const STDOUT: usize = 1;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let fd = dup(STDOUT);
+    assert!(fd > 2);
+    let fd = fd as usize;
+    let msg = b"written through stdout\n";
+    assert_eq!(write(STDOUT, msg), msg.len() as isize);
+    let msg = b"written through the dup\n";
+    assert_eq!(write(fd, msg), msg.len() as isize);
+    // closing the copy leaves stdout open
+    assert_eq!(close(fd), 0);
+    assert_eq!(write(fd, msg), KernelError::BadFd as isize);
+    assert_eq!(dup(fd), -1);
+    assert_eq!(dup(1024), -1);
+    println!("dup_stdout passed!");
+    0
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use user_lib::{fork, getenv, setenv, waitpid, yield_};
+
+const ENV_MAX_VARS: usize = 16;
+
+fn get<'a>(key: &str, buf: &'a mut [u8]) -> Option<&'a [u8]> {
+    let len = getenv(key, buf);
+    if len < 0 {
+        None
+    } else {
+        Some(&buf[..len as usize])
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [0u8; 64];
+    assert_eq!(get("GREETING\0", &mut buf), None);
+    assert_eq!(setenv("GREETING\0", "hello\0"), 0);
+    yield_();
+    assert_eq!(get("GREETING\0", &mut buf), Some(&b"hello"[..]));
+    assert_eq!(buf[5], 0);
+    // no room for the terminating NUL
+    assert_eq!(getenv("GREETING\0", &mut buf[..5]), -1);
+    assert_eq!(setenv("GREETING\0", "bye\0"), 0);
+    assert_eq!(get("GREETING\0", &mut buf), Some(&b"bye"[..]));
+    assert_eq!(setenv("\0", "empty key\0"), -1);
+    assert_eq!(setenv("A=B\0", "bad key\0"), -1);
+    let pid = fork();
+    if pid == 0 {
+        // the child starts with a copy
+        assert_eq!(get("GREETING\0", &mut buf), Some(&b"bye"[..]));
+        assert_eq!(setenv("GREETING\0", "child\0"), 0);
+        return 0;
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(get("GREETING\0", &mut buf), Some(&b"bye"[..]));
+    // fill the remaining slots
+    for i in 1..ENV_MAX_VARS {
+        assert_eq!(setenv(&format!("VAR{}\0", i), "x\0"), 0);
+    }
+    assert_eq!(setenv("ONE_TOO_MANY\0", "x\0"), -1);
+    assert_eq!(setenv("VAR1\0", "replaced\0"), 0);
+    assert_eq!(get("VAR1\0", &mut buf), Some(&b"replaced"[..]));
+    println!("env passed!");
+    0
+}
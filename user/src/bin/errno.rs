@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, mmap, munmap, read, write, KernelError, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let rw = MmapProt::READ | MmapProt::WRITE;
+    let start = mmap(0, PAGE_SIZE, rw);
+    assert!(start > 0);
+    let start = start as usize;
+    assert_eq!(munmap(start, PAGE_SIZE), 0);
+    // the range is gone now
+    assert_eq!(
+        KernelError::from_code(munmap(start, PAGE_SIZE)),
+        Some(KernelError::NoMem)
+    );
+    assert_eq!(
+        KernelError::from_code(munmap(start + 1, PAGE_SIZE)),
+        Some(KernelError::Invalid)
+    );
+    assert_eq!(
+        KernelError::from_code(munmap(start, 0)),
+        Some(KernelError::Invalid)
+    );
+    assert_eq!(
+        KernelError::from_code(mmap(0, PAGE_SIZE, MmapProt::empty())),
+        Some(KernelError::Invalid)
+    );
+    assert_eq!(
+        KernelError::from_code(write(1024, b"x")),
+        Some(KernelError::BadFd)
+    );
+    let mut buf = [0u8; 1];
+    assert_eq!(
+        KernelError::from_code(read(1024, &mut buf)),
+        Some(KernelError::BadFd)
+    );
+    assert_eq!(
+        KernelError::from_code(close(1024)),
+        Some(KernelError::BadFd)
+    );
+    // success and plain -1 are not errors with a cause
+    assert_eq!(KernelError::from_code(0), None);
+    assert_eq!(KernelError::from_code(-1), None);
+    println!("errno passed!");
+    0
+}
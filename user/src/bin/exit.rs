@@ -22,7 +22,7 @@ pub fn main() -> i32 {
     }
     println!("I am the parent, waiting now..");
     let mut xstate: i32 = 0;
-    assert!(waitpid(pid as usize, &mut xstate) == pid && xstate == MAGIC);
+    assert!(waitpid(pid as usize, &mut xstate) == pid && xstate == MAGIC & 0xff);
     assert!(waitpid(pid as usize, &mut xstate) < 0 && wait(&mut xstate) <= 0);
     println!("waitpid {} ok.", pid);
     println!("exit pass.");
@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, meminfo, mmap, sleep, waitpid, MemInfo, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+const PAGES: usize = 10;
+
+fn frames_used() -> usize {
+    let mut info = MemInfo::default();
+    assert_eq!(meminfo(&mut info), 0);
+    info.frames_used
+}
+
+/// Fork a child that maps and touches `PAGES` pages if `baseline` is set,
+/// then exits, and reap it.
+fn run_child(baseline: Option<usize>) {
+    let pid = fork();
+    if pid == 0 {
+        if baseline.is_some() {
+            let start = mmap(
+                0,
+                PAGES * PAGE_SIZE,
+                MmapProt::READ | MmapProt::WRITE | MmapProt::POPULATE,
+            );
+            assert!(start > 0);
+            let pages =
+                unsafe { core::slice::from_raw_parts_mut(start as *mut u8, PAGES * PAGE_SIZE) };
+            pages.fill(0xa5);
+        }
+        exit(0);
+    }
+    // let the child exit, it stays a zombie until waited for
+    sleep(20);
+    if let Some(baseline) = baseline {
+        let zombie = frames_used() - baseline;
+        println!("frames still used by the zombie: {}", zombie);
+        // only its kernel stack and page table root are left
+        assert!(zombie < PAGES);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // the first fork may add kernel page tables for its kernel stack
+    run_child(None);
+    let baseline = frames_used();
+    for _ in 0..3 {
+        run_child(Some(baseline));
+        assert_eq!(frames_used(), baseline);
+    }
+    println!("exit_frames passed!");
+    0
+}
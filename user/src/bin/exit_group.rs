@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit_group, fork, kill, pipe, read, sleep, waitpid, write, yield_};
+
+const GROUP_EXIT_CODE: i32 = 7;
+const CHILDREN: usize = 2;
+
+/// Whether `pid` still exists; signal 0 only checks for the process.
+fn alive(pid: usize) -> bool {
+    kill(pid, 0) == 0
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let leader = fork();
+    if leader == 0 {
+        close(pipe_fd[0]);
+        for _ in 0..CHILDREN {
+            let pid = fork();
+            if pid == 0 {
+                loop {
+                    yield_();
+                }
+            }
+            write(pipe_fd[1], &(pid as usize).to_ne_bytes());
+        }
+        close(pipe_fd[1]);
+        exit_group(GROUP_EXIT_CODE);
+    }
+    close(pipe_fd[1]);
+    let mut children = [0usize; CHILDREN];
+    for child in children.iter_mut() {
+        let mut bytes = [0u8; core::mem::size_of::<usize>()];
+        assert_eq!(read(pipe_fd[0], &mut bytes), bytes.len() as isize);
+        *child = usize::from_ne_bytes(bytes);
+    }
+    close(pipe_fd[0]);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(leader as usize, &mut exit_code), leader);
+    assert_eq!(exit_code, GROUP_EXIT_CODE);
+    // the children leave at their next trap and are reaped by initproc
+    sleep(10);
+    for &pid in children.iter() {
+        println!("child {} alive: {}", pid, alive(pid));
+        assert!(!alive(pid));
+    }
+    assert!(!alive(leader as usize));
+    println!("exit_group passed!");
+    0
+}
@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, exit_reason, fork, sleep, thread_create, waitpid, waittid, yield_};
+
+const SIGSEGV: isize = 11;
+
+/// Wait until `pid` has exited and return its reason, then reap it.
+fn reason_of(pid: isize) -> isize {
+    let reason = loop {
+        match exit_reason(pid as usize) {
+            -2 => {
+                yield_();
+            }
+            reason => break reason,
+        }
+    };
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    reason
+}
+
+fn fault() -> ! {
+    unsafe {
+        core::ptr::null_mut::<u8>().write_volatile(0);
+    }
+    exit(0)
+}
+
+fn quit() -> ! {
+    exit(7)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let clean = fork();
+    if clean == 0 {
+        exit(3);
+    }
+    assert_eq!(reason_of(clean), 0);
+    let faulty = fork();
+    if faulty == 0 {
+        unsafe {
+            core::ptr::null_mut::<u8>().write_volatile(0);
+        }
+        exit(0);
+    }
+    assert_eq!(reason_of(faulty), SIGSEGV);
+    assert_eq!(exit_reason(faulty as usize), -1);
+
+    // a fault in any thread takes the whole process down as killed
+    let faulty_thread = fork();
+    if faulty_thread == 0 {
+        thread_create(fault as usize, 0);
+        sleep(1000);
+        exit(0);
+    }
+    assert_eq!(reason_of(faulty_thread), SIGSEGV);
+    // while a thread that just exits leaves a clean exit
+    let quitting_thread = fork();
+    if quitting_thread == 0 {
+        let tid = thread_create(quit as usize, 0);
+        assert_eq!(waittid(tid as usize), 7);
+        exit(0);
+    }
+    assert_eq!(reason_of(quitting_thread), 0);
+    println!("exit_reason passed!");
+    0
+}
@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+use user_lib::{exit, fork, thread_create, waitpid, waittid};
+
+fn worker() -> ! {
+    exit(0x1ff)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exit(0x1ff);
+    }
+    let mut xstate: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut xstate), pid);
+    // only the status reported by waitpid is cut down to 8 bits
+    assert_eq!(xstate, 0xff);
+
+    let pid = fork();
+    if pid == 0 {
+        exit(-1);
+    }
+    assert_eq!(waitpid(pid as usize, &mut xstate), pid);
+    assert_eq!(xstate, 0xff);
+
+    let tid = thread_create(worker as usize, 0);
+    assert!(tid > 0);
+    assert_eq!(waittid(tid as usize), 0x1ff);
+    println!("exit_status passed!");
+    0
+}
@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time, getrusage, set_scheduler, waitpid, yield_, RUsage, SCHED_FAIR, SCHED_RR,
+};
+
+const RUN_MS: isize = 1000;
+
+/// CPU time of the calling thread in units of 10 ms, small enough for an
+/// exit code.
+fn cpu_time_10ms() -> i32 {
+    let mut usage = RUsage::default();
+    assert_eq!(getrusage(&mut usage), 0);
+    ((usage.user_time_us + usage.kernel_time_us) / 10_000) as i32
+}
+
+/// Spin for whole time slices.
+fn hog() -> ! {
+    let start = get_time();
+    while get_time() - start < RUN_MS {}
+    exit(cpu_time_10ms())
+}
+
+/// Do about a millisecond of work, then yield.
+fn light() -> ! {
+    let start = get_time();
+    while get_time() - start < RUN_MS {
+        let now = get_time();
+        while get_time() == now {}
+        yield_();
+    }
+    exit(cpu_time_10ms())
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_scheduler(SCHED_FAIR), SCHED_RR as isize);
+    let hog_pid = fork();
+    if hog_pid == 0 {
+        hog();
+    }
+    let light_pid = fork();
+    if light_pid == 0 {
+        light();
+    }
+    let (mut hog_cpu, mut light_cpu) = (0, 0);
+    assert_eq!(waitpid(hog_pid as usize, &mut hog_cpu), hog_pid);
+    assert_eq!(waitpid(light_pid as usize, &mut light_cpu), light_pid);
+    assert_eq!(set_scheduler(SCHED_RR), SCHED_FAIR as isize);
+    println!(
+        "cpu time: hog {} ms, light {} ms",
+        hog_cpu * 10,
+        light_cpu * 10
+    );
+    // round robin would give the light task one short turn per hog slice,
+    // about a tenth of the CPU; a fair share is half, but leave room for noise
+    assert!(light_cpu * 4 >= hog_cpu + light_cpu);
+    assert!(hog_cpu * 4 >= hog_cpu + light_cpu);
+    println!("fair_share passed!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    fault_inject, get_time, getrusage, mmap, munmap, KernelError, MmapProt, RUsage,
+    FAULT_OUT_OF_FRAMES, FAULT_STALE_METRIC, FAULT_USER_READ,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // the kernel refuses the bad pointer instead of faulting
+    assert_eq!(fault_inject(FAULT_USER_READ), 0);
+
+    // a populated mmap fails once, then works again
+    let prot = MmapProt::READ | MmapProt::WRITE | MmapProt::POPULATE;
+    assert_eq!(fault_inject(FAULT_OUT_OF_FRAMES), 0);
+    assert_eq!(mmap(0, PAGE_SIZE, prot), KernelError::NoMem as isize);
+    let start = mmap(0, PAGE_SIZE, prot);
+    assert!(start > 0);
+    assert_eq!(munmap(start as usize, PAGE_SIZE), 0);
+
+    // a mark in the future adds no time instead of wrapping around
+    let begin = get_time() as usize;
+    let mut before = RUsage::default();
+    getrusage(&mut before);
+    assert_eq!(fault_inject(FAULT_STALE_METRIC), 0);
+    let mut after = RUsage::default();
+    getrusage(&mut after);
+    let elapsed_us = (get_time() as usize - begin + 1) * 1000;
+    assert!(
+        after.user_time_us + after.kernel_time_us
+            <= before.user_time_us + before.kernel_time_us + elapsed_us
+    );
+
+    assert_eq!(fault_inject(3), -1);
+    println!("fault_inject passed!");
+    0
+}
@@ -0,0 +1,18 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fence, FENCE_I, FENCE_RW, FENCE_VMA};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for kind in [FENCE_RW, FENCE_I, FENCE_VMA] {
+        assert_eq!(fence(kind), 0);
+    }
+    assert_eq!(fence(3), -1);
+    assert_eq!(fence(usize::MAX), -1);
+    println!("fence passed!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::AtomicU32;
+use user_lib::{fork, futex_wait, futex_wake, mmap, sleep, thread_create, waitpid, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+/// Pages mapped once the child is gone, to get its freed frames back.
+const PAGES: usize = 64;
+
+fn waiter(word: usize) -> ! {
+    let word = unsafe { &*(word as *const AtomicU32) };
+    loop {
+        futex_wait(word, 0);
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let rw = MmapProt::READ | MmapProt::WRITE;
+    let pid = fork();
+    if pid == 0 {
+        let page = mmap(0, PAGE_SIZE, rw | MmapProt::POPULATE);
+        assert!(page > 0);
+        assert!(thread_create(waiter as usize, page as usize) > 0);
+        // the process goes away while its thread is still queued
+        sleep(10);
+        return 0;
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // a wake on a reused frame must not find the dead thread
+    let base = mmap(0, PAGES * PAGE_SIZE, rw | MmapProt::POPULATE);
+    assert!(base > 0);
+    for page in 0..PAGES {
+        let word = unsafe { &*((base as usize + page * PAGE_SIZE) as *const AtomicU32) };
+        assert_eq!(futex_wake(word, 1), 0);
+    }
+    println!("futex_exit passed!");
+    0
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use user_lib::{exit, futex_wait, futex_wake, thread_create, waittid, KernelError};
+
+const ROUNDS: u32 = 100;
+
+/// Number of handoffs so far: main moves on even values, the worker on odd.
+static TURN: AtomicU32 = AtomicU32::new(0);
+
+/// Sleep until it is `turn`, then pass the next one to the other thread.
+fn play(turn: u32) {
+    loop {
+        let seen = TURN.load(Ordering::Acquire);
+        if seen == turn {
+            break;
+        }
+        let ret = futex_wait(&TURN, seen);
+        assert!(ret == 0 || KernelError::from_code(ret) == Some(KernelError::Again));
+    }
+    TURN.store(turn + 1, Ordering::Release);
+    futex_wake(&TURN, 1);
+}
+
+fn worker() -> ! {
+    for round in 0..ROUNDS {
+        play(round * 2 + 1);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(
+        KernelError::from_code(futex_wait(&TURN, 1)),
+        Some(KernelError::Again)
+    );
+    assert_eq!(futex_wake(&TURN, 1), 0);
+    let tid = thread_create(worker as usize, 0);
+    assert!(tid > 0);
+    for round in 0..ROUNDS {
+        play(round * 2);
+    }
+    assert_eq!(waittid(tid as usize), 0);
+    assert_eq!(TURN.load(Ordering::Acquire), ROUNDS * 2);
+    println!("futex_handoff passed!");
+    0
+}
@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getcpu, sleep, thread_create, waitpid, waittid, yield_};
+
+fn thread_cpu() -> ! {
+    exit(getcpu() as i32)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let cpu = getcpu();
+    assert!(cpu >= 0);
+    // stays put across reschedules
+    for _ in 0..10 {
+        yield_();
+        assert_eq!(getcpu(), cpu);
+    }
+    sleep(20);
+    assert_eq!(getcpu(), cpu);
+    // a single hart runs everything for now
+    let tid = thread_create(thread_cpu as usize, 0);
+    assert_eq!(waittid(tid as usize), cpu);
+    let pid = fork();
+    if pid == 0 {
+        exit(getcpu() as i32);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, cpu as i32);
+    println!("running on hart {}", cpu);
+    println!("getcpu passed!");
+    0
+}
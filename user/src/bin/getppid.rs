@@ -0,0 +1,22 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, getppid, waitpid};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let parent = getpid();
+    let pid = fork();
+    if pid == 0 {
+        println!("child {}: parent is {}", getpid(), getppid());
+        exit(if getppid() == parent { 0 } else { 1 });
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("getppid passed!");
+    0
+}
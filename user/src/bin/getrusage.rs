@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, getrusage, mmap, munmap, yield_, MmapProt, RUsage};
+
+const PAGE_SIZE: usize = 0x1000;
+const PAGES: usize = 8;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = RUsage::default();
+    assert_eq!(getrusage(&mut before), 0);
+    // voluntary switches
+    for _ in 0..3 {
+        yield_();
+    }
+    // page faults and resident set, from touching lazy pages
+    let start = mmap(0, PAGES * PAGE_SIZE, MmapProt::READ | MmapProt::WRITE) as usize;
+    for i in 0..PAGES {
+        unsafe { ((start + i * PAGE_SIZE) as *mut u8).write_volatile(i as u8) };
+    }
+    // user time and, with a 10ms timeslice, involuntary switches
+    let begin = get_time();
+    while get_time() - begin < 50 {}
+    let mut after = RUsage::default();
+    assert_eq!(getrusage(&mut after), 0);
+    println!("{:?}", after);
+    assert!(after.voluntary_switches >= before.voluntary_switches + 3);
+    assert!(after.involuntary_switches > before.involuntary_switches);
+    assert!(after.page_faults >= before.page_faults + PAGES);
+    assert!(after.max_rss_pages >= before.max_rss_pages + PAGES);
+    assert!(after.user_time_us > before.user_time_us);
+    assert!(after.kernel_time_us > before.kernel_time_us);
+    // the peak survives unmapping
+    assert_eq!(munmap(start, PAGES * PAGE_SIZE), 0);
+    let mut last = RUsage::default();
+    getrusage(&mut last);
+    assert_eq!(last.max_rss_pages, after.max_rss_pages);
+    println!("getrusage passed!");
+    0
+}
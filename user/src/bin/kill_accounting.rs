@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use core::str::from_utf8;
+use user_lib::{dmesg, exit, fork, get_time, set_log_level, waitpid};
+
+const LEVEL_DEBUG: usize = 4;
+
+/// Parse the number ending in "us" right after `key` in `line`.
+fn field(line: &str, key: &str) -> usize {
+    let rest = &line[line.find(key).unwrap() + key.len()..];
+    rest[..rest.find("us").unwrap()].parse().unwrap()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let prev = set_log_level(LEVEL_DEBUG);
+    let start = get_time() as usize;
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            core::ptr::null_mut::<u8>().write_volatile(0);
+        }
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    let elapsed_us = (get_time() as usize - start + 1) * 1000;
+    set_log_level(prev as usize);
+    assert_eq!(exit_code, -11);
+    let mut buf = [0u8; 4096];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    let prefix = format!("pid {} tid 0 cpu time:", pid);
+    let line = text.lines().find(|line| line.contains(&prefix)).unwrap();
+    println!("{}", line);
+    let user = field(line, "user ");
+    let kernel = field(line, "kernel ");
+    assert!(kernel > 0);
+    assert!(user + kernel <= elapsed_us);
+    println!("kill_accounting passed!");
+    0
+}
@@ -0,0 +1,25 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, kill, sleep, waitpid, yield_, SignalFlags};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        loop {
+            yield_();
+        }
+    }
+    sleep(10);
+    assert_eq!(kill(pid as usize, SignalFlags::SIGKILL.bits()), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    println!("child {} exited with code {}", pid, exit_code);
+    assert_eq!(exit_code, -9);
+    println!("killtest passed!");
+    0
+}
@@ -0,0 +1,80 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec::Vec;
+use user_lib::{
+    exit, reset_buffer, set_stdout_mode, stdout_log, thread_create, waittid, yield_,
+    BUFFER_STDOUT_LOG, STDOUT_LINE_BUFFERED, STDOUT_UNBUFFERED,
+};
+
+const LINES: usize = 3;
+
+fn worker(name: *const &str) -> ! {
+    let name = unsafe { *name };
+    assert_eq!(
+        set_stdout_mode(STDOUT_LINE_BUFFERED),
+        STDOUT_UNBUFFERED as isize
+    );
+    for i in 0..LINES {
+        // the other thread runs in between, but the line comes out whole
+        print!("{}: line {}", name, i);
+        yield_();
+        println!(" of {}", LINES);
+    }
+    // no newline, printed when the thread exits
+    print!("{}: exiting; ", name);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_stdout_mode(2), -22);
+    let names = ["alpha", "beta"];
+    assert_eq!(reset_buffer(BUFFER_STDOUT_LOG), 0);
+    let tids: Vec<_> = names
+        .iter()
+        .map(|name| thread_create(worker as usize, name as *const _ as usize))
+        .collect();
+    for tid in tids {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    // every line came out whole, even though the threads took turns
+    // between its two halves
+    let mut log = [0u8; 1024];
+    let len = stdout_log(&mut log) as usize;
+    let log = core::str::from_utf8(&log[..len]).unwrap();
+    for name in names {
+        for i in 0..LINES {
+            let line = format!("{}: line {} of {}", name, i, LINES);
+            assert!(log.lines().any(|l| l == line), "{:?} missing", line);
+        }
+    }
+    for line in log.lines().filter(|l| l.contains("line")) {
+        assert!(
+            names.iter().any(
+                |name| (0..LINES).any(|i| line == format!("{}: line {} of {}", name, i, LINES))
+            ),
+            "interleaved: {:?}",
+            line
+        );
+    }
+    println!("(both flushed at exit)");
+    assert_eq!(
+        set_stdout_mode(STDOUT_LINE_BUFFERED),
+        STDOUT_UNBUFFERED as isize
+    );
+    print!("held back");
+    // leaving line-buffered mode prints the partial line
+    assert_eq!(
+        set_stdout_mode(STDOUT_UNBUFFERED),
+        STDOUT_LINE_BUFFERED as isize
+    );
+    println!(", then completed");
+    println!("line_buffered passed!");
+    0
+}
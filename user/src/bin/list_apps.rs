@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::from_utf8;
+use user_lib::{close, list_apps, open, write, OpenFlags};
+
+const DATA_FILE: &str = "list_apps_data\0";
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // a data file in the same directory is not an app
+    let fd = open(DATA_FILE, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    assert_eq!(write(fd as usize, b"not an elf"), 10);
+    close(fd as usize);
+
+    let mut buf = vec![0u8; 8192];
+    let len = list_apps(&mut buf) as usize;
+    assert!(len > 0 && len < buf.len());
+    let text = from_utf8(&buf[..len]).unwrap();
+    assert!(text.ends_with('\n'));
+    let mut names: Vec<&str> = text.lines().collect();
+    println!("{} apps", names.len());
+    for app in ["initproc", "usertests", "list_apps", "hello_world"] {
+        assert!(names.contains(&app), "{} not listed", app);
+    }
+    assert!(!names.contains(&DATA_FILE.trim_end_matches('\0')));
+    let count = names.len();
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), count);
+    // a short buffer gets the start of the list
+    let mut short = [0u8; 8];
+    assert_eq!(list_apps(&mut short), short.len() as isize);
+    assert_eq!(&short[..], &buf[..short.len()]);
+    println!("list_apps passed!");
+    0
+}
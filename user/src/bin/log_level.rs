@@ -0,0 +1,23 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{set_log_level, yield_};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // quiet the kernel for a tight scheduling loop
+    let prev = set_log_level(0);
+    assert!((0..=5).contains(&prev));
+    for _ in 0..100 {
+        yield_();
+    }
+    assert_eq!(set_log_level(5), 0);
+    assert_eq!(set_log_level(6), -1);
+    // restore whatever the kernel was built with
+    assert_eq!(set_log_level(prev as usize), 5);
+    println!("log_level passed!");
+    0
+}
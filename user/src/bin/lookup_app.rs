@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec;
+use core::str::from_utf8;
+use user_lib::{list_apps, lookup_app};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = vec![0u8; 8192];
+    let len = list_apps(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    // ids are positions in the list
+    for name in ["initproc", "usertests", "lookup_app"] {
+        let id = text.lines().position(|app| app == name).unwrap();
+        let found = lookup_app(&format!("{}\0", name));
+        println!("{} -> {}", name, found);
+        assert_eq!(found, id as isize);
+    }
+    assert_eq!(lookup_app("no_such_app\0"), -1);
+    assert_eq!(lookup_app("\0"), -1);
+    println!("lookup_app passed!");
+    0
+}
@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    madvise, meminfo, mmap, munmap, KernelError, MemInfo, MmapProt, MADV_DONTNEED, MADV_WILLNEED,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+
+fn frames_free() -> usize {
+    let mut info = MemInfo::default();
+    assert_eq!(meminfo(&mut info), 0);
+    info.frames_free
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap(0, 2 * PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    let start = start as usize;
+    let pages = unsafe { core::slice::from_raw_parts_mut(start as *mut u8, 2 * PAGE_SIZE) };
+    pages.fill(0xab);
+
+    assert_eq!(madvise(start, 2 * PAGE_SIZE, MADV_WILLNEED), 0);
+    assert_eq!(
+        KernelError::from_code(madvise(start, PAGE_SIZE, 99)),
+        Some(KernelError::Invalid)
+    );
+
+    let before = frames_free();
+    assert_eq!(madvise(start, PAGE_SIZE, MADV_DONTNEED), 0);
+    assert_eq!(frames_free(), before + 1);
+    // still mapped: the first page reads back as zeroes, the second kept its
+    // contents
+    assert!(pages[..PAGE_SIZE].iter().all(|&b| b == 0));
+    assert!(pages[PAGE_SIZE..].iter().all(|&b| b == 0xab));
+    pages[0] = 1;
+    assert_eq!(pages[0], 1);
+
+    assert_eq!(munmap(start, 2 * PAGE_SIZE), 0);
+    assert_eq!(
+        KernelError::from_code(madvise(start, PAGE_SIZE, MADV_DONTNEED)),
+        Some(KernelError::NoMem)
+    );
+    println!("madvise passed!");
+    0
+}
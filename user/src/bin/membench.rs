@@ -0,0 +1,24 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{membench, KernelError, MEMBENCH_MAX_SIZE};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let invalid = KernelError::Invalid as isize;
+    assert_eq!(membench(0), invalid);
+    assert_eq!(membench(MEMBENCH_MAX_SIZE + 1), invalid);
+
+    let first = membench(MEMBENCH_MAX_SIZE);
+    let second = membench(MEMBENCH_MAX_SIZE);
+    println!("kernel memcpy: {} MB/s, then {} MB/s", first, second);
+    assert!(first > 0 && second > 0);
+    // each is the best of a few copies with interrupts masked, so two calls
+    // agree closely
+    assert!(first <= second * 2 && second <= first * 2);
+    println!("membench passed!");
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{meminfo, mmap, munmap, MemInfo, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+const PAGES: usize = 10;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = MemInfo::default();
+    assert_eq!(meminfo(&mut before), 0);
+    assert_eq!(before.frames_used + before.frames_free, before.frames_total);
+    let start = mmap(
+        0,
+        PAGES * PAGE_SIZE,
+        MmapProt::READ | MmapProt::WRITE | MmapProt::POPULATE,
+    );
+    assert!(start > 0);
+    let mut after = MemInfo::default();
+    meminfo(&mut after);
+    println!(
+        "frames used {} -> {} of {}",
+        before.frames_used, after.frames_used, after.frames_total
+    );
+    // 10 data frames, plus up to 3 new page table frames
+    let delta = after.frames_used - before.frames_used;
+    assert!((PAGES..=PAGES + 3).contains(&delta));
+    assert_eq!(munmap(start as usize, PAGES * PAGE_SIZE), 0);
+    let mut freed = MemInfo::default();
+    meminfo(&mut freed);
+    assert_eq!(after.frames_used - freed.frames_used, PAGES);
+    println!("meminfo passed!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, mprotect, munmap, KernelError, MmapProt, MMAP_BASE, MMAP_TOP};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let prot = MmapProt::READ | MmapProt::WRITE;
+    let len_a = 2 * PAGE_SIZE;
+    let len_b = PAGE_SIZE;
+    let a = mmap(0, len_a, prot);
+    let b = mmap(0, len_b, prot);
+    println!("mmap anywhere: a = {:#x}, b = {:#x}", a, b);
+    assert!(a > 0 && b > 0);
+    let (a, b) = (a as usize, b as usize);
+    assert!((MMAP_BASE..MMAP_TOP).contains(&a) && (MMAP_BASE..MMAP_TOP).contains(&b));
+    assert!(a + len_a <= b || b + len_b <= a);
+    // both regions are usable and independent
+    let pa = unsafe { core::slice::from_raw_parts_mut(a as *mut u8, len_a) };
+    let pb = unsafe { core::slice::from_raw_parts_mut(b as *mut u8, len_b) };
+    pa.fill(0xaa);
+    pb.fill(0x55);
+    assert!(pa.iter().all(|&x| x == 0xaa));
+    assert!(pb.iter().all(|&x| x == 0x55));
+    // a fixed mapping over an existing one is refused
+    assert_eq!(mmap(a, PAGE_SIZE, prot), KernelError::NoMem as isize);
+    assert_eq!(munmap(a, len_a), 0);
+    assert_eq!(munmap(b, len_b), 0);
+    // the freed range can be mapped again at a fixed address
+    assert_eq!(mmap(a, PAGE_SIZE, prot), a as isize);
+    assert_eq!(munmap(a, PAGE_SIZE), 0);
+    // the app image below the window is off limits
+    let code = main as usize & !(PAGE_SIZE - 1);
+    let invalid = KernelError::Invalid as isize;
+    assert_eq!(mmap(code + 0x10_0000, PAGE_SIZE, prot), invalid);
+    assert_eq!(munmap(code, PAGE_SIZE), invalid);
+    assert_eq!(mprotect(code, PAGE_SIZE, prot), invalid);
+    println!("mmap_anywhere passed!");
+    0
+}
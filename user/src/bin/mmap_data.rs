@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap_data, munmap, KernelError, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+const EM_RISCV: u16 = 243;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap_data(0, PAGE_SIZE, MmapProt::READ);
+    assert!(start > 0);
+    let header = unsafe { core::slice::from_raw_parts(start as *const u8, PAGE_SIZE) };
+    // ELF magic, 64-bit, little endian
+    assert_eq!(&header[..6], b"\x7fELF\x02\x01");
+    assert_eq!(u16::from_le_bytes([header[18], header[19]]), EM_RISCV);
+    assert_eq!(munmap(start as usize, PAGE_SIZE), 0);
+
+    assert_eq!(
+        KernelError::from_code(mmap_data(1, PAGE_SIZE, MmapProt::READ)),
+        Some(KernelError::Invalid)
+    );
+    // far past the end of the file
+    assert_eq!(
+        KernelError::from_code(mmap_data(0x1000_0000, PAGE_SIZE, MmapProt::READ)),
+        Some(KernelError::Invalid)
+    );
+    // starts inside the file but runs past its end; must not be allocated
+    assert_eq!(
+        KernelError::from_code(mmap_data(0, 1 << 40, MmapProt::READ)),
+        Some(KernelError::Invalid)
+    );
+    assert_eq!(
+        KernelError::from_code(mmap_data(PAGE_SIZE, usize::MAX, MmapProt::READ)),
+        Some(KernelError::Invalid)
+    );
+    println!("mmap_data passed!");
+    0
+}
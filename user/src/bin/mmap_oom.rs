@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{meminfo, mmap, munmap, KernelError, MemInfo, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+/// Frames left free after draining, enough for the drain's own page tables.
+const SPARE: usize = 256;
+/// In a 1 GiB region of its own, so mapping there needs new page tables.
+const FRESH_START: usize = 0x18_0000_0000;
+
+fn mem_info() -> MemInfo {
+    let mut info = MemInfo::default();
+    meminfo(&mut info);
+    info
+}
+
+fn frames_free() -> usize {
+    mem_info().frames_free
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let rw = MmapProt::READ | MmapProt::WRITE;
+    let drain_len = (frames_free() - SPARE) * PAGE_SIZE;
+    let drain = mmap(0, drain_len, rw | MmapProt::POPULATE);
+    assert!(drain > 0);
+
+    // as many pages as user memory may take, with no frames for the page
+    // tables on top
+    let free = frames_free();
+    let len = (free - mem_info().frames_reserved) * PAGE_SIZE;
+    assert_eq!(
+        mmap(FRESH_START, len, rw | MmapProt::POPULATE),
+        KernelError::NoMem as isize
+    );
+    let after = frames_free();
+    println!(
+        "{} frames free before the failed mmap, {} after",
+        free, after
+    );
+    assert_eq!(after, free);
+    // the range was left unmapped, so it can be mapped again
+    assert_eq!(mmap(FRESH_START, len, rw), FRESH_START as isize);
+    assert_eq!(munmap(FRESH_START, len), 0);
+
+    assert_eq!(munmap(drain as usize, drain_len), 0);
+    println!("mmap_oom passed!");
+    0
+}
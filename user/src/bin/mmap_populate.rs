@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    close, exit, fork, meminfo, mmap, munmap, pipe, waitpid, KernelError, MemInfo, MmapProt,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+const PAGES: usize = 16;
+
+fn frames_used() -> usize {
+    let mut info = MemInfo::default();
+    meminfo(&mut info);
+    info.frames_used
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let rw = MmapProt::READ | MmapProt::WRITE;
+    // lazy: nothing is allocated until a page is touched
+    let before = frames_used();
+    let lazy = mmap(0, PAGES * PAGE_SIZE, rw);
+    assert!(lazy > 0);
+    assert_eq!(frames_used(), before);
+    unsafe {
+        (lazy as *mut u8).write_volatile(1);
+        assert_eq!((lazy as *const u8).read_volatile(), 1);
+    }
+    // one data frame, plus up to 3 new page table frames
+    let touched = frames_used() - before;
+    println!("lazy: {} frames after touching one page", touched);
+    assert!((1..=4).contains(&touched));
+    assert_eq!(munmap(lazy as usize, PAGES * PAGE_SIZE), 0);
+    // the kernel faults in untouched pages it writes results to
+    let lazy = mmap(0, 2 * PAGE_SIZE, rw);
+    assert!(lazy > 0);
+    let fds = unsafe { core::slice::from_raw_parts_mut(lazy as *mut usize, 2) };
+    assert_eq!(pipe(fds), 0);
+    assert_eq!(close(fds[0]), 0);
+    assert_eq!(close(fds[1]), 0);
+    let pid = fork();
+    if pid == 0 {
+        exit(7);
+    }
+    let exit_code = unsafe { &mut *((lazy as usize + PAGE_SIZE) as *mut i32) };
+    assert_eq!(waitpid(pid as usize, exit_code), pid);
+    assert_eq!(*exit_code, 7);
+    // and refuses them once they are unmapped
+    assert_eq!(munmap(lazy as usize, 2 * PAGE_SIZE), 0);
+    assert_eq!(pipe(fds), KernelError::Fault as isize);
+    // populated: every page is backed right away
+    let before = frames_used();
+    let eager = mmap(0, PAGES * PAGE_SIZE, rw | MmapProt::POPULATE);
+    assert!(eager > 0);
+    let populated = frames_used() - before;
+    println!("populate: {} frames right after mmap", populated);
+    assert!((PAGES..=PAGES + 3).contains(&populated));
+    assert_eq!(munmap(eager as usize, PAGES * PAGE_SIZE), 0);
+    // more than is free cannot be populated
+    let mut info = MemInfo::default();
+    meminfo(&mut info);
+    let too_big = (info.frames_free + 1) * PAGE_SIZE;
+    assert_eq!(
+        mmap(0, too_big, rw | MmapProt::POPULATE),
+        KernelError::NoMem as isize
+    );
+    println!("mmap_populate passed!");
+    0
+}
@@ -0,0 +1,31 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, mmap, waitpid, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap(0, PAGE_SIZE, MmapProt::READ);
+    assert!(start > 0);
+    let addr = start as *mut u8;
+    // reading is fine
+    assert_eq!(unsafe { addr.read_volatile() }, 0);
+    let pid = fork();
+    if pid == 0 {
+        // the kernel should report a write to a read-only mapping
+        unsafe {
+            addr.write_volatile(1);
+        }
+        panic!("write to a read-only mapping should not succeed");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -11);
+    println!("mmap_readonly passed!");
+    0
+}
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fence, fork, mmap, mprotect, waitpid, KernelError, MmapProt, FENCE_I};
+
+const PAGE_SIZE: usize = 0x1000;
+
+/// `li a0, 42; ret`
+const CODE: [u32; 2] = [0x02a0_0513, 0x0000_8067];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap(0, 2 * PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    let start = start as usize;
+    let code = start as *mut u32;
+    for (i, &inst) in CODE.iter().enumerate() {
+        unsafe {
+            code.add(i).write_volatile(inst);
+        }
+    }
+    // only the first page becomes code, the second stays writable
+    assert_eq!(
+        mprotect(start, PAGE_SIZE, MmapProt::READ | MmapProt::EXEC),
+        0
+    );
+    fence(FENCE_I);
+    let f: fn() -> usize = unsafe { core::mem::transmute(start) };
+    assert_eq!(f(), 42);
+    unsafe {
+        ((start + PAGE_SIZE) as *mut u8).write_volatile(1);
+    }
+
+    let pid = fork();
+    if pid == 0 {
+        // the code page is no longer writable
+        unsafe {
+            code.write_volatile(0);
+        }
+        panic!("write to a read-only page should not succeed");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -11);
+
+    // partly unmapped range, write without read, unknown bits
+    assert_eq!(
+        mprotect(start, 3 * PAGE_SIZE, MmapProt::READ),
+        KernelError::NoMem as isize
+    );
+    assert_eq!(
+        mprotect(start, PAGE_SIZE, MmapProt::WRITE),
+        KernelError::Invalid as isize
+    );
+    assert_eq!(
+        mprotect(start, PAGE_SIZE, MmapProt::POPULATE),
+        KernelError::Invalid as isize
+    );
+    println!("mprotect passed!");
+    0
+}
@@ -0,0 +1,81 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::exit;
+use user_lib::{condvar_create, condvar_signal, condvar_wait};
+use user_lib::{mutex_blocking_create, mutex_lock, mutex_unlock};
+use user_lib::{thread_create, waittid};
+
+const MUTEX: usize = 0;
+const NOT_FULL: usize = 0;
+const NOT_EMPTY: usize = 1;
+const BUFFER_SIZE: usize = 8;
+static mut BUFFER: [usize; BUFFER_SIZE] = [0; BUFFER_SIZE];
+static mut FRONT: usize = 0;
+static mut TAIL: usize = 0;
+static mut COUNT: usize = 0;
+const PRODUCER_COUNT: usize = 4;
+const NUMBER_PER_PRODUCER: usize = 100;
+const TOTAL: usize = PRODUCER_COUNT * NUMBER_PER_PRODUCER;
+/// how often the consumer has taken each item
+static mut TAKEN: [usize; TOTAL] = [0; TOTAL];
+
+unsafe fn producer(id: *const usize) -> ! {
+    let id = *id;
+    for i in 0..NUMBER_PER_PRODUCER {
+        mutex_lock(MUTEX);
+        while COUNT == BUFFER_SIZE {
+            condvar_wait(NOT_FULL, MUTEX);
+        }
+        BUFFER[TAIL] = id * NUMBER_PER_PRODUCER + i;
+        TAIL = (TAIL + 1) % BUFFER_SIZE;
+        COUNT += 1;
+        condvar_signal(NOT_EMPTY);
+        mutex_unlock(MUTEX);
+    }
+    exit(0)
+}
+
+unsafe fn consumer() -> ! {
+    for _ in 0..TOTAL {
+        mutex_lock(MUTEX);
+        while COUNT == 0 {
+            condvar_wait(NOT_EMPTY, MUTEX);
+        }
+        TAKEN[BUFFER[FRONT]] += 1;
+        FRONT = (FRONT + 1) % BUFFER_SIZE;
+        COUNT -= 1;
+        condvar_signal(NOT_FULL);
+        mutex_unlock(MUTEX);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(mutex_blocking_create() as usize, MUTEX);
+    assert_eq!(condvar_create() as usize, NOT_FULL);
+    assert_eq!(condvar_create() as usize, NOT_EMPTY);
+    let ids: Vec<_> = (0..PRODUCER_COUNT).collect();
+    let mut threads = Vec::new();
+    for id in ids.iter() {
+        threads.push(thread_create(producer as usize, id as *const _ as usize));
+    }
+    threads.push(thread_create(consumer as usize, 0));
+    for thread in threads.iter() {
+        waittid(*thread as usize);
+    }
+    // every item was taken exactly once: none lost, none duplicated
+    unsafe {
+        assert_eq!(COUNT, 0);
+        assert!(TAKEN.iter().all(|&taken| taken == 1));
+    }
+    println!("mpsc_condvar passed!");
+    0
+}
@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::str::from_utf8;
+use user_lib::{dmesg, fork, reset_buffer, set_log_level, waitpid, BUFFER_DMESG};
+
+const LEVEL_WARN: usize = 2;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(reset_buffer(BUFFER_DMESG), 0);
+    // the diagnosis is logged at warn level
+    let prev = set_log_level(LEVEL_WARN);
+    assert!(prev >= 0);
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            (4 as *mut u8).write_volatile(1);
+        }
+        panic!("write to page 0 should not succeed");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    set_log_level(prev as usize);
+    assert_eq!(exit_code, -11);
+    let mut buf = [0u8; 1024];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    assert!(text
+        .lines()
+        .any(|line| line.contains("null pointer dereference, bad addr = 0x4")));
+    println!("null_deref passed!");
+    0
+}
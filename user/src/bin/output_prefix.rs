@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{exit, set_output_prefix, thread_create, waittid, yield_};
+
+const LINES: usize = 3;
+
+fn worker(name: *const &str) -> ! {
+    let name = unsafe { *name };
+    assert_eq!(set_output_prefix(name), 0);
+    for i in 0..LINES {
+        // a line written in two pieces gets a single prefix
+        print!("line {}", i);
+        yield_();
+        println!(" of {}", LINES);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_output_prefix("way too long for a prefix"), -1);
+    let names = ["alpha", "beta"];
+    let tids: Vec<_> = names
+        .iter()
+        .map(|name| thread_create(worker as usize, name as *const _ as usize))
+        .collect();
+    for tid in tids {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    assert_eq!(set_output_prefix("main"), 0);
+    println!("prefixed");
+    assert_eq!(set_output_prefix(""), 0);
+    println!("output_prefix passed!");
+    0
+}
@@ -0,0 +1,20 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fault_inject, FAULT_KERNEL_PANIC};
+
+/// Panic the kernel to see what `PANIC_ACTION` does: with `Halt` QEMU exits
+/// with a failure status, with `Reboot` the kernel boots again, and with
+/// `Shell` a "panic> " prompt takes `regs`, `metrics` and `continue`. Each
+/// action is checked without a real panic by the kernel's `panic actions`
+/// self-test.
+#[no_mangle]
+pub fn main() -> i32 {
+    println!("panic_action: panicking the kernel");
+    fault_inject(FAULT_KERNEL_PANIC);
+    println!("panic_action: the kernel did not panic");
+    -1
+}
@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, kill, pause, sleep, waitpid, SignalFlags};
+
+/// Fork a child that pauses, signal it once it sleeps and return its exit code.
+fn pause_then_kill(signal: SignalFlags) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        pause();
+        // not reached: the signal kills us on the way out of pause
+        exit(1);
+    }
+    sleep(20);
+    assert_eq!(kill(pid as usize, signal.bits()), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(pause_then_kill(SignalFlags::SIGINT), -2);
+    assert_eq!(pause_then_kill(SignalFlags::SIGKILL), -9);
+    println!("pause passed!");
+    0
+}
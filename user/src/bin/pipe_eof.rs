@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, pipe, read, waitpid, write, KernelError};
+
+static STR: &str = "Hello, pipe!";
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        // child: drop our copy of the write end, then read until EOF
+        assert_eq!(close(pipe_fd[1]), 0);
+        let mut buffer = [0u8; 32];
+        let mut total = 0;
+        loop {
+            let n = read(pipe_fd[0], &mut buffer[total..]);
+            assert!(n >= 0);
+            if n == 0 {
+                break;
+            }
+            total += n as usize;
+        }
+        assert_eq!(&buffer[..total], STR.as_bytes());
+        exit(0);
+    }
+    assert_eq!(close(pipe_fd[0]), 0);
+    assert_eq!(write(pipe_fd[1], STR.as_bytes()), STR.len() as isize);
+    // closing the last write end lets the reader see EOF
+    assert_eq!(close(pipe_fd[1]), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // already closed or out of range
+    assert_eq!(close(pipe_fd[1]), KernelError::BadFd as isize);
+    assert_eq!(close(1024), KernelError::BadFd as isize);
+    println!("pipe_eof passed!");
+    0
+}
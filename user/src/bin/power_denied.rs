@@ -0,0 +1,18 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{drop_privilege, poweroff, reboot};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // the privilege is inherited from initproc, so running under the shell
+    // would be enough to power off; without it both calls are refused
+    assert_eq!(drop_privilege(), 0);
+    assert_eq!(poweroff(0), -1);
+    assert_eq!(reboot(), -1);
+    println!("power_denied passed!");
+    0
+}
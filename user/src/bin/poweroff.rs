@@ -0,0 +1,21 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::poweroff;
+
+/// Power off the machine with exit code `argv[1]`, 0 if not given. Only works
+/// if no ancestor has called `drop_privilege`.
+#[no_mangle]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    let code = if argc > 1 {
+        argv[1].parse().expect("usage: poweroff [code]")
+    } else {
+        0
+    };
+    println!("powering off with code {}", code);
+    poweroff(code);
+    panic!("poweroff returned");
+}
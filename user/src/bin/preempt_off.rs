@@ -0,0 +1,62 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, getrusage, preempt_disable, preempt_enable, switch_count, RUsage};
+
+fn spin(ms: isize) {
+    let start = get_time();
+    while get_time() - start < ms {}
+}
+
+fn involuntary_switches() -> usize {
+    let mut usage = RUsage::default();
+    assert_eq!(getrusage(&mut usage), 0);
+    usage.involuntary_switches
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(preempt_enable(), -1);
+
+    // five time slices without a single switch
+    let (switches, preempted) = (switch_count(), involuntary_switches());
+    assert_eq!(preempt_disable(), 0);
+    spin(50);
+    let (switches_off, preempted_off) = (switch_count(), involuntary_switches());
+    assert_eq!(preempt_enable(), 0);
+    println!(
+        "preemption off: {} switches, {} preemptions",
+        switches_off - switches,
+        preempted_off - preempted
+    );
+    assert_eq!(switches_off, switches);
+    assert_eq!(preempted_off, preempted);
+
+    // the same loop is preempted with preemption on
+    spin(50);
+    assert!(involuntary_switches() > preempted_off);
+
+    // the kernel turns preemption back on after 100 ms
+    let preempted = involuntary_switches();
+    assert_eq!(preempt_disable(), 0);
+    spin(300);
+    assert!(involuntary_switches() > preempted);
+    assert_eq!(preempt_enable(), -1);
+
+    // turning it on for a moment does not start the 100 ms over
+    let preempted = involuntary_switches();
+    assert_eq!(preempt_disable(), 0);
+    let start = get_time();
+    while get_time() - start < 300 {
+        spin(5);
+        preempt_enable();
+        preempt_disable();
+    }
+    preempt_enable();
+    assert!(involuntary_switches() > preempted);
+    println!("preempt_off passed!");
+    0
+}
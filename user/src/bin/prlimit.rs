@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    fork, mmap, munmap, prlimit, waitpid, KernelError, MmapProt, RLIMIT_MMAP_PAGES, RLIM_INFINITY,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let rw = MmapProt::READ | MmapProt::WRITE;
+    let mut old = 0;
+    assert_eq!(prlimit(RLIMIT_MMAP_PAGES, 4, Some(&mut old)), 0);
+    assert_eq!(old, RLIM_INFINITY);
+    assert_eq!(
+        KernelError::from_code(prlimit(99, 4, None)),
+        Some(KernelError::Invalid)
+    );
+
+    let mut pages = [0usize; 4];
+    for page in pages.iter_mut() {
+        let start = mmap(0, PAGE_SIZE, rw);
+        assert!(start > 0);
+        *page = start as usize;
+    }
+    // the 5th page is over the limit
+    assert_eq!(
+        KernelError::from_code(mmap(0, PAGE_SIZE, rw)),
+        Some(KernelError::NoMem)
+    );
+    // so is a single mapping that would cross it
+    assert_eq!(munmap(pages[3], PAGE_SIZE), 0);
+    assert_eq!(
+        KernelError::from_code(mmap(0, 2 * PAGE_SIZE, rw)),
+        Some(KernelError::NoMem)
+    );
+    // unmapping gives pages back
+    assert!(mmap(0, PAGE_SIZE, rw) > 0);
+
+    // children inherit the limit
+    let pid = fork();
+    if pid == 0 {
+        let mut limit = 0;
+        assert_eq!(prlimit(RLIMIT_MMAP_PAGES, 4, Some(&mut limit)), 0);
+        assert_eq!(limit, 4);
+        return 0;
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    assert_eq!(prlimit(RLIMIT_MMAP_PAGES, RLIM_INFINITY, Some(&mut old)), 0);
+    assert_eq!(old, 4);
+    assert!(mmap(0, PAGE_SIZE, rw) > 0);
+    println!("prlimit passed!");
+    0
+}
@@ -0,0 +1,70 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, profile_start, profile_stop, ProfileBucket};
+
+const HOT_MS: isize = 300;
+const CHUNK: usize = 100_000;
+
+#[inline(never)]
+fn hot_a(n: usize) {
+    let mut x = 0usize;
+    for i in 0..n {
+        unsafe { (&mut x as *mut usize).write_volatile(x.wrapping_add(i)) };
+    }
+}
+
+#[inline(never)]
+fn hot_b(n: usize) {
+    let mut x = 1usize;
+    for i in 0..n {
+        unsafe { (&mut x as *mut usize).write_volatile(x.wrapping_mul(i | 1)) };
+    }
+}
+
+/// Keep calling `f` for `HOT_MS`, so nearly all time is spent inside it.
+fn spin(f: fn(usize)) {
+    let start = get_time();
+    while get_time() - start < HOT_MS {
+        f(CHUNK);
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [ProfileBucket::default(); 64];
+    assert_eq!(profile_stop(&mut buf), -1);
+
+    assert_eq!(profile_start(0), 0);
+    spin(hot_a);
+    spin(hot_b);
+    let n = profile_stop(&mut buf);
+    assert!(n > 0);
+    let buckets = &buf[..n as usize];
+    assert!(buckets.windows(2).all(|w| w[0].samples >= w[1].samples));
+
+    // each function runs up to the next one, the upper one gets 256 bytes
+    let (a, b) = (hot_a as usize, hot_b as usize);
+    let (lo, hi) = (a.min(b), a.max(b));
+    let samples_in = |start: usize, end: usize| -> usize {
+        buckets
+            .iter()
+            .filter(|bucket| (start..end).contains(&bucket.pc))
+            .map(|bucket| bucket.samples)
+            .sum()
+    };
+    let total: usize = buckets.iter().map(|bucket| bucket.samples).sum();
+    let (in_lo, in_hi) = (samples_in(lo, hi), samples_in(hi, hi + 256));
+    println!(
+        "{} samples in {} buckets, {} in {:#x}, {} in {:#x}, top pc {:#x}",
+        total, n, in_lo, lo, in_hi, hi, buckets[0].pc
+    );
+    assert!((in_lo + in_hi) * 10 >= total * 8);
+    assert!(in_lo * 4 >= total && in_hi * 4 >= total);
+    assert!((lo..hi + 256).contains(&buckets[0].pc));
+    println!("profile passed!");
+    0
+}
@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, meminfo, mmap, munmap, spawn, thread_create, waitpid, waittid, KernelError,
+    MemInfo, MmapProt,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+/// Frames left for user memory after draining, enough for the drain's own
+/// page tables.
+const SPARE: usize = 256;
+
+fn mem_info() -> MemInfo {
+    let mut info = MemInfo::default();
+    meminfo(&mut info);
+    info
+}
+
+fn frames_free() -> usize {
+    mem_info().frames_free
+}
+
+fn thread_main() -> ! {
+    exit(7)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let reserved = mem_info().frames_reserved;
+    assert!(reserved > 0);
+    let rw = MmapProt::READ | MmapProt::WRITE;
+    let populate = rw | MmapProt::POPULATE;
+    let drain_len = (frames_free() - reserved - SPARE) * PAGE_SIZE;
+    let drain = mmap(0, drain_len, populate);
+    assert!(drain > 0);
+
+    // free frames are still there, but only the reserve is left of them
+    let top_up_len = (frames_free() - reserved - 8) * PAGE_SIZE;
+    let top_up = mmap(0, top_up_len, populate);
+    assert!(top_up > 0);
+    let free = frames_free();
+    println!("{} frames free, {} reserved", free, reserved);
+    assert!(free > reserved);
+    assert!(free <= reserved + 8);
+    assert_eq!(
+        mmap(0, (free - reserved + 1) * PAGE_SIZE, populate),
+        KernelError::NoMem as isize
+    );
+    assert_eq!(fork(), KernelError::NoMem as isize);
+    // take the last frames outside the reserve, a page at a time
+    let mut pages = [0; 16];
+    let mut taken = 0;
+    loop {
+        let page = mmap(0, PAGE_SIZE, populate);
+        if page < 0 {
+            assert_eq!(page, KernelError::NoMem as isize);
+            break;
+        }
+        pages[taken] = page as usize;
+        taken += 1;
+    }
+    // a new image, and a thread's stack and trap context, are user memory too
+    assert_eq!(spawn("hello_world\0"), KernelError::NoMem as isize);
+    assert_eq!(
+        thread_create(thread_main as usize, 0),
+        KernelError::NoMem as isize
+    );
+
+    for page in &pages[..taken] {
+        assert_eq!(munmap(*page, PAGE_SIZE), 0);
+    }
+    assert_eq!(munmap(top_up as usize, top_up_len), 0);
+    assert_eq!(munmap(drain as usize, drain_len), 0);
+    // with the frames back, fork and thread_create work again
+    let pid = fork();
+    if pid == 0 {
+        exit(0);
+    }
+    assert!(pid > 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    let tid = thread_create(thread_main as usize, 0);
+    assert!(tid > 0);
+    assert_eq!(waittid(tid as usize), 7);
+    println!("reserved_frames passed!");
+    0
+}
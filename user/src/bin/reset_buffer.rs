@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    dmesg, exit, fork, reset_buffer, sched_trace, set_log_level, waitpid, yield_, BUFFER_DMESG,
+    BUFFER_SCHED_TRACE,
+};
+
+const LEVEL_INFO: usize = 3;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [0u8; 1024];
+    // process exits are logged at info level
+    let prev = set_log_level(LEVEL_INFO);
+    assert!(prev >= 0);
+    let pid = fork();
+    if pid == 0 {
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    set_log_level(prev as usize);
+    assert!(dmesg(&mut buf) > 0);
+    assert_eq!(reset_buffer(BUFFER_DMESG), 0);
+    assert_eq!(dmesg(&mut buf), 0);
+
+    yield_();
+    assert!(sched_trace(&mut buf) > 0);
+    assert_eq!(reset_buffer(BUFFER_SCHED_TRACE), 0);
+    assert_eq!(sched_trace(&mut buf), 0);
+
+    assert_eq!(reset_buffer(2), -1);
+    println!("reset_buffer passed!");
+    0
+}
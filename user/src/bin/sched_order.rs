@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, pipe, read, wait, write, yield_};
+
+const CHILDREN: usize = 3;
+const ROUNDS: usize = 5;
+
+/// Let a few children take turns writing their id to a pipe and return the
+/// order the bytes arrived in.
+fn record() -> [u8; CHILDREN * ROUNDS] {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    for id in 0..CHILDREN {
+        if fork() == 0 {
+            close(pipe_fd[0]);
+            for _ in 0..ROUNDS {
+                write(pipe_fd[1], &[b'a' + id as u8]);
+                yield_();
+            }
+            close(pipe_fd[1]);
+            exit(0);
+        }
+    }
+    close(pipe_fd[1]);
+    let mut exit_code: i32 = 0;
+    for _ in 0..CHILDREN {
+        wait(&mut exit_code);
+    }
+    let mut order = [0u8; CHILDREN * ROUNDS];
+    assert_eq!(read(pipe_fd[0], &mut order), order.len() as isize);
+    close(pipe_fd[0]);
+    order
+}
+
+/// Only meaningful with `DETERMINISTIC_SCHED` enabled in the kernel config.
+#[no_mangle]
+pub fn main() -> i32 {
+    let first = record();
+    let second = record();
+    println!("first:  {}", core::str::from_utf8(&first).unwrap());
+    println!("second: {}", core::str::from_utf8(&second).unwrap());
+    assert_eq!(first, second);
+    println!("sched_order passed!");
+    0
+}
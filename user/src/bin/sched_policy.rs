@@ -0,0 +1,121 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    drop_privilege, exit, fork, gettid, preempt_disable, preempt_enable, sched_setscheduler,
+    semaphore_create, semaphore_down, semaphore_up, thread_create, waitpid, waittid, yield_,
+    KernelError, SCHED_POLICY_EDF, SCHED_POLICY_PRIORITY, SCHED_POLICY_RR,
+};
+
+const THREADS: usize = 3;
+
+/// Threads append their number here when they first get past `GATE`.
+static mut ORDER: [usize; THREADS] = [0; THREADS];
+static RAN: AtomicUsize = AtomicUsize::new(0);
+/// Threads that reached `GATE`.
+static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+/// Semaphore id the threads wait on until all of them are ready.
+static GATE: AtomicUsize = AtomicUsize::new(0);
+
+fn record(n: usize) -> ! {
+    ARRIVED.fetch_add(1, Ordering::SeqCst);
+    semaphore_down(GATE.load(Ordering::SeqCst));
+    let slot = RAN.fetch_add(1, Ordering::SeqCst);
+    unsafe { ORDER[slot] = n };
+    exit(0)
+}
+
+/// Create threads 1..=THREADS, let `setup` change their policies, and return
+/// the order they ran in once released together. The threads wait on `GATE`
+/// first, so that a timer tick or the kernel scheduler in use cannot let one
+/// of them record before the others are ready.
+fn run_order(setup: fn(&[usize; THREADS])) -> [usize; THREADS] {
+    RAN.store(0, Ordering::SeqCst);
+    ARRIVED.store(0, Ordering::SeqCst);
+    let gate = semaphore_create(0);
+    assert!(gate >= 0);
+    GATE.store(gate as usize, Ordering::SeqCst);
+    let mut tids = [0; THREADS];
+    for (i, tid) in tids.iter_mut().enumerate() {
+        let ret = thread_create(record as usize, i + 1);
+        assert!(ret > 0);
+        *tid = ret as usize;
+    }
+    setup(&tids);
+    while ARRIVED.load(Ordering::SeqCst) < THREADS {
+        yield_();
+    }
+    // a thread preempted between arriving and blocking is still ready, so
+    // all of them are ready once the gate is open; keep the timer from
+    // running one before the last is released
+    preempt_disable();
+    for _ in 0..THREADS {
+        semaphore_up(gate as usize);
+    }
+    preempt_enable();
+    for tid in tids {
+        assert_eq!(waittid(tid), 0);
+    }
+    unsafe { ORDER }
+}
+
+fn idle() -> ! {
+    exit(0)
+}
+
+/// Once privilege is dropped, only moving the caller to round robin is left.
+fn unprivileged() -> i32 {
+    assert_eq!(drop_privilege(), 0);
+    let me = gettid() as usize;
+    assert_eq!(
+        sched_setscheduler(me, SCHED_POLICY_PRIORITY, usize::MAX),
+        -1
+    );
+    assert_eq!(sched_setscheduler(me, SCHED_POLICY_EDF, 1), -1);
+    assert_eq!(sched_setscheduler(me, SCHED_POLICY_RR, 0), 0);
+    let other = thread_create(idle as usize, 0);
+    assert!(other > 0);
+    assert_eq!(sched_setscheduler(other as usize, SCHED_POLICY_RR, 0), -1);
+    assert_eq!(waittid(other as usize), 0);
+    0
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let me = gettid() as usize;
+    assert_eq!(
+        KernelError::from_code(sched_setscheduler(me, 7, 0)),
+        Some(KernelError::Invalid)
+    );
+    assert_eq!(
+        KernelError::from_code(sched_setscheduler(me, SCHED_POLICY_EDF, 0)),
+        Some(KernelError::Invalid)
+    );
+    assert_eq!(sched_setscheduler(1000, SCHED_POLICY_RR, 0), -1);
+    assert_eq!(sched_setscheduler(me, SCHED_POLICY_PRIORITY, 1), 0);
+    assert_eq!(sched_setscheduler(me, SCHED_POLICY_RR, 0), 0);
+
+    let by_priority = run_order(|tids| {
+        assert_eq!(sched_setscheduler(tids[1], SCHED_POLICY_PRIORITY, 5), 0);
+        assert_eq!(sched_setscheduler(tids[2], SCHED_POLICY_PRIORITY, 10), 0);
+    });
+    println!("2 and 3 moved to priority: {:?}", by_priority);
+    assert_eq!(by_priority, [3, 2, 1]);
+
+    let pid = fork();
+    if pid == 0 {
+        exit(unprivileged());
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // dropping it in the child left the parent privileged
+    assert_eq!(sched_setscheduler(me, SCHED_POLICY_PRIORITY, 1), 0);
+    assert_eq!(sched_setscheduler(me, SCHED_POLICY_RR, 0), 0);
+    println!("sched_policy passed!");
+    0
+}
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use core::str::from_utf8;
+use user_lib::{
+    fork, getpid, kill, meminfo, mmap, sched_trace, waitpid, yield_, MemInfo, MmapProt, SignalFlags,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+/// must match MEMORY_PRESSURE_FRAMES in the kernel config
+const PRESSURE_FRAMES: usize = 1024;
+const CHUNK_PAGES: usize = 256;
+
+fn frames_free() -> usize {
+    let mut info = MemInfo::default();
+    meminfo(&mut info);
+    info.frames_free
+}
+
+/// Only meaningful with `SCHEDULER = SchedulerKind::MemoryAware` in the kernel config.
+#[no_mangle]
+pub fn main() -> i32 {
+    let small = fork();
+    if small == 0 {
+        loop {
+            yield_();
+        }
+    }
+    // become the largest task and push free frames below the threshold,
+    // leaving some headroom for page tables
+    while frames_free() >= PRESSURE_FRAMES {
+        let pages = CHUNK_PAGES.min(frames_free() - PRESSURE_FRAMES / 2);
+        assert!(
+            mmap(
+                0,
+                pages * PAGE_SIZE,
+                MmapProt::READ | MmapProt::WRITE | MmapProt::POPULATE
+            ) > 0
+        );
+    }
+    for _ in 0..16 {
+        yield_();
+    }
+    let mut buf = [0u8; 4096];
+    let len = sched_trace(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    println!("{}", text);
+    let me = format!("-> {}:0", getpid());
+    let picks = text.lines().count();
+    let mine = text.lines().filter(|line| line.contains(&me)).count();
+    println!("picked {} of {} times under pressure", mine, picks);
+    // round robin with the small child, usertests and initproc would give 1/4
+    assert!(mine * 4 < picks);
+    assert_eq!(kill(small as usize, SignalFlags::SIGKILL.bits()), 0);
+    let mut exit_code: i32 = 0;
+    waitpid(small as usize, &mut exit_code);
+    println!("sched_pressure passed!");
+    0
+}
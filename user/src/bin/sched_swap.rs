@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    drop_privilege, exit, fork, set_scheduler, sleep, thread_create, waitpid, waittid, SCHED_LIFO,
+    SCHED_RR,
+};
+
+const THREADS: usize = 3;
+
+static STARTED: AtomicUsize = AtomicUsize::new(0);
+static mut ORDER: [usize; THREADS] = [0; THREADS];
+
+/// Record the start order. The last thread to start switches back to round
+/// robin, so yielding tasks elsewhere cannot starve the others under LIFO.
+fn record(id: usize) -> ! {
+    let slot = STARTED.fetch_add(1, Ordering::SeqCst);
+    unsafe {
+        ORDER[slot] = id;
+    }
+    if slot == THREADS - 1 {
+        set_scheduler(SCHED_RR);
+    }
+    exit(0)
+}
+
+/// Create the threads, get off the CPU and return their start order.
+fn run_threads() -> [usize; THREADS] {
+    STARTED.store(0, Ordering::SeqCst);
+    let mut tids = [0; THREADS];
+    for (i, tid) in tids.iter_mut().enumerate() {
+        *tid = thread_create(record as usize, i + 1) as usize;
+    }
+    // blocked on a timer, not in the ready queue
+    sleep(10);
+    for tid in tids {
+        assert_eq!(waittid(tid), 0);
+    }
+    unsafe { ORDER }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_scheduler(99), -1);
+
+    assert_eq!(set_scheduler(SCHED_RR), SCHED_RR as isize);
+    let rr = run_threads();
+    println!("round robin order: {:?}", rr);
+    assert_eq!(rr, [1, 2, 3]);
+
+    assert_eq!(set_scheduler(SCHED_LIFO), SCHED_RR as isize);
+    let lifo = run_threads();
+    println!("lifo order: {:?}", lifo);
+    // the last thread already switched back
+    assert_eq!(set_scheduler(SCHED_RR), SCHED_RR as isize);
+    assert_eq!(lifo, [3, 2, 1]);
+
+    // the scheduler is shared by every task, so switching it is privileged
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(drop_privilege(), 0);
+        exit((set_scheduler(SCHED_LIFO) == -1) as i32);
+    }
+    let mut refused = 0;
+    assert_eq!(waitpid(pid as usize, &mut refused), pid);
+    assert_eq!(refused, 1);
+    assert_eq!(set_scheduler(SCHED_RR), SCHED_RR as isize);
+    println!("sched_swap passed!");
+    0
+}
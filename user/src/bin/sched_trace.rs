@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use core::str::from_utf8;
+use user_lib::{getpid, sched_trace, yield_};
+
+const YIELD_COUNT: usize = 5;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..YIELD_COUNT {
+        yield_();
+    }
+    let mut buf = [0u8; 4096];
+    let len = sched_trace(&mut buf);
+    assert!(len > 0);
+    let text = from_utf8(&buf[..len as usize]).unwrap();
+    println!("{}", text);
+    let me = format!("{}:0", getpid());
+    let out = format!("{} -> ", me);
+    let into = format!(" -> {}", me);
+    let yields = text
+        .lines()
+        .filter(|line| line.contains(&out) && line.ends_with("(ready)"))
+        .count();
+    assert!(yields >= YIELD_COUNT);
+    assert!(text.lines().any(|line| line.contains(&into)));
+    // a short buffer is filled up to its length
+    let mut short = [0u8; 8];
+    assert_eq!(sched_trace(&mut short), 8);
+    println!("sched_trace passed!");
+    0
+}
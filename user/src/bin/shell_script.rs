@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::str::from_utf8;
+use user_lib::{close, dup, exec, fork, open, pipe, read, waitpid, write, OpenFlags};
+
+/// A blank line, spaces only, an unknown app and a real one.
+const SCRIPT: &str = "\n   \nno_such_app\nhello_world > shell_out\n";
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        // the shell reads its commands from the pipe
+        close(0);
+        assert_eq!(dup(pipe_fd[0]), 0);
+        close(pipe_fd[0]);
+        close(pipe_fd[1]);
+        exec(
+            "user_shell\0",
+            &["user_shell\0".as_ptr(), core::ptr::null()],
+        );
+        panic!("unreachable!");
+    }
+    close(pipe_fd[0]);
+    assert_eq!(write(pipe_fd[1], SCRIPT.as_bytes()), SCRIPT.len() as isize);
+    // EOF ends the shell
+    close(pipe_fd[1]);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    let fd = open("shell_out\0", OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut buf = [0u8; 128];
+    let len = read(fd as usize, &mut buf) as usize;
+    close(fd as usize);
+    let text = from_utf8(&buf[..len]).unwrap();
+    assert!(text.contains("Hello world from user mode program!"));
+    println!("shell_script passed!");
+    0
+}
@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, sleep_until, TimeVal};
+
+const DELAY_MS: isize = 50;
+/// the timer ticks every 10ms, allow a few ticks of lateness
+const MAX_JITTER_MS: isize = 30;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    let deadline = TimeVal::from_ms((start + DELAY_MS) as usize);
+    assert_eq!(sleep_until(&deadline), 0);
+    let elapsed = get_time() - start;
+    println!("slept {}ms for a {}ms deadline", elapsed, DELAY_MS);
+    assert!(elapsed >= DELAY_MS);
+    assert!(elapsed - DELAY_MS <= MAX_JITTER_MS);
+    // a deadline in the past returns at once
+    let start = get_time();
+    assert_eq!(sleep_until(&TimeVal::default()), 0);
+    assert!(get_time() - start < DELAY_MS);
+    // malformed usec
+    let bad = TimeVal {
+        sec: 0,
+        usec: 1_000_000,
+    };
+    assert_eq!(sleep_until(&bad), -1);
+    println!("sleep_until passed!");
+    0
+}
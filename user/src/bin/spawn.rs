@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    close, exec, exit, fork, getpid, open, read, spawn, waitpid, write, KernelError, OpenFlags,
+};
+
+/// Write `data` to a new file `path`.
+fn make_file(path: &str, data: &[u8]) {
+    let fd = open(path, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    assert_eq!(write(fd as usize, data), data.len() as isize);
+    close(fd as usize);
+}
+
+/// Bad app images are refused with `Invalid`, by spawn and by exec, which
+/// then keeps running the old image.
+fn bad_images() {
+    make_file("not_an_elf\0", b"not an elf file");
+    // headers intact, segment data cut off
+    let fd = open("hello_world\0", OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let mut head = [0u8; 512];
+    assert_eq!(read(fd as usize, &mut head), head.len() as isize);
+    close(fd as usize);
+    make_file("truncated_elf\0", &head);
+    for path in ["not_an_elf\0", "truncated_elf\0"] {
+        assert_eq!(spawn(path), KernelError::Invalid as isize);
+        let pid = fork();
+        if pid == 0 {
+            let args = [path.as_ptr(), core::ptr::null()];
+            assert_eq!(exec(path, &args), KernelError::Invalid as isize);
+            exit(3);
+        }
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 3);
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(spawn("no_such_app\0"), -1);
+    let hello = spawn("hello_world\0");
+    let forktest = spawn("forktest_simple\0");
+    assert!(hello > 0 && forktest > 0);
+    assert!(hello != forktest && hello != getpid());
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(hello as usize, &mut exit_code), hello);
+    assert_eq!(exit_code, 0);
+    exit_code = -1;
+    assert_eq!(waitpid(forktest as usize, &mut exit_code), forktest);
+    assert_eq!(exit_code, 0);
+    bad_images();
+    println!("spawn passed!");
+    0
+}
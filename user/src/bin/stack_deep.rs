@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+/// Stack used per call, well past the default 8 KiB stack after `DEPTH` calls.
+const FRAME_BYTES: usize = 1024;
+const DEPTH: usize = 32;
+
+fn recurse(depth: usize) -> usize {
+    let mut frame = [0u8; FRAME_BYTES];
+    unsafe { (&mut frame[0] as *mut u8).write_volatile(depth as u8) };
+    if depth == 0 {
+        return 0;
+    }
+    let below = recurse(depth - 1);
+    below + unsafe { (&frame[0] as *const u8).read_volatile() } as usize
+}
+
+/// Runs with the larger stack from `APP_STACK_SIZES` in the kernel config.
+#[no_mangle]
+pub fn main() -> i32 {
+    let sum = recurse(DEPTH);
+    println!(
+        "recursed {} frames of {} bytes, sum {}",
+        DEPTH, FRAME_BYTES, sum
+    );
+    assert_eq!(sum, DEPTH * (DEPTH + 1) / 2);
+    println!("stack_deep passed!");
+    0
+}
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::str::from_utf8;
+use user_lib::{
+    dmesg, exit, fork, getrusage, reset_buffer, set_log_level, waitpid, RUsage, BUFFER_DMESG,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+/// `stack_growth` gets 16 pages of stack from `APP_STACK_SIZES`.
+const STACK_PAGES: usize = 16;
+const FRAME_BYTES: usize = 1024;
+const LEVEL_WARN: usize = 2;
+
+fn recurse(depth: usize) -> usize {
+    let mut frame = [0u8; FRAME_BYTES];
+    unsafe { (&mut frame[0] as *mut u8).write_volatile(1) };
+    if depth == 0 {
+        return 0;
+    }
+    recurse(depth - 1) + unsafe { (&frame[0] as *const u8).read_volatile() } as usize
+}
+
+fn max_rss_pages() -> usize {
+    let mut usage = RUsage::default();
+    getrusage(&mut usage);
+    usage.max_rss_pages
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // within the limit: the stack gets frames as it grows
+    let before = max_rss_pages();
+    let depth = STACK_PAGES / 2 * PAGE_SIZE / FRAME_BYTES;
+    assert_eq!(recurse(depth), depth);
+    let grown = max_rss_pages() - before;
+    println!("{} frames deep grew the stack by {} pages", depth, grown);
+    assert!(grown >= STACK_PAGES / 2 - 2);
+
+    // beyond the limit
+    assert_eq!(reset_buffer(BUFFER_DMESG), 0);
+    let prev = set_log_level(LEVEL_WARN);
+    assert!(prev >= 0);
+    let pid = fork();
+    if pid == 0 {
+        recurse(STACK_PAGES * PAGE_SIZE / FRAME_BYTES * 2);
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    set_log_level(prev as usize);
+    assert_eq!(exit_code, -11);
+    let mut buf = [0u8; 1024];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    assert!(text
+        .lines()
+        .any(|line| line.contains("stack limit exceeded")));
+    println!("stack_growth passed!");
+    0
+}
@@ -0,0 +1,23 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::read;
+
+const STDIN: usize = 0;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    println!("type \"abc\" and press Enter");
+    let mut buf = [0u8; 16];
+    let len = read(STDIN, &mut buf);
+    println!("got {} bytes: {:?}", len, &buf[..len as usize]);
+    // the whole line comes back from one read, terminator included
+    assert_eq!(len, 4);
+    assert_eq!(&buf[..3], b"abc");
+    assert!(buf[3] == b'\n' || buf[3] == b'\r');
+    println!("stdin_line passed!");
+    0
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use core::str::from_utf8;
+use user_lib::{dmesg, getpid, getppid, reset_buffer, set_log_level, trace_syscalls, BUFFER_DMESG};
+
+const LEVEL_INFO: usize = 3;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // syscalls are traced at info level
+    let prev = set_log_level(LEVEL_INFO);
+    assert!(prev >= 0);
+    assert_eq!(reset_buffer(BUFFER_DMESG), 0);
+    assert_eq!(trace_syscalls(true), 0);
+    let pid = getpid();
+    let ppid = getppid();
+    assert_eq!(trace_syscalls(false), 0);
+    // not traced any more
+    getpid();
+    set_log_level(prev as usize);
+
+    let mut buf = [0u8; 1024];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    println!("{}", text);
+    let traced = |id: usize, ret: isize| {
+        let expected = format!("syscall {}(0x0, 0x0, 0x0) = {}", id, ret);
+        text.lines()
+            .filter(|line| line.contains("[strace]") && line.contains(&expected))
+            .count()
+    };
+    assert_eq!(traced(SYSCALL_GETPID, pid), 1);
+    assert_eq!(traced(SYSCALL_GETPPID, ppid), 1);
+    println!("strace passed!");
+    0
+}
@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{switch_count, task_info, yield_, TaskInfo};
+
+const YIELD_COUNT: usize = 50;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = TaskInfo::default();
+    task_info(&mut before);
+    let global_before = switch_count();
+    for _ in 0..YIELD_COUNT {
+        yield_();
+    }
+    let mut after = TaskInfo::default();
+    task_info(&mut after);
+    let global_after = switch_count();
+    println!(
+        "switches: task {} -> {}, global {} -> {}",
+        before.switches, after.switches, global_before, global_after
+    );
+    assert!(after.switches - before.switches >= YIELD_COUNT);
+    assert!((global_after - global_before) as usize >= YIELD_COUNT);
+    println!("switch_count passed!");
+    0
+}
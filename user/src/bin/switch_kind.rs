@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, task_info, yield_, TaskInfo};
+
+const YIELD_COUNT: usize = 20;
+const BUSY_MS: isize = 50;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut start = TaskInfo::default();
+    task_info(&mut start);
+    for _ in 0..YIELD_COUNT {
+        yield_();
+    }
+    let mut yielded = TaskInfo::default();
+    task_info(&mut yielded);
+    let begin = get_time();
+    while get_time() - begin < BUSY_MS {}
+    let mut busy = TaskInfo::default();
+    task_info(&mut busy);
+    println!(
+        "yielding: {} voluntary, busy loop: {} involuntary",
+        yielded.voluntary_switches - start.voluntary_switches,
+        busy.involuntary_switches - yielded.involuntary_switches
+    );
+    assert_eq!(
+        yielded.voluntary_switches - start.voluntary_switches,
+        YIELD_COUNT
+    );
+    // a busy loop never yields but gets preempted by the timer
+    assert_eq!(busy.voluntary_switches, yielded.voluntary_switches);
+    assert!(busy.involuntary_switches - yielded.involuntary_switches >= 2);
+    println!("switch_kind passed!");
+    0
+}
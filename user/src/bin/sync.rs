@@ -0,0 +1,26 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::str::from_utf8;
+use user_lib::{dmesg, reset_buffer, set_log_level, sync, BUFFER_DMESG};
+
+const LEVEL_DEBUG: usize = 4;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(reset_buffer(BUFFER_DMESG), 0);
+    // every flushed subsystem is logged at debug level
+    let prev = set_log_level(LEVEL_DEBUG);
+    assert!(prev >= 0);
+    assert_eq!(sync(), 0);
+    set_log_level(prev as usize);
+    let mut buf = [0u8; 1024];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    assert!(text.lines().any(|line| line.contains("sync: flushed noop")));
+    println!("sync passed!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{syscall_latency, write, SyscallLatency};
+
+const SYSCALL_WRITE: usize = 64;
+const STDOUT: usize = 1;
+const WRITE_COUNT: usize = 100;
+const MAX_ENTRIES: usize = 32;
+
+fn write_stat() -> SyscallLatency {
+    let mut buf = [SyscallLatency::default(); MAX_ENTRIES];
+    let n = syscall_latency(&mut buf) as usize;
+    buf[..n]
+        .iter()
+        .find(|stat| stat.id == SYSCALL_WRITE)
+        .copied()
+        .unwrap_or_default()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let before = write_stat();
+    for _ in 0..WRITE_COUNT {
+        write(STDOUT, b"");
+    }
+    let after = write_stat();
+    println!(
+        "sys_write: {} calls, {}us in total",
+        after.count, after.total_us
+    );
+    assert_eq!(after.count - before.count, WRITE_COUNT);
+    assert!(after.total_us >= before.total_us);
+    // entries come back ordered by id
+    let mut buf = [SyscallLatency::default(); MAX_ENTRIES];
+    let n = syscall_latency(&mut buf) as usize;
+    assert!(buf[..n].windows(2).all(|w| w[0].id < w[1].id));
+    println!("syscall_latency passed!");
+    0
+}
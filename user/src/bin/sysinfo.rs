@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{sleep, sysinfo, SysInfo};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = SysInfo::default();
+    assert_eq!(sysinfo(&mut before), 0);
+    // the parent and initproc are blocked in waitpid, so nothing is left to
+    // run while we sleep
+    sleep(50);
+    let mut after = SysInfo::default();
+    assert_eq!(sysinfo(&mut after), 0);
+    println!("{:?}", after);
+    assert!(after.uptime_ms - before.uptime_ms >= 50);
+    let idle = after.idle_time_ms - before.idle_time_ms;
+    assert!(idle > 0 && idle <= after.uptime_ms - before.uptime_ms);
+    assert!(after.idle_time_ms <= after.uptime_ms);
+    assert!(after.switches > before.switches);
+    assert!(after.tasks_created >= before.tasks_created);
+    println!("sysinfo passed!");
+    0
+}
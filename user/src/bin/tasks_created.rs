@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, tasks_created, thread_create, waitpid, waittid};
+
+fn thread_main() -> ! {
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let before = tasks_created();
+    // every running task went through the creation hook
+    assert!(before > 0);
+    let pid = fork();
+    if pid == 0 {
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    let tid = thread_create(thread_main as usize, 0);
+    assert_eq!(waittid(tid as usize), 0);
+    let after = tasks_created();
+    println!("tasks created: {} -> {}", before, after);
+    // one forked process and one thread
+    assert_eq!(after - before, 2);
+    println!("tasks_created passed!");
+    0
+}
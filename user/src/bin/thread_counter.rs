@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, mmap, thread_create, waittid, MmapProt};
+
+const PAGE_SIZE: usize = 0x1000;
+const THREADS: usize = 2;
+const INCREMENTS: usize = 10_000;
+
+fn increment(counter: *const AtomicUsize) -> ! {
+    let counter = unsafe { &*counter };
+    for _ in 0..INCREMENTS {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // not touched yet: the first thread to get there faults the page in for
+    // the whole process
+    let start = mmap(0, PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    let counter = start as *const AtomicUsize;
+    let mut tids = [0; THREADS];
+    for tid in tids.iter_mut() {
+        *tid = thread_create(increment as usize, counter as usize);
+        assert!(*tid > 0);
+    }
+    for tid in tids {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    let total = unsafe { &*counter }.load(Ordering::Relaxed);
+    println!("counter: {}", total);
+    assert_eq!(total, THREADS * INCREMENTS);
+    println!("thread_counter passed!");
+    0
+}
@@ -0,0 +1,85 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use user_lib::{
+    exit, fork, futex_wait, get_time, semaphore_create, semaphore_down, sleep, thread_create,
+    waitpid,
+};
+
+/// how long the killed sleeper would sleep
+const SLEEP_MS: usize = 200;
+
+static LEFT_BY_MAIN: AtomicI32 = AtomicI32::new(0);
+static FUTEX_WORD: AtomicU32 = AtomicU32::new(0);
+
+/// Outlives the main thread, then ends the process with what main left.
+fn survivor() -> ! {
+    sleep(50);
+    exit(LEFT_BY_MAIN.load(Ordering::SeqCst))
+}
+
+fn spinner() -> ! {
+    #[allow(clippy::empty_loop)]
+    loop {}
+}
+
+fn sleeper() -> ! {
+    sleep(SLEEP_MS);
+    exit(1)
+}
+
+fn sem_waiter(sem_id: usize) -> ! {
+    semaphore_down(sem_id);
+    exit(1)
+}
+
+fn futex_waiter() -> ! {
+    loop {
+        futex_wait(&FUTEX_WORD, 0);
+    }
+}
+
+fn wait_for(pid: isize) -> i32 {
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // the main thread exits first: the address space stays with the other
+    // thread, whose exit code ends the process
+    let pid = fork();
+    if pid == 0 {
+        thread_create(survivor as usize, 0);
+        LEFT_BY_MAIN.store(5, Ordering::SeqCst);
+        exit(3);
+    }
+    assert_eq!(wait_for(pid), 5);
+
+    // returning from main kills the other threads, whether ready, sleeping
+    // or blocked
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        let sem_id = semaphore_create(0) as usize;
+        thread_create(spinner as usize, 0);
+        thread_create(sleeper as usize, 0);
+        thread_create(sem_waiter as usize, sem_id);
+        thread_create(futex_waiter as usize, 0);
+        sleep(10);
+        return 9;
+    }
+    assert_eq!(wait_for(pid), 9);
+    let elapsed = get_time() - start;
+    println!("process with 4 more threads gone after {}ms", elapsed);
+    assert!(elapsed < SLEEP_MS as isize);
+    // the dead sleeper's timer must not wake it
+    sleep(2 * SLEEP_MS);
+    println!("thread_exit passed!");
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, getpid, gettid, thread_create, waittid};
+
+/// (pid, tid) as seen by each thread, main thread first.
+static mut IDS: [(isize, isize); 2] = [(-1, -1); 2];
+
+fn report(slot: usize) -> ! {
+    unsafe {
+        IDS[slot] = (getpid(), gettid());
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    unsafe {
+        IDS[0] = (getpid(), gettid());
+    }
+    let tid = thread_create(report as usize, 1);
+    assert!(tid > 0);
+    assert_eq!(waittid(tid as usize), 0);
+    let [(main_pid, main_tid), (pid, thread_tid)] = unsafe { IDS };
+    println!(
+        "main: pid {} tid {}, thread: pid {} tid {}",
+        main_pid, main_tid, pid, thread_tid
+    );
+    assert_eq!(main_pid, pid);
+    assert_eq!(main_tid, 0);
+    assert_eq!(thread_tid, tid);
+    assert_ne!(main_tid, thread_tid);
+    println!("thread_ids passed!");
+    0
+}
@@ -0,0 +1,19 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{assert_duration_ge, assert_duration_le, sleep, time_it};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let elapsed_us = time_it(|| sleep(20));
+    println!("sleep(20) took {}us", elapsed_us);
+    assert_duration_ge(elapsed_us, 20_000);
+    // nothing to do, but a preemption can put other ready tasks in between,
+    // each with a 10ms slice, and the clock only counts whole milliseconds
+    assert_duration_le(time_it(|| {}), 500_000);
+    println!("time_it passed!");
+    0
+}
@@ -0,0 +1,26 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, sleep, sysinfo, SysInfo};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    println!("timebase: {} Hz", info.timebase_hz);
+    // QEMU virt's device tree advertises a 10 MHz timebase
+    assert_eq!(info.timebase_hz, 10_000_000);
+    // both scale the time CSR by the detected rate, so they must agree
+    for ms in [20, 100] {
+        let start = get_time();
+        sleep(ms);
+        let elapsed = (get_time() - start) as usize;
+        println!("sleep({}) took {} ms", ms, elapsed);
+        assert!((ms..ms * 3 / 2 + 20).contains(&elapsed));
+    }
+    println!("timebase passed!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, getrusage, times, RUsage, Tms, CLOCKS_PER_SEC};
+
+fn ticks(us: usize) -> usize {
+    us * CLOCKS_PER_SEC / 1_000_000
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let begin = get_time();
+    while get_time() - begin < 50 {}
+    let mut before = Tms::default();
+    let mut usage = RUsage::default();
+    let mut after = Tms::default();
+    assert_eq!(times(&mut before), 0);
+    assert_eq!(getrusage(&mut usage), 0);
+    assert_eq!(times(&mut after), 0);
+    println!("{:?} {:?}", after, usage);
+    // 50ms of spinning is at least 4 ticks of 10ms
+    assert!(after.utime_ticks >= 4);
+    // the microsecond counts read in between fall between both tick readings
+    assert!(before.utime_ticks <= ticks(usage.user_time_us));
+    assert!(ticks(usage.user_time_us) <= after.utime_ticks);
+    assert!(before.stime_ticks <= ticks(usage.kernel_time_us));
+    assert!(ticks(usage.kernel_time_us) <= after.stime_ticks);
+    println!("times passed!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, set_timeslice, task_info, TaskInfo};
+
+const DEFAULT_TIMESLICE_US: usize = 10_000;
+const RUN_MS: isize = 50;
+
+/// Busy loop for `RUN_MS` and count how often we got switched in.
+fn busy_switches() -> usize {
+    let mut before = TaskInfo::default();
+    task_info(&mut before);
+    let start = get_time();
+    while get_time() - start < RUN_MS {}
+    let mut after = TaskInfo::default();
+    task_info(&mut after);
+    after.switches - before.switches
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(
+        set_timeslice(DEFAULT_TIMESLICE_US),
+        DEFAULT_TIMESLICE_US as isize
+    );
+    let default_switches = busy_switches();
+    assert_eq!(set_timeslice(1_000), 1_000);
+    let short_switches = busy_switches();
+    println!(
+        "switches in {}ms: {} at 10ms, {} at 1ms",
+        RUN_MS, default_switches, short_switches
+    );
+    assert!(short_switches > default_switches * 2);
+    // out of range values are clamped
+    assert_eq!(set_timeslice(0), 1_000);
+    assert_eq!(set_timeslice(usize::MAX), 1_000_000);
+    set_timeslice(DEFAULT_TIMESLICE_US);
+    println!("timeslice passed!");
+    0
+}
@@ -0,0 +1,31 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, sleep_until, TimeVal};
+
+const GUARD: u8 = 0xa5;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tv = TimeVal::from_ms(get_time() as usize);
+    // room for a TimeVal one byte past an aligned address
+    let mut buf = [GUARD; core::mem::size_of::<TimeVal>() + 16];
+    let base = buf.as_mut_ptr() as usize;
+    let offset = core::mem::align_of::<TimeVal>() - base % core::mem::align_of::<TimeVal>() + 1;
+    let misaligned = (base + offset) as *mut TimeVal;
+    unsafe { misaligned.write_unaligned(tv) };
+    assert_eq!(sleep_until(unsafe { &*misaligned }), -1);
+    // the kernel rejected it without writing anywhere
+    assert!(buf[..offset].iter().all(|&b| b == GUARD));
+    assert!(buf[offset + core::mem::size_of::<TimeVal>()..]
+        .iter()
+        .all(|&b| b == GUARD));
+    assert_eq!(unsafe { misaligned.read_unaligned() }.sec, tv.sec);
+    // the same value, aligned, is fine
+    assert_eq!(sleep_until(&tv), 0);
+    println!("timeval_align passed!");
+    0
+}
@@ -0,0 +1,46 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    getpid, mmap, trap_stats, MmapProt, TRAP_SLOTS, TRAP_STORE_PAGE_FAULT, TRAP_USER_ENV_CALL,
+};
+
+const CALLS: usize = 100;
+const PAGE_SIZE: usize = 0x1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = [0; TRAP_SLOTS];
+    let mut after = [0; TRAP_SLOTS];
+    assert_eq!(trap_stats(&mut before), TRAP_SLOTS as isize);
+    for _ in 0..CALLS {
+        getpid();
+    }
+    assert_eq!(trap_stats(&mut after), TRAP_SLOTS as isize);
+    // the second trap_stats is counted too; nothing else runs meanwhile, the
+    // parent is blocked in waitpid
+    let env_calls = after[TRAP_USER_ENV_CALL] - before[TRAP_USER_ENV_CALL];
+
+    // the first store to a lazily mapped page faults
+    let page = mmap(0, PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(page > 0);
+    unsafe {
+        (page as *mut u8).write_volatile(1);
+    }
+    let mut faulted = [0; TRAP_SLOTS];
+    assert_eq!(trap_stats(&mut faulted), TRAP_SLOTS as isize);
+
+    // a short buffer gets a prefix
+    let mut short = [0; 1];
+    assert_eq!(trap_stats(&mut short), 1);
+
+    println!("{:?}", after);
+    assert!((CALLS + 1..CALLS + 10).contains(&env_calls));
+    assert!(faulted[TRAP_STORE_PAGE_FAULT] > after[TRAP_STORE_PAGE_FAULT]);
+    assert!(short[0] > faulted[TRAP_USER_ENV_CALL]);
+    println!("trap_stats passed!");
+    0
+}
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::str::from_utf8;
+use user_lib::{
+    dmesg, fork, mmap, munmap, reset_buffer, set_log_level, waitpid, MmapProt, BUFFER_DMESG,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+const LEVEL_WARN: usize = 2;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = mmap(0, 2 * PAGE_SIZE, MmapProt::READ | MmapProt::WRITE);
+    assert!(start > 0);
+    let addr = start as *mut u8;
+    unsafe {
+        addr.write_volatile(1);
+    }
+    assert_eq!(reset_buffer(BUFFER_DMESG), 0);
+    // the diagnosis is logged at warn level
+    let prev = set_log_level(LEVEL_WARN);
+    assert!(prev >= 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(munmap(start as usize, 2 * PAGE_SIZE), 0);
+        unsafe {
+            addr.add(PAGE_SIZE).write_volatile(2);
+        }
+        panic!("write to an unmapped page should not succeed");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    set_log_level(prev as usize);
+    assert_eq!(exit_code, -11);
+    let mut buf = [0u8; 1024];
+    let len = dmesg(&mut buf) as usize;
+    let text = from_utf8(&buf[..len]).unwrap();
+    assert!(text.lines().any(|line| line.contains("use after unmap")));
+    // the parent still has its own mapping
+    assert_eq!(unsafe { addr.read_volatile() }, 1);
+    println!("use_after_unmap passed!");
+    0
+}
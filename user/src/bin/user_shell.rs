@@ -12,67 +12,11 @@ const CR: u8 = 0x0du8;
 const DL: u8 = 0x7fu8;
 const BS: u8 = 0x08u8;
 const LINE_START: &str = ">> ";
+const STDIN: usize = 0;
 
 use alloc::string::String;
 use alloc::vec::Vec;
-use user_lib::console::getchar;
-use user_lib::{close, dup, exec, fork, open, pipe, waitpid, OpenFlags};
-
-#[derive(Debug)]
-struct ProcessArguments {
-    input: String,
-    output: String,
-    args_copy: Vec<String>,
-    args_addr: Vec<*const u8>,
-}
-
-impl ProcessArguments {
-    pub fn new(command: &str) -> Self {
-        let args: Vec<_> = command.split(' ').collect();
-        let mut args_copy: Vec<String> = args
-            .iter()
-            .filter(|&arg| !arg.is_empty())
-            .map(|&arg| {
-                let mut string = String::new();
-                string.push_str(arg);
-                string.push('\0');
-                string
-            })
-            .collect();
-
-        // redirect input
-        let mut input = String::new();
-        if let Some((idx, _)) = args_copy
-            .iter()
-            .enumerate()
-            .find(|(_, arg)| arg.as_str() == "<\0")
-        {
-            input = args_copy[idx + 1].clone();
-            args_copy.drain(idx..=idx + 1);
-        }
-
-        // redirect output
-        let mut output = String::new();
-        if let Some((idx, _)) = args_copy
-            .iter()
-            .enumerate()
-            .find(|(_, arg)| arg.as_str() == ">\0")
-        {
-            output = args_copy[idx + 1].clone();
-            args_copy.drain(idx..=idx + 1);
-        }
-
-        let mut args_addr: Vec<*const u8> = args_copy.iter().map(|arg| arg.as_ptr()).collect();
-        args_addr.push(core::ptr::null::<u8>());
-
-        Self {
-            input,
-            output,
-            args_copy,
-            args_addr,
-        }
-    }
-}
+use user_lib::{close, dup, exec, fork, open, parse_pipeline, pipe, read, waitpid, OpenFlags};
 
 #[no_mangle]
 pub fn main() -> i32 {
@@ -80,37 +24,18 @@ pub fn main() -> i32 {
     let mut line: String = String::new();
     print!("{}", LINE_START);
     loop {
-        let c = getchar();
+        let mut c = [0u8; 1];
+        // stdin is at EOF when it is a pipe whose writers are gone
+        if read(STDIN, &mut c) <= 0 {
+            println!("");
+            return 0;
+        }
+        let c = c[0];
         match c {
             LF | CR => {
                 println!("");
                 if !line.is_empty() {
-                    let splited: Vec<_> = line.as_str().split('|').collect();
-                    let process_arguments_list: Vec<_> = splited
-                        .iter()
-                        .map(|&cmd| ProcessArguments::new(cmd))
-                        .collect();
-                    let mut valid = true;
-                    for (i, process_args) in process_arguments_list.iter().enumerate() {
-                        if i == 0 {
-                            if !process_args.output.is_empty() {
-                                valid = false;
-                            }
-                        } else if i == process_arguments_list.len() - 1 {
-                            if !process_args.input.is_empty() {
-                                valid = false;
-                            }
-                        } else if !process_args.output.is_empty() || !process_args.input.is_empty()
-                        {
-                            valid = false;
-                        }
-                    }
-                    if process_arguments_list.len() == 1 {
-                        valid = true;
-                    }
-                    if !valid {
-                        println!("Invalid command: Inputs/Outputs cannot be correctly binded!");
-                    } else {
+                    if let Some(process_arguments_list) = parse_pipeline(line.as_str()) {
                         // create pipes
                         let mut pipes_fd: Vec<[usize; 2]> = Vec::new();
                         if !process_arguments_list.is_empty() {
@@ -174,7 +99,10 @@ pub fn main() -> i32 {
                                 }
                                 // execute new application
                                 if exec(args_copy[0].as_str(), args_addr.as_slice()) == -1 {
-                                    println!("Error when executing!");
+                                    println!(
+                                        "{}: command not found",
+                                        args_copy[0].trim_end_matches('\0')
+                                    );
                                     return -4;
                                 }
                                 unreachable!();
@@ -192,6 +120,8 @@ pub fn main() -> i32 {
                             assert_eq!(pid, exit_pid);
                             //println!("Shell: Process {} exited with code {}", pid, exit_code);
                         }
+                    } else {
+                        println!("Invalid command: Inputs/Outputs cannot be correctly binded!");
                     }
                     line.clear();
                 }
@@ -6,6 +6,12 @@ extern crate user_lib;
 
 // not in SUCC_TESTS & FAIL_TESTS
 // count_lines, infloop, user_shell, usertests
+// sched_order (needs DETERMINISTIC_SCHED)
+// sched_pressure (needs SCHEDULER = MemoryAware)
+// stdin_line (needs console input)
+// fault_inject (needs the testing feature)
+// panic_action (needs the testing feature, panics the kernel)
+// poweroff (shuts the machine down, QEMU exits with status 0)
 
 // item of TESTS : app_name(argv_0), argv_1, argv_2, argv_3, exit_code
 static SUCC_TESTS: &[(&str, &str, &str, &str, i32)] = &[
@@ -43,6 +49,92 @@ static SUCC_TESTS: &[(&str, &str, &str, &str, i32)] = &[
     ("yield\0", "\0", "\0", "\0", 0),
     ("barrier_fail\0", "\0", "\0", "\0", 0),
     ("barrier_condvar\0", "\0", "\0", "\0", 0),
+    ("switch_count\0", "\0", "\0", "\0", 0),
+    ("killtest\0", "\0", "\0", "\0", 0),
+    ("alarm\0", "\0", "\0", "\0", 0),
+    ("exit_status\0", "\0", "\0", "\0", 0),
+    ("log_level\0", "\0", "\0", "\0", 0),
+    ("cpu_time\0", "\0", "\0", "\0", 0),
+    ("app_info\0", "\0", "\0", "\0", 0),
+    ("mmap_anywhere\0", "\0", "\0", "\0", 0),
+    ("close_stdout\0", "\0", "\0", "\0", 0),
+    ("pipe_eof\0", "\0", "\0", "\0", 0),
+    ("meminfo\0", "\0", "\0", "\0", 0),
+    ("timeslice\0", "\0", "\0", "\0", 0),
+    ("mmap_readonly\0", "\0", "\0", "\0", 0),
+    ("dup_stdout\0", "\0", "\0", "\0", 0),
+    ("tasks_created\0", "\0", "\0", "\0", 0),
+    ("switch_kind\0", "\0", "\0", "\0", 0),
+    ("syscall_latency\0", "\0", "\0", "\0", 0),
+    ("bad_syscall\0", "\0", "\0", "\0", 0),
+    ("bad_buffer\0", "\0", "\0", "\0", 0),
+    ("sleep_until\0", "\0", "\0", "\0", 0),
+    ("sched_trace\0", "\0", "\0", "\0", 0),
+    ("exit_group\0", "\0", "\0", "\0", 0),
+    ("copy_user\0", "\0", "\0", "\0", 0),
+    ("getppid\0", "\0", "\0", "\0", 0),
+    ("fence\0", "\0", "\0", "\0", 0),
+    ("exit_reason\0", "\0", "\0", "\0", 0),
+    ("dmesg\0", "\0", "\0", "\0", 0),
+    ("kill_accounting\0", "\0", "\0", "\0", 0),
+    ("output_prefix\0", "\0", "\0", "\0", 0),
+    ("mmap_populate\0", "\0", "\0", "\0", 0),
+    ("getrusage\0", "\0", "\0", "\0", 0),
+    ("stack_deep\0", "\0", "\0", "\0", 0),
+    ("timeval_align\0", "\0", "\0", "\0", 0),
+    ("yield_deadline\0", "\0", "\0", "\0", 0),
+    ("reset_buffer\0", "\0", "\0", "\0", 0),
+    ("shell_script\0", "\0", "\0", "\0", 0),
+    ("mprotect\0", "\0", "\0", "\0", 0),
+    ("sync\0", "\0", "\0", "\0", 0),
+    ("times\0", "\0", "\0", "\0", 0),
+    ("wait_time\0", "\0", "\0", "\0", 0),
+    ("pause\0", "\0", "\0", "\0", 0),
+    ("yield_donate\0", "\0", "\0", "\0", 0),
+    ("use_after_unmap\0", "\0", "\0", "\0", 0),
+    ("spawn\0", "\0", "\0", "\0", 0),
+    ("env\0", "\0", "\0", "\0", 0),
+    ("dump_pagetable\0", "\0", "\0", "\0", 0),
+    ("time_it\0", "\0", "\0", "\0", 0),
+    ("errno\0", "\0", "\0", "\0", 0),
+    ("power_denied\0", "\0", "\0", "\0", 0),
+    ("stack_growth\0", "\0", "\0", "\0", 0),
+    ("list_apps\0", "\0", "\0", "\0", 0),
+    ("lookup_app\0", "\0", "\0", "\0", 0),
+    ("writev\0", "\0", "\0", "\0", 0),
+    ("sched_swap\0", "\0", "\0", "\0", 0),
+    ("sysinfo\0", "\0", "\0", "\0", 0),
+    ("prlimit\0", "\0", "\0", "\0", 0),
+    ("trap_stats\0", "\0", "\0", "\0", 0),
+    ("madvise\0", "\0", "\0", "\0", 0),
+    ("mpsc_condvar\0", "\0", "\0", "\0", 0),
+    ("mmap_data\0", "\0", "\0", "\0", 0),
+    ("deadline_miss\0", "\0", "\0", "\0", 0),
+    ("deadline_fair\0", "\0", "\0", "\0", 0),
+    ("getcpu\0", "\0", "\0", "\0", 0),
+    ("zero_len\0", "\0", "\0", "\0", 0),
+    ("checkpoint\0", "\0", "\0", "\0", 0),
+    ("timebase\0", "\0", "\0", "\0", 0),
+    ("line_buffered\0", "\0", "\0", "\0", 0),
+    ("exit_frames\0", "\0", "\0", "\0", 0),
+    ("yield_off_cpu\0", "\0", "\0", "\0", 0),
+    ("clock_getres\0", "\0", "\0", "\0", 0),
+    ("fair_share\0", "\0", "\0", "\0", 0),
+    ("dev_null\0", "\0", "\0", "\0", 0),
+    ("profile\0", "\0", "\0", "\0", 0),
+    ("preempt_off\0", "\0", "\0", "\0", 0),
+    ("membench\0", "\0", "\0", "\0", 0),
+    ("null_deref\0", "\0", "\0", "\0", 0),
+    ("strace\0", "\0", "\0", "\0", 0),
+    ("thread_counter\0", "\0", "\0", "\0", 0),
+    ("thread_exit\0", "\0", "\0", "\0", 0),
+    ("thread_ids\0", "\0", "\0", "\0", 0),
+    ("futex_handoff\0", "\0", "\0", "\0", 0),
+    ("futex_exit\0", "\0", "\0", "\0", 0),
+    ("working_set\0", "\0", "\0", "\0", 0),
+    ("sched_policy\0", "\0", "\0", "\0", 0),
+    ("mmap_oom\0", "\0", "\0", "\0", 0),
+    ("reserved_frames\0", "\0", "\0", "\0", 0),
 ];
 
 static FAIL_TESTS: &[(&str, &str, &str, &str, i32)] = &[
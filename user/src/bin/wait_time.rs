@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, getrusage, sleep, task_info, RUsage, TaskInfo};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = RUsage::default();
+    assert_eq!(getrusage(&mut before), 0);
+    sleep(50);
+    let begin = get_time();
+    while get_time() - begin < 20 {}
+    let mut after = RUsage::default();
+    assert_eq!(getrusage(&mut after), 0);
+    println!("{:?}", after);
+    let blocked = after.blocked_time_us - before.blocked_time_us;
+    let ready_wait = after.ready_wait_us - before.ready_wait_us;
+    // sleep wakes up on the first timer tick after its deadline
+    assert!((45_000..100_000).contains(&blocked));
+    // nothing else keeps the CPU busy
+    assert!(ready_wait < 20_000);
+    let mut info = TaskInfo::default();
+    assert_eq!(task_info(&mut info), 0);
+    assert!(info.blocked_time_us >= after.blocked_time_us);
+    println!("wait_time passed!");
+    0
+}
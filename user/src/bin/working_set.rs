@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, get_time, getrusage, profile_start, profile_stop, thread_create, waittid, ProfileBucket,
+    RUsage,
+};
+
+const PAGE_SIZE: usize = 0x1000;
+const TOTAL_PAGES: usize = 64;
+const TOUCHED_PAGES: usize = 16;
+/// Code, stack and data pages touched besides the buffer.
+const SLACK_PAGES: usize = 8;
+const RUN_MS: isize = 300;
+
+static mut BUFFER: [u8; TOTAL_PAGES * PAGE_SIZE] = [0; TOTAL_PAGES * PAGE_SIZE];
+
+fn working_set_pages() -> usize {
+    let mut usage = RUsage::default();
+    assert_eq!(getrusage(&mut usage), 0);
+    usage.working_set_pages
+}
+
+fn report_working_set() -> ! {
+    exit(working_set_pages() as i32)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // fault in the whole buffer, only part of it stays in use
+    for page in 0..TOTAL_PAGES {
+        unsafe { (&mut BUFFER[page * PAGE_SIZE] as *mut u8).write_volatile(1) };
+    }
+    assert_eq!(working_set_pages(), 0);
+
+    assert_eq!(profile_start(0), 0);
+    let start = get_time();
+    while get_time() - start < RUN_MS {
+        for page in 0..TOUCHED_PAGES {
+            let byte = unsafe { &mut BUFFER[page * PAGE_SIZE] as *mut u8 };
+            unsafe { byte.write_volatile(byte.read_volatile().wrapping_add(1)) };
+        }
+    }
+    // stop first, so no sample lands between the two reads below
+    let mut buf = [ProfileBucket::default(); 1];
+    profile_stop(&mut buf);
+    let estimate = working_set_pages();
+    println!(
+        "touched {} of {} pages, working set estimate {} pages",
+        TOUCHED_PAGES, TOTAL_PAGES, estimate
+    );
+    assert!(estimate >= TOUCHED_PAGES);
+    assert!(estimate <= TOUCHED_PAGES + SLACK_PAGES);
+    // the estimate belongs to the address space, not the profiling thread
+    let tid = thread_create(report_working_set as usize, 0);
+    assert!(tid > 0);
+    assert_eq!(waittid(tid as usize), estimate as isize);
+    println!("working_set passed!");
+    0
+}
@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, pipe, read, writev, IoVec, KernelError};
+
+const STDOUT: usize = 1;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let parts: [&[u8]; 3] = [b"three ", b"joined ", b"segments\n"];
+    let iov = parts.map(IoVec::new);
+    assert_eq!(writev(STDOUT, &iov), 22);
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(writev(pipe_fd[1], &iov), 22);
+    let mut buf = [0u8; 22];
+    assert_eq!(read(pipe_fd[0], &mut buf), 22);
+    assert_eq!(&buf, b"three joined segments\n");
+    // an invalid segment fails the whole call before anything is written
+    let bad = [IoVec::new(b"never written"), IoVec { base: 0, len: 8 }];
+    assert_eq!(writev(pipe_fd[1], &bad), KernelError::Fault as isize);
+    assert_eq!(writev(pipe_fd[0], &iov), KernelError::BadFd as isize);
+    assert_eq!(writev(pipe_fd[1], &[]), 0);
+    assert_eq!(writev(pipe_fd[1], &iov[..1]), 6);
+    assert_eq!(read(pipe_fd[0], &mut buf[..6]), 6);
+    assert_eq!(&buf[..6], b"three ");
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+    println!("writev passed!");
+    0
+}
@@ -0,0 +1,73 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use user_lib::{
+    exit, semaphore_create, semaphore_down, semaphore_up, thread_create, waittid, yield_,
+    yield_deadline,
+};
+
+const ROUNDS: usize = 10;
+
+static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+static POS: AtomicUsize = AtomicUsize::new(0);
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: AtomicU8 = AtomicU8::new(0);
+static ORDER: [AtomicU8; 2 * ROUNDS] = [EMPTY; 2 * ROUNDS];
+
+struct Worker {
+    name: u8,
+    deadline_ms: usize,
+    sem_id: usize,
+}
+
+fn worker(arg: *const Worker) -> ! {
+    let arg = unsafe { &*arg };
+    for _ in 0..ROUNDS {
+        yield_deadline(arg.deadline_ms);
+        ARRIVED.fetch_add(1, Ordering::SeqCst);
+        semaphore_down(arg.sem_id);
+        let pos = POS.fetch_add(1, Ordering::SeqCst);
+        ORDER[pos].store(arg.name, Ordering::SeqCst);
+    }
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let late = Worker {
+        name: b'l',
+        deadline_ms: 50,
+        sem_id: semaphore_create(0) as usize,
+    };
+    let early = Worker {
+        name: b'e',
+        deadline_ms: 10,
+        sem_id: semaphore_create(0) as usize,
+    };
+    let tids = [
+        thread_create(worker as usize, &late as *const _ as usize),
+        thread_create(worker as usize, &early as *const _ as usize),
+    ];
+    for round in 0..ROUNDS {
+        // Both workers hold a deadline and wait on their semaphore, so once
+        // released they are ordered only by deadline.
+        while ARRIVED.load(Ordering::SeqCst) < 2 * (round + 1) {
+            yield_();
+        }
+        semaphore_up(early.sem_id);
+        semaphore_up(late.sem_id);
+    }
+    for tid in tids {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    for round in 0..ROUNDS {
+        assert_eq!(ORDER[2 * round].load(Ordering::SeqCst), b'e');
+        assert_eq!(ORDER[2 * round + 1].load(Ordering::SeqCst), b'l');
+    }
+    println!("yield_deadline passed!");
+    0
+}
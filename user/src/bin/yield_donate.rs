@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use user_lib::{
+    exit, get_time, gettid, set_timeslice, task_info, thread_create, waittid, yield_, yield_donate,
+    TaskInfo,
+};
+
+const TIMESLICE_US: usize = 10_000;
+/// `get_time` only has millisecond resolution.
+const SLACK_US: usize = 2_000;
+
+static GO: AtomicBool = AtomicBool::new(false);
+static SLICE_MS: AtomicUsize = AtomicUsize::new(0);
+
+fn recipient() -> ! {
+    while !GO.load(Ordering::SeqCst) {
+        yield_();
+    }
+    // Running right after the donation: measure until the timer preempts us.
+    let mut info = TaskInfo::default();
+    task_info(&mut info);
+    let preempted = info.involuntary_switches;
+    let start = get_time();
+    let mut last = start;
+    loop {
+        let now = get_time();
+        task_info(&mut info);
+        if info.involuntary_switches != preempted {
+            break;
+        }
+        last = now;
+    }
+    SLICE_MS.store((last - start) as usize, Ordering::SeqCst);
+    exit(0)
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_timeslice(TIMESLICE_US), TIMESLICE_US as isize);
+    let tid = thread_create(recipient as usize, 0) as usize;
+    assert_eq!(yield_donate(gettid() as usize), -1);
+    assert_eq!(yield_donate(tid + 100), -1);
+    // Start from a fresh slice so that most of it is left to donate.
+    yield_();
+    GO.store(true, Ordering::SeqCst);
+    let donated_us = yield_donate(tid);
+    assert!(donated_us >= 0);
+    let donated_us = donated_us as usize;
+    assert_eq!(waittid(tid), 0);
+    let slice_us = SLICE_MS.load(Ordering::SeqCst) * 1000;
+    println!(
+        "donated {}us, recipient ran {}us before preemption",
+        donated_us, slice_us
+    );
+    assert!(donated_us >= TIMESLICE_US / 2);
+    assert!(slice_us + SLACK_US >= TIMESLICE_US + donated_us);
+    println!("yield_donate passed!");
+    0
+}
@@ -0,0 +1,76 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time, mmap, munmap, thread_create, waitpid, waittid, yield_off_cpu,
+    KernelError, MmapProt,
+};
+
+const YIELDS: usize = 10;
+const SPINNERS: usize = 3;
+const SPIN_MS: isize = 300;
+const PAGE_SIZE: usize = 0x1000;
+
+/// Total off-CPU time over `YIELDS` yields.
+fn off_cpu_total() -> usize {
+    let mut total = 0;
+    for _ in 0..YIELDS {
+        let mut off_cpu_us = 0;
+        assert_eq!(yield_off_cpu(&mut off_cpu_us), 0);
+        total += off_cpu_us;
+    }
+    total
+}
+
+fn unmapper(page: usize) -> ! {
+    assert_eq!(munmap(page, PAGE_SIZE), 0);
+    exit(0)
+}
+
+/// The result buffer is unmapped by another thread while this one is off
+/// the CPU, so storing the result fails.
+fn unmapped_while_off_cpu() {
+    let page = mmap(
+        0,
+        PAGE_SIZE,
+        MmapProt::READ | MmapProt::WRITE | MmapProt::POPULATE,
+    );
+    assert!(page > 0);
+    let tid = thread_create(unmapper as usize, page as usize);
+    let off_cpu_us = unsafe { &mut *(page as *mut usize) };
+    assert_eq!(yield_off_cpu(off_cpu_us), KernelError::Fault as isize);
+    assert_eq!(waittid(tid as usize), 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // the parent and initproc are blocked in waitpid, nothing else runs
+    let alone = off_cpu_total();
+    let mut pids = [0; SPINNERS];
+    for pid in pids.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            let start = get_time();
+            while get_time() - start < SPIN_MS {}
+            exit(0);
+        }
+    }
+    // every yield now waits for the spinners' time slices
+    let busy = off_cpu_total();
+    println!(
+        "off cpu over {} yields: {}us alone, {}us with {} spinners",
+        YIELDS, alone, busy, SPINNERS
+    );
+    assert!(alone > 0);
+    assert!(busy > alone);
+    for pid in pids {
+        let mut exit_code = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    }
+    unmapped_while_off_cpu();
+    println!("yield_off_cpu passed!");
+    0
+}
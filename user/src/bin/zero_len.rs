@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, pipe, read, write, KernelError};
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut fds = [0usize; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    // the pipe is empty with its write end open: a read of 1 byte would
+    // wait, a read of 0 bytes returns at once
+    assert_eq!(read(fds[0], &mut []), 0);
+    assert_eq!(write(fds[1], &[]), 0);
+    // and consumes nothing
+    assert_eq!(write(fds[1], b"x"), 1);
+    assert_eq!(read(fds[0], &mut []), 0);
+    let mut byte = [0u8; 1];
+    assert_eq!(read(fds[0], &mut byte), 1);
+    assert_eq!(&byte, b"x");
+    assert_eq!(write(1, &[]), 0);
+    // the fd is still checked
+    assert_eq!(
+        KernelError::from_code(read(fds[1], &mut [])),
+        Some(KernelError::BadFd)
+    );
+    assert_eq!(
+        KernelError::from_code(write(99, &[])),
+        Some(KernelError::BadFd)
+    );
+    close(fds[0]);
+    close(fds[1]);
+    println!("zero_len passed!");
+    0
+}
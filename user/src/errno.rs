@@ -0,0 +1,31 @@
+/// Why a syscall failed, decoded from its negative return value. Mirrors the
+/// kernel's `KernelError`; calls not converted there still return -1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(isize)]
+pub enum KernelError {
+    /// the fd is not open, or not open for this kind of access
+    BadFd = -9,
+    /// the call would block, try again
+    Again = -11,
+    /// no memory left, or the range is not (entirely) mapped
+    NoMem = -12,
+    /// a user buffer is not mapped with the needed permission
+    Fault = -14,
+    /// an argument is malformed
+    Invalid = -22,
+}
+
+impl KernelError {
+    /// The error a syscall return value stands for. `None` for success and
+    /// for codes without a variant, like a plain -1.
+    pub fn from_code(ret: isize) -> Option<Self> {
+        match ret {
+            -9 => Some(Self::BadFd),
+            -11 => Some(Self::Again),
+            -12 => Some(Self::NoMem),
+            -14 => Some(Self::Fault),
+            -22 => Some(Self::Invalid),
+            _ => None,
+        }
+    }
+}
@@ -13,12 +13,19 @@ bitflags! {
 pub fn dup(fd: usize) -> isize {
     sys_dup(fd)
 }
+/// Opening this path gives an fd that discards writes and reads as empty.
+pub const DEV_NULL: &str = "/dev/null\0";
+
 pub fn open(path: &str, flags: OpenFlags) -> isize {
     sys_open(path, flags.bits)
 }
 pub fn close(fd: usize) -> isize {
     sys_close(fd)
 }
+/// Ask the kernel to flush all buffered writes.
+pub fn sync() -> isize {
+    sys_sync()
+}
 pub fn pipe(pipe_fd: &mut [usize]) -> isize {
     sys_pipe(pipe_fd)
 }
@@ -28,3 +35,52 @@ pub fn read(fd: usize, buf: &mut [u8]) -> isize {
 pub fn write(fd: usize, buf: &[u8]) -> isize {
     sys_write(fd, buf)
 }
+
+/// One buffer of a `writev` call.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct IoVec {
+    pub base: usize,
+    pub len: usize,
+}
+
+impl IoVec {
+    pub fn new(buf: &[u8]) -> Self {
+        Self {
+            base: buf.as_ptr() as usize,
+            len: buf.len(),
+        }
+    }
+}
+
+/// Write the buffers in `iov` to `fd` in order, in one syscall. Nothing is
+/// written if any buffer is invalid. Returns the total bytes written.
+pub fn writev(fd: usize, iov: &[IoVec]) -> isize {
+    sys_writev(fd, iov)
+}
+/// Have each line this thread prints to stdout start with `[prefix] `, up to
+/// 16 bytes. An empty prefix turns it off.
+pub fn set_output_prefix(prefix: &str) -> isize {
+    sys_set_output_prefix(prefix)
+}
+
+/// Stdout output is printed as soon as it is written, the default.
+pub const STDOUT_UNBUFFERED: usize = 0;
+/// Stdout output is held back until a line is complete or the thread exits.
+pub const STDOUT_LINE_BUFFERED: usize = 1;
+
+/// Switch this thread's stdout to one of the `STDOUT_*` modes. Returns the
+/// previous mode, or -22 if `mode` is unknown.
+pub fn set_stdout_mode(mode: usize) -> isize {
+    sys_set_stdout_mode(mode)
+}
+/// Write the names of the loadable apps into `buf`, one per line. Returns the
+/// bytes written, the list is cut off when `buf` is full.
+pub fn list_apps(buf: &mut [u8]) -> isize {
+    sys_list_apps(buf)
+}
+/// Id of the app `name` (ending in '\0'), its position in the `list_apps`
+/// list, or -1 if there is no such app.
+pub fn lookup_app(name: &str) -> isize {
+    sys_lookup_app(name)
+}
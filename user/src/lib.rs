@@ -6,10 +6,13 @@
 
 #[macro_use]
 pub mod console;
+mod errno;
 mod file;
 mod io;
 mod lang_items;
+mod mm;
 mod net;
+mod shell;
 mod sync;
 mod syscall;
 mod task;
@@ -20,9 +23,12 @@ extern crate bitflags;
 
 use alloc::vec::Vec;
 use buddy_system_allocator::LockedHeap;
+pub use errno::*;
 pub use file::*;
 pub use io::*;
+pub use mm::*;
 pub use net::*;
+pub use shell::*;
 pub use sync::*;
 use syscall::*;
 pub use task::*;
@@ -60,7 +66,7 @@ pub extern "C" fn _start(argc: usize, argv: usize) -> ! {
             .unwrap(),
         );
     }
-    exit(main(argc, v.as_slice()));
+    exit_process(main(argc, v.as_slice()));
 }
 
 #[linkage = "weak"]
@@ -0,0 +1,98 @@
+use super::*;
+
+bitflags! {
+    pub struct MmapProt: usize {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXEC = 1 << 2;
+        /// Not a protection: back every page with a frame right away
+        /// instead of on first touch.
+        const POPULATE = 1 << 3;
+    }
+}
+
+/// Kinds of `fence`: a full memory fence, `fence.i` and `sfence.vma`.
+pub const FENCE_RW: usize = 0;
+pub const FENCE_I: usize = 1;
+pub const FENCE_VMA: usize = 2;
+
+/// Advices for `madvise`.
+pub const MADV_NORMAL: usize = 0;
+pub const MADV_RANDOM: usize = 1;
+pub const MADV_SEQUENTIAL: usize = 2;
+pub const MADV_WILLNEED: usize = 3;
+pub const MADV_DONTNEED: usize = 4;
+
+/// `prlimit` resource: pages `mmap` may have added at a time.
+pub const RLIMIT_MMAP_PAGES: usize = 0;
+/// No limit.
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// Physical frame usage reported by `meminfo`.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct MemInfo {
+    pub frames_total: usize,
+    pub frames_used: usize,
+    pub frames_free: usize,
+    /// Free frames the kernel keeps for itself: user memory (`mmap`, first
+    /// touches of lazy pages, `fork`, `exec` and thread stacks) fails with
+    /// `KernelError::NoMem` rather than take them.
+    pub frames_reserved: usize,
+}
+
+/// `mmap`, `munmap`, `mprotect` and `madvise` only work on
+/// `[MMAP_BASE, MMAP_TOP)`, below are the app image and the thread stacks.
+pub const MMAP_BASE: usize = 0x10_0000_0000;
+pub const MMAP_TOP: usize = 0x20_0000_0000;
+
+/// Map `len` bytes at `start`, or wherever the kernel finds room if `start`
+/// is 0. Returns the mapped address, `KernelError::Invalid` if `start` is
+/// outside `[MMAP_BASE, MMAP_TOP)`, or `KernelError::NoMem` if there is no
+/// room. Pages get frames when first touched unless `MmapProt::POPULATE` is
+/// given.
+pub fn mmap(start: usize, len: usize, prot: MmapProt) -> isize {
+    sys_mmap(start, len, prot.bits)
+}
+/// Map a private copy of `len` bytes of this app's ELF file, starting at the
+/// page aligned `offset`, wherever the kernel finds room. Returns the mapped
+/// address, or `KernelError::Invalid` if the range is not inside the file.
+pub fn mmap_data(offset: usize, len: usize, prot: MmapProt) -> isize {
+    sys_mmap_data(offset, len, prot.bits)
+}
+pub fn munmap(start: usize, len: usize) -> isize {
+    sys_munmap(start, len)
+}
+/// Change the protection of an already mapped range. Returns
+/// `KernelError::NoMem` if any page in it is unmapped, `KernelError::Invalid`
+/// if `prot` is not a valid protection.
+pub fn mprotect(start: usize, len: usize, prot: MmapProt) -> isize {
+    sys_mprotect(start, len, prot.bits)
+}
+/// Tell the kernel how a mapped range will be used. With `MADV_DONTNEED` its
+/// frames are freed and it reads as zeroes afterwards, the other advices
+/// are ignored.
+pub fn madvise(start: usize, len: usize, advice: usize) -> isize {
+    sys_madvise(start, len, advice)
+}
+/// Set the limit on `resource` to `new_limit` and store the previous one in
+/// `old_limit`. Once the limit is reached `mmap` fails with
+/// `KernelError::NoMem`. Limits are inherited by fork and spawn.
+pub fn prlimit(resource: usize, new_limit: usize, old_limit: Option<&mut usize>) -> isize {
+    let old_limit = old_limit.map_or(core::ptr::null_mut(), |old| old as *mut usize);
+    sys_prlimit(resource, new_limit, old_limit)
+}
+pub fn meminfo(info: &mut MemInfo) -> isize {
+    sys_meminfo(info)
+}
+/// Have the kernel execute the fence selected by `kind`. Returns -1 for an
+/// unknown kind.
+pub fn fence(kind: usize) -> isize {
+    sys_fence(kind)
+}
+/// Write a listing of the current page table into `buf`, one
+/// `vpn -> ppn VRWXUGAD` line per mapped page. Returns the bytes written,
+/// the listing is cut off when `buf` is full.
+pub fn dump_pagetable(buf: &mut [u8]) -> isize {
+    sys_dump_pagetable(buf)
+}
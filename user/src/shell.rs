@@ -0,0 +1,87 @@
+//! Command line parsing for `user_shell`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// One command of a pipeline: its NUL-terminated arguments and optional
+/// `<` / `>` redirections.
+#[derive(Debug)]
+pub struct ProcessArguments {
+    pub input: String,
+    pub output: String,
+    pub args_copy: Vec<String>,
+    pub args_addr: Vec<*const u8>,
+}
+
+impl ProcessArguments {
+    /// Parse a single command. Returns `None` if it has no program name or a
+    /// redirection lacks its file name.
+    pub fn new(command: &str) -> Option<Self> {
+        let args: Vec<_> = command.split(' ').collect();
+        let mut args_copy: Vec<String> = args
+            .iter()
+            .filter(|&arg| !arg.is_empty())
+            .map(|&arg| {
+                let mut string = String::new();
+                string.push_str(arg);
+                string.push('\0');
+                string
+            })
+            .collect();
+
+        // redirect input
+        let mut input = String::new();
+        if let Some((idx, _)) = args_copy
+            .iter()
+            .enumerate()
+            .find(|(_, arg)| arg.as_str() == "<\0")
+        {
+            input = args_copy.get(idx + 1)?.clone();
+            args_copy.drain(idx..=idx + 1);
+        }
+
+        // redirect output
+        let mut output = String::new();
+        if let Some((idx, _)) = args_copy
+            .iter()
+            .enumerate()
+            .find(|(_, arg)| arg.as_str() == ">\0")
+        {
+            output = args_copy.get(idx + 1)?.clone();
+            args_copy.drain(idx..=idx + 1);
+        }
+
+        if args_copy.is_empty() {
+            return None;
+        }
+        let mut args_addr: Vec<*const u8> = args_copy.iter().map(|arg| arg.as_ptr()).collect();
+        args_addr.push(core::ptr::null::<u8>());
+
+        Some(Self {
+            input,
+            output,
+            args_copy,
+            args_addr,
+        })
+    }
+}
+
+/// Split a command line into `|`-separated commands. A blank line gives an
+/// empty pipeline. Returns `None` if a command is malformed or an input or
+/// output is redirected anywhere but at the matching end of the pipeline.
+pub fn parse_pipeline(line: &str) -> Option<Vec<ProcessArguments>> {
+    if line.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    let commands = line
+        .split('|')
+        .map(ProcessArguments::new)
+        .collect::<Option<Vec<_>>>()?;
+    let last = commands.len() - 1;
+    for (i, command) in commands.iter().enumerate() {
+        if (i != 0 && !command.input.is_empty()) || (i != last && !command.output.is_empty()) {
+            return None;
+        }
+    }
+    Some(commands)
+}
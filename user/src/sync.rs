@@ -1,4 +1,5 @@
 use super::*;
+use core::sync::atomic::AtomicU32;
 
 pub fn mutex_create() -> isize {
     sys_mutex_create(false)
@@ -30,3 +31,15 @@ pub fn condvar_signal(condvar_id: usize) {
 pub fn condvar_wait(condvar_id: usize, mutex_id: usize) {
     sys_condvar_wait(condvar_id, mutex_id);
 }
+
+/// Sleep until woken by `futex_wake` on `word`, unless it no longer holds
+/// `expected`, in which case `KernelError::Again` is returned at once.
+pub fn futex_wait(word: &AtomicU32, expected: u32) -> isize {
+    sys_futex_wait(word as *const AtomicU32 as *const u32, expected)
+}
+
+/// Wake up to `n` threads sleeping in `futex_wait` on `word`. Returns how
+/// many were woken.
+pub fn futex_wake(word: &AtomicU32, n: usize) -> isize {
+    sys_futex_wake(word as *const AtomicU32 as *const u32, n)
+}
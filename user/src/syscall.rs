@@ -1,3 +1,7 @@
+use super::{
+    IoVec, MemInfo, ProfileBucket, RUsage, SysInfo, SyscallLatency, TaskInfo, TimeVal, Tms,
+};
+
 const SYSCALL_DUP: usize = 24;
 const SYSCALL_CONNECT: usize = 29;
 const SYSCALL_LISTEN: usize = 30;
@@ -7,14 +11,28 @@ const SYSCALL_CLOSE: usize = 57;
 const SYSCALL_PIPE: usize = 59;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_WRITEV: usize = 66;
+const SYSCALL_SYNC: usize = 81;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_EXIT_GROUP: usize = 94;
 const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_SETALARM: usize = 103;
+const SYSCALL_CLOCK_GETRES: usize = 114;
+const SYSCALL_SLEEP_UNTIL: usize = 115;
 const SYSCALL_YIELD: usize = 124;
 const SYSCALL_KILL: usize = 129;
+const SYSCALL_SIGRETURN: usize = 139;
+const SYSCALL_TIMES: usize = 153;
+const SYSCALL_GETRUSAGE: usize = 165;
 const SYSCALL_GET_TIME: usize = 169;
 const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
+const SYSCALL_MUNMAP: usize = 215;
 const SYSCALL_FORK: usize = 220;
 const SYSCALL_EXEC: usize = 221;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_MPROTECT: usize = 226;
+const SYSCALL_MADVISE: usize = 233;
 const SYSCALL_WAITPID: usize = 260;
 const SYSCALL_THREAD_CREATE: usize = 1000;
 const SYSCALL_GETTID: usize = 1001;
@@ -28,10 +46,57 @@ const SYSCALL_SEMAPHORE_DOWN: usize = 1022;
 const SYSCALL_CONDVAR_CREATE: usize = 1030;
 const SYSCALL_CONDVAR_SIGNAL: usize = 1031;
 const SYSCALL_CONDVAR_WAIT: usize = 1032;
+const SYSCALL_FUTEX_WAIT: usize = 1040;
+const SYSCALL_FUTEX_WAKE: usize = 1041;
 const SYSCALL_FRAMEBUFFER: usize = 2000;
 const SYSCALL_FRAMEBUFFER_FLUSH: usize = 2001;
 const SYSCALL_EVENT_GET: usize = 3000;
 const SYSCALL_KEY_PRESSED: usize = 3001;
+const SYSCALL_TASK_INFO: usize = 4000;
+const SYSCALL_SWITCH_COUNT: usize = 4001;
+const SYSCALL_SET_LOG_LEVEL: usize = 4002;
+const SYSCALL_CURRENT_APP_INFO: usize = 4003;
+const SYSCALL_MEMINFO: usize = 4004;
+const SYSCALL_SET_TIMESLICE: usize = 4005;
+const SYSCALL_TASKS_CREATED: usize = 4006;
+const SYSCALL_SYSCALL_LATENCY: usize = 4007;
+const SYSCALL_SCHED_TRACE: usize = 4008;
+const SYSCALL_FENCE: usize = 4009;
+const SYSCALL_EXIT_REASON: usize = 4010;
+const SYSCALL_DMESG: usize = 4011;
+const SYSCALL_SET_OUTPUT_PREFIX: usize = 4012;
+const SYSCALL_YIELD_DEADLINE: usize = 4013;
+const SYSCALL_RESET_BUFFER: usize = 4014;
+const SYSCALL_FAULT_INJECT: usize = 4015;
+const SYSCALL_PAUSE: usize = 4016;
+const SYSCALL_YIELD_DONATE: usize = 4017;
+const SYSCALL_SPAWN: usize = 4018;
+const SYSCALL_SETENV: usize = 4019;
+const SYSCALL_GETENV: usize = 4020;
+const SYSCALL_DUMP_PAGETABLE: usize = 4021;
+const SYSCALL_POWEROFF: usize = 4022;
+const SYSCALL_REBOOT: usize = 4023;
+const SYSCALL_LIST_APPS: usize = 4024;
+const SYSCALL_SET_SCHEDULER: usize = 4025;
+const SYSCALL_SYSINFO: usize = 4026;
+const SYSCALL_PRLIMIT: usize = 4027;
+const SYSCALL_TRAP_STATS: usize = 4028;
+const SYSCALL_MMAP_DATA: usize = 4029;
+const SYSCALL_GETCPU: usize = 4030;
+const SYSCALL_CHECKPOINT: usize = 4031;
+const SYSCALL_RESTORE: usize = 4032;
+const SYSCALL_SET_STDOUT_MODE: usize = 4033;
+const SYSCALL_PROFILE_START: usize = 4034;
+const SYSCALL_PROFILE_STOP: usize = 4035;
+const SYSCALL_PREEMPT_DISABLE: usize = 4036;
+const SYSCALL_PREEMPT_ENABLE: usize = 4037;
+const SYSCALL_MEMBENCH: usize = 4038;
+const SYSCALL_TRACE_SYSCALLS: usize = 4039;
+const SYSCALL_SCHED_SETSCHEDULER: usize = 4040;
+const SYSCALL_LOOKUP_APP: usize = 4041;
+const SYSCALL_EXIT_PROCESS: usize = 4042;
+const SYSCALL_DROP_PRIVILEGE: usize = 4043;
+const SYSCALL_STDOUT_LOG: usize = 4044;
 
 fn syscall(id: usize, args: [usize; 3]) -> isize {
     let mut ret: isize;
@@ -75,6 +140,10 @@ pub fn sys_close(fd: usize) -> isize {
     syscall(SYSCALL_CLOSE, [fd, 0, 0])
 }
 
+pub fn sys_sync() -> isize {
+    syscall(SYSCALL_SYNC, [0, 0, 0])
+}
+
 pub fn sys_pipe(pipe: &mut [usize]) -> isize {
     syscall(SYSCALL_PIPE, [pipe.as_mut_ptr() as usize, 0, 0])
 }
@@ -90,31 +159,73 @@ pub fn sys_write(fd: usize, buffer: &[u8]) -> isize {
     syscall(SYSCALL_WRITE, [fd, buffer.as_ptr() as usize, buffer.len()])
 }
 
+pub fn sys_writev(fd: usize, iov: &[IoVec]) -> isize {
+    syscall(SYSCALL_WRITEV, [fd, iov.as_ptr() as usize, iov.len()])
+}
+
 pub fn sys_exit(exit_code: i32) -> ! {
     syscall(SYSCALL_EXIT, [exit_code as usize, 0, 0]);
     panic!("sys_exit never returns!");
 }
 
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    syscall(SYSCALL_EXIT_GROUP, [exit_code as usize, 0, 0]);
+    panic!("sys_exit_group never returns!");
+}
+
+pub fn sys_exit_process(exit_code: i32) -> ! {
+    syscall(SYSCALL_EXIT_PROCESS, [exit_code as usize, 0, 0]);
+    panic!("sys_exit_process never returns!");
+}
+
 pub fn sys_sleep(sleep_ms: usize) -> isize {
     syscall(SYSCALL_SLEEP, [sleep_ms, 0, 0])
 }
 
-pub fn sys_yield() -> isize {
-    syscall(SYSCALL_YIELD, [0, 0, 0])
+pub fn sys_sleep_until(tv: &TimeVal) -> isize {
+    syscall(SYSCALL_SLEEP_UNTIL, [tv as *const _ as usize, 0, 0])
+}
+
+pub fn sys_yield(off_cpu_us: *mut usize) -> isize {
+    syscall(SYSCALL_YIELD, [off_cpu_us as usize, 0, 0])
 }
 
 pub fn sys_kill(pid: usize, signal: i32) -> isize {
     syscall(SYSCALL_KILL, [pid, signal as usize, 0])
 }
 
+pub fn sys_setalarm(ms: usize, handler: usize) -> isize {
+    syscall(SYSCALL_SETALARM, [ms, handler, 0])
+}
+
+pub fn sys_sigreturn() -> isize {
+    syscall(SYSCALL_SIGRETURN, [0, 0, 0])
+}
+
+pub fn sys_getrusage(usage: &mut RUsage) -> isize {
+    syscall(SYSCALL_GETRUSAGE, [usage as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_times(buf: &mut Tms) -> isize {
+    syscall(SYSCALL_TIMES, [buf as *mut _ as usize, 0, 0])
+}
+
 pub fn sys_get_time() -> isize {
     syscall(SYSCALL_GET_TIME, [0, 0, 0])
 }
 
+pub fn sys_clock_getres(res: &mut usize) -> isize {
+    syscall(SYSCALL_CLOCK_GETRES, [res as *mut _ as usize, 0, 0])
+}
+
 pub fn sys_getpid() -> isize {
     syscall(SYSCALL_GETPID, [0, 0, 0])
 }
 
+pub fn sys_getppid() -> isize {
+    syscall(SYSCALL_GETPPID, [0, 0, 0])
+}
+
 pub fn sys_fork() -> isize {
     syscall(SYSCALL_FORK, [0, 0, 0])
 }
@@ -126,8 +237,24 @@ pub fn sys_exec(path: &str, args: &[*const u8]) -> isize {
     )
 }
 
-pub fn sys_waitpid(pid: isize, exit_code: *mut i32) -> isize {
-    syscall(SYSCALL_WAITPID, [pid as usize, exit_code as usize, 0])
+pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_MMAP, [start, len, prot])
+}
+
+pub fn sys_munmap(start: usize, len: usize) -> isize {
+    syscall(SYSCALL_MUNMAP, [start, len, 0])
+}
+
+pub fn sys_mprotect(start: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_MPROTECT, [start, len, prot])
+}
+
+pub fn sys_madvise(start: usize, len: usize, advice: usize) -> isize {
+    syscall(SYSCALL_MADVISE, [start, len, advice])
+}
+
+pub fn sys_waitpid(pid: isize, exit_code: *mut i32, options: usize) -> isize {
+    syscall(SYSCALL_WAITPID, [pid as usize, exit_code as usize, options])
 }
 
 pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
@@ -178,6 +305,14 @@ pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
     syscall(SYSCALL_CONDVAR_WAIT, [condvar_id, mutex_id, 0])
 }
 
+pub fn sys_futex_wait(addr: *const u32, expected: u32) -> isize {
+    syscall(SYSCALL_FUTEX_WAIT, [addr as usize, expected as usize, 0])
+}
+
+pub fn sys_futex_wake(addr: *const u32, n: usize) -> isize {
+    syscall(SYSCALL_FUTEX_WAKE, [addr as usize, n, 0])
+}
+
 pub fn sys_framebuffer() -> isize {
     syscall(SYSCALL_FRAMEBUFFER, [0, 0, 0])
 }
@@ -193,3 +328,212 @@ pub fn sys_event_get() -> isize {
 pub fn sys_key_pressed() -> isize {
     syscall(SYSCALL_KEY_PRESSED, [0, 0, 0])
 }
+
+pub fn sys_task_info(info: &mut TaskInfo) -> isize {
+    syscall(SYSCALL_TASK_INFO, [info as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_switch_count() -> isize {
+    syscall(SYSCALL_SWITCH_COUNT, [0, 0, 0])
+}
+
+pub fn sys_set_log_level(level: usize) -> isize {
+    syscall(SYSCALL_SET_LOG_LEVEL, [level, 0, 0])
+}
+
+pub fn sys_current_app_info(buffer: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_CURRENT_APP_INFO,
+        [buffer.as_mut_ptr() as usize, buffer.len(), 0],
+    )
+}
+
+pub fn sys_meminfo(info: &mut MemInfo) -> isize {
+    syscall(SYSCALL_MEMINFO, [info as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_set_timeslice(us: usize) -> isize {
+    syscall(SYSCALL_SET_TIMESLICE, [us, 0, 0])
+}
+
+pub fn sys_tasks_created() -> isize {
+    syscall(SYSCALL_TASKS_CREATED, [0, 0, 0])
+}
+
+pub fn sys_syscall_latency(buf: &mut [SyscallLatency]) -> isize {
+    syscall(
+        SYSCALL_SYSCALL_LATENCY,
+        [buf.as_mut_ptr() as usize, buf.len(), 0],
+    )
+}
+
+pub fn sys_sched_trace(buffer: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_SCHED_TRACE,
+        [buffer.as_mut_ptr() as usize, buffer.len(), 0],
+    )
+}
+
+pub fn sys_fence(kind: usize) -> isize {
+    syscall(SYSCALL_FENCE, [kind, 0, 0])
+}
+
+pub fn sys_exit_reason(pid: usize) -> isize {
+    syscall(SYSCALL_EXIT_REASON, [pid, 0, 0])
+}
+
+pub fn sys_dmesg(buffer: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_DMESG,
+        [buffer.as_mut_ptr() as usize, buffer.len(), 0],
+    )
+}
+
+pub fn sys_set_output_prefix(prefix: &str) -> isize {
+    syscall(
+        SYSCALL_SET_OUTPUT_PREFIX,
+        [prefix.as_ptr() as usize, prefix.len(), 0],
+    )
+}
+
+pub fn sys_yield_deadline(ms: usize) -> isize {
+    syscall(SYSCALL_YIELD_DEADLINE, [ms, 0, 0])
+}
+
+pub fn sys_reset_buffer(which: usize) -> isize {
+    syscall(SYSCALL_RESET_BUFFER, [which, 0, 0])
+}
+
+pub fn sys_fault_inject(kind: usize) -> isize {
+    syscall(SYSCALL_FAULT_INJECT, [kind, 0, 0])
+}
+
+pub fn sys_pause() -> isize {
+    syscall(SYSCALL_PAUSE, [0, 0, 0])
+}
+
+pub fn sys_yield_donate(tid: usize) -> isize {
+    syscall(SYSCALL_YIELD_DONATE, [tid, 0, 0])
+}
+
+pub fn sys_spawn(path: &str) -> isize {
+    syscall(SYSCALL_SPAWN, [path.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_setenv(key: &str, value: &str) -> isize {
+    syscall(
+        SYSCALL_SETENV,
+        [key.as_ptr() as usize, value.as_ptr() as usize, 0],
+    )
+}
+
+pub fn sys_getenv(key: &str, buf: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_GETENV,
+        [key.as_ptr() as usize, buf.as_mut_ptr() as usize, buf.len()],
+    )
+}
+
+pub fn sys_dump_pagetable(buf: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_DUMP_PAGETABLE,
+        [buf.as_mut_ptr() as usize, buf.len(), 0],
+    )
+}
+
+pub fn sys_poweroff(code: usize) -> isize {
+    syscall(SYSCALL_POWEROFF, [code, 0, 0])
+}
+
+pub fn sys_reboot() -> isize {
+    syscall(SYSCALL_REBOOT, [0, 0, 0])
+}
+
+pub fn sys_list_apps(buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_LIST_APPS, [buf.as_mut_ptr() as usize, buf.len(), 0])
+}
+
+pub fn sys_lookup_app(name: &str) -> isize {
+    syscall(SYSCALL_LOOKUP_APP, [name.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_set_scheduler(kind: usize) -> isize {
+    syscall(SYSCALL_SET_SCHEDULER, [kind, 0, 0])
+}
+
+pub fn sys_sysinfo(info: &mut SysInfo) -> isize {
+    syscall(SYSCALL_SYSINFO, [info as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_prlimit(resource: usize, new_limit: usize, old_limit: *mut usize) -> isize {
+    syscall(SYSCALL_PRLIMIT, [resource, new_limit, old_limit as usize])
+}
+
+pub fn sys_trap_stats(buf: &mut [usize]) -> isize {
+    syscall(
+        SYSCALL_TRAP_STATS,
+        [buf.as_mut_ptr() as usize, buf.len(), 0],
+    )
+}
+
+pub fn sys_mmap_data(offset: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_MMAP_DATA, [offset, len, prot])
+}
+
+pub fn sys_getcpu() -> isize {
+    syscall(SYSCALL_GETCPU, [0, 0, 0])
+}
+
+pub fn sys_checkpoint() -> isize {
+    syscall(SYSCALL_CHECKPOINT, [0, 0, 0])
+}
+
+pub fn sys_restore() -> isize {
+    syscall(SYSCALL_RESTORE, [0, 0, 0])
+}
+
+pub fn sys_set_stdout_mode(mode: usize) -> isize {
+    syscall(SYSCALL_SET_STDOUT_MODE, [mode, 0, 0])
+}
+
+pub fn sys_profile_start(interval_ms: usize) -> isize {
+    syscall(SYSCALL_PROFILE_START, [interval_ms, 0, 0])
+}
+
+pub fn sys_profile_stop(buf: &mut [ProfileBucket]) -> isize {
+    syscall(
+        SYSCALL_PROFILE_STOP,
+        [buf.as_mut_ptr() as usize, buf.len(), 0],
+    )
+}
+
+pub fn sys_preempt_disable() -> isize {
+    syscall(SYSCALL_PREEMPT_DISABLE, [0, 0, 0])
+}
+
+pub fn sys_preempt_enable() -> isize {
+    syscall(SYSCALL_PREEMPT_ENABLE, [0, 0, 0])
+}
+
+pub fn sys_membench(size: usize) -> isize {
+    syscall(SYSCALL_MEMBENCH, [size, 0, 0])
+}
+
+pub fn sys_trace_syscalls(enable: usize) -> isize {
+    syscall(SYSCALL_TRACE_SYSCALLS, [enable, 0, 0])
+}
+
+pub fn sys_sched_setscheduler(tid: usize, policy: usize, param: usize) -> isize {
+    syscall(SYSCALL_SCHED_SETSCHEDULER, [tid, policy, param])
+}
+
+pub fn sys_drop_privilege() -> isize {
+    syscall(SYSCALL_DROP_PRIVILEGE, [0, 0, 0])
+}
+
+pub fn sys_stdout_log(buffer: &mut [u8]) -> isize {
+    syscall(
+        SYSCALL_STDOUT_LOG,
+        [buffer.as_mut_ptr() as usize, buffer.len(), 0],
+    )
+}
@@ -1,27 +1,138 @@
 use super::*;
 
+/// Exit the calling thread. The last thread to exit ends the process with
+/// its `exit_code`.
 pub fn exit(exit_code: i32) -> ! {
     sys_exit(exit_code);
 }
+/// Exit the process, killing its other threads wherever they are. Returning
+/// from `main` does this.
+pub fn exit_process(exit_code: i32) -> ! {
+    sys_exit_process(exit_code);
+}
+/// Exit together with all descendant processes.
+pub fn exit_group(exit_code: i32) -> ! {
+    sys_exit_group(exit_code);
+}
 pub fn yield_() -> isize {
-    sys_yield()
+    sys_yield(core::ptr::null_mut())
+}
+/// Yield and store in `off_cpu_us` how many microseconds passed until this
+/// thread ran again. Returns `KernelError::Fault` if `off_cpu_us` is no
+/// longer mapped by then.
+pub fn yield_off_cpu(off_cpu_us: &mut usize) -> isize {
+    sys_yield(off_cpu_us)
+}
+/// Yield, then hold a deadline `ms` milliseconds from when this thread runs
+/// again. The next time it waits to run, it goes before ready tasks with later
+/// deadlines and is used up once it runs; `ms == 0` drops it. A deadline that
+/// passes while the thread waits counts in `RUsage::deadline_misses`.
+pub fn yield_deadline(ms: usize) -> isize {
+    sys_yield_deadline(ms)
+}
+/// Yield to ready thread `tid` of this process, which then runs for a full
+/// time slice plus what was left of ours. Returns the donated microseconds.
+pub fn yield_donate(tid: usize) -> isize {
+    sys_yield_donate(tid)
+}
+/// Keep the timer from preempting this thread until `preempt_enable`, for
+/// at most 100 ms in total each time it gets the CPU, however often it is
+/// turned on and off. Yielding or blocking still gives up the CPU.
+pub fn preempt_disable() -> isize {
+    sys_preempt_disable()
+}
+/// Let the timer preempt this thread again. Returns -1 if preemption was not
+/// off, also when the kernel turned it back on after 100 ms.
+pub fn preempt_enable() -> isize {
+    sys_preempt_enable()
 }
 pub fn get_time() -> isize {
     sys_get_time()
 }
+/// Store the resolution of the kernel clock, in nanoseconds, in `res`.
+pub fn clock_getres(res: &mut usize) -> isize {
+    sys_clock_getres(res)
+}
+/// Run `f` and return how long it took in microseconds. `get_time` counts
+/// milliseconds, so the result is a multiple of 1000.
+pub fn time_it<F: FnOnce()>(f: F) -> usize {
+    let start = get_time();
+    f();
+    (get_time() - start) as usize * 1000
+}
+#[track_caller]
+pub fn assert_duration_ge(elapsed_us: usize, min_us: usize) {
+    assert!(
+        elapsed_us >= min_us,
+        "took {}us, expected at least {}us",
+        elapsed_us,
+        min_us
+    );
+}
+#[track_caller]
+pub fn assert_duration_le(elapsed_us: usize, max_us: usize) {
+    assert!(
+        elapsed_us <= max_us,
+        "took {}us, expected at most {}us",
+        elapsed_us,
+        max_us
+    );
+}
 pub fn getpid() -> isize {
     sys_getpid()
 }
+pub fn getppid() -> isize {
+    sys_getppid()
+}
+/// Save this thread's registers, like `setjmp`. Returns 0, and 1 again
+/// every time `restore` jumps back. Only registers are saved: as with
+/// `longjmp`, the stack frame that called `checkpoint` must still be live
+/// when restoring, so keep both in the same function, or in assembly.
+pub fn checkpoint() -> isize {
+    sys_checkpoint()
+}
+/// Reload the registers saved by the last `checkpoint` of this thread.
+/// Returns -1 if there is none, and does not return otherwise.
+pub fn restore() -> isize {
+    sys_restore()
+}
+/// Id of the hart (CPU) this thread is running on.
+pub fn getcpu() -> isize {
+    sys_getcpu()
+}
 pub fn fork() -> isize {
     sys_fork()
 }
 pub fn exec(path: &str, args: &[*const u8]) -> isize {
     sys_exec(path, args)
 }
+/// Start app `path` as a child process without forking. Returns its pid, or
+/// `KernelError::Invalid` if the app image is broken.
+pub fn spawn(path: &str) -> isize {
+    sys_spawn(path)
+}
+/// Power off the machine, as a failure if `code` is not 0. Returns -1 if
+/// this process has dropped its privilege.
+pub fn poweroff(code: usize) -> isize {
+    sys_poweroff(code)
+}
+/// Reset the machine. Returns -1 if this process has dropped its privilege.
+pub fn reboot() -> isize {
+    sys_reboot()
+}
+/// Set environment variable `key` to `value`; both must end with `\0`.
+pub fn setenv(key: &str, value: &str) -> isize {
+    sys_setenv(key, value)
+}
+/// Copy the value of `key` (ending with `\0`) into `buf`, followed by a NUL.
+/// Returns the length of the value, or -1 if `key` is not set.
+pub fn getenv(key: &str, buf: &mut [u8]) -> isize {
+    sys_getenv(key, buf)
+}
 
 pub fn wait(exit_code: &mut i32) -> isize {
     loop {
-        match sys_waitpid(-1, exit_code as *mut _) {
+        match sys_waitpid(-1, exit_code as *mut _, 0) {
             -2 => {
                 yield_();
             }
@@ -33,7 +144,7 @@ pub fn wait(exit_code: &mut i32) -> isize {
 
 pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
     loop {
-        match sys_waitpid(pid as isize, exit_code as *mut _) {
+        match sys_waitpid(pid as isize, exit_code as *mut _, 0) {
             -2 => {
                 yield_();
             }
@@ -43,8 +154,11 @@ pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
     }
 }
 
+/// `sys_waitpid` option: return -2 at once instead of blocking.
+const WNOHANG: usize = 1;
+
 pub fn waitpid_nb(pid: usize, exit_code: &mut i32) -> isize {
-    sys_waitpid(pid as isize, exit_code as *mut _)
+    sys_waitpid(pid as isize, exit_code as *mut _, WNOHANG)
 }
 
 bitflags! {
@@ -53,21 +167,72 @@ bitflags! {
         const SIGILL    = 1 << 4;
         const SIGABRT   = 1 << 6;
         const SIGFPE    = 1 << 8;
+        const SIGKILL   = 1 << 9;
         const SIGSEGV   = 1 << 11;
+        const SIGSYS    = 1 << 31;
     }
 }
 
 pub fn kill(pid: usize, signal: i32) -> isize {
     sys_kill(pid, signal)
 }
+/// Why the exited child `pid` ended, without reaping it: 0 for a normal
+/// exit or the number of the fatal signal. -2 while it is still running.
+pub fn exit_reason(pid: usize) -> isize {
+    sys_exit_reason(pid)
+}
+
+static mut ALARM_HANDLER: Option<fn()> = None;
+
+/// Entered by the kernel when the alarm fires: run the user handler, then
+/// resume the interrupted context.
+fn alarm_trampoline() {
+    if let Some(handler) = unsafe { ALARM_HANDLER } {
+        handler();
+    }
+    sys_sigreturn();
+    unreachable!("sigreturn never returns to the alarm trampoline!");
+}
+
+/// Call `handler` once after `ms` milliseconds. `ms == 0` cancels the alarm.
+pub fn setalarm(ms: usize, handler: fn()) -> isize {
+    unsafe {
+        ALARM_HANDLER = Some(handler);
+    }
+    sys_setalarm(ms, alarm_trampoline as usize)
+}
 
 pub fn sleep(sleep_ms: usize) {
     sys_sleep(sleep_ms);
 }
 
+/// Time since boot, as used by `sleep_until`.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TimeVal {
+    pub sec: usize,
+    pub usec: usize,
+}
+
+impl TimeVal {
+    pub fn from_ms(ms: usize) -> Self {
+        Self {
+            sec: ms / 1000,
+            usec: ms % 1000 * 1000,
+        }
+    }
+}
+
+/// Sleep until the absolute time `tv`; returns at once if it has passed.
+pub fn sleep_until(tv: &TimeVal) -> isize {
+    sys_sleep_until(tv)
+}
+
 pub fn thread_create(entry: usize, arg: usize) -> isize {
     sys_thread_create(entry, arg)
 }
+/// Id of this thread within the process, 0 for the main thread. Unlike
+/// `getpid`, it differs between threads of the same process.
 pub fn gettid() -> isize {
     sys_gettid()
 }
@@ -81,3 +246,270 @@ pub fn waittid(tid: usize) -> isize {
         }
     }
 }
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TaskInfo {
+    pub switches: usize,
+    pub voluntary_switches: usize,
+    pub involuntary_switches: usize,
+    pub user_time_us: usize,
+    pub kernel_time_us: usize,
+    pub blocked_time_us: usize,
+    pub ready_wait_us: usize,
+}
+
+/// Resource usage of the calling thread, filled in by `getrusage`.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RUsage {
+    pub user_time_us: usize,
+    pub kernel_time_us: usize,
+    pub voluntary_switches: usize,
+    pub involuntary_switches: usize,
+    pub page_faults: usize,
+    /// peak number of frames backing the process's user memory
+    pub max_rss_pages: usize,
+    /// time spent sleeping or waiting for a lock or a device
+    pub blocked_time_us: usize,
+    /// time spent ready but waiting for the CPU
+    pub ready_wait_us: usize,
+    /// deadlines set by `yield_deadline` that passed before the thread got
+    /// the CPU back
+    pub deadline_misses: usize,
+    /// user pages of the process touched in the last window, estimated only
+    /// while some thread is between `profile_start` and `profile_stop`; the
+    /// same for all threads
+    pub working_set_pages: usize,
+}
+
+/// Clock ticks per second used by `times`.
+pub const CLOCKS_PER_SEC: usize = 100;
+
+/// CPU time of the calling thread in clock ticks, filled in by `times`.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Tms {
+    pub utime_ticks: usize,
+    pub stime_ticks: usize,
+}
+
+/// System-wide counters filled in by `sysinfo`.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SysInfo {
+    pub uptime_ms: usize,
+    /// time with every task blocked or sleeping
+    pub idle_time_ms: usize,
+    pub switches: usize,
+    pub tasks_created: usize,
+    /// rate of the clock behind `get_time`, in Hz
+    pub timebase_hz: usize,
+}
+
+/// Calls to one syscall id and the time spent in them.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SyscallLatency {
+    pub id: usize,
+    pub count: usize,
+    pub total_us: usize,
+}
+
+/// Fill `buf` with the per-syscall latency of the current thread, ordered by
+/// syscall id. Returns the number of entries filled.
+pub fn syscall_latency(buf: &mut [SyscallLatency]) -> isize {
+    sys_syscall_latency(buf)
+}
+
+/// Timer samples of the thread's PC in one `PROFILE_BUCKET_BYTES` range,
+/// filled in by `profile_stop`.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ProfileBucket {
+    /// lowest address of the range
+    pub pc: usize,
+    pub samples: usize,
+}
+
+/// Size of the PC ranges `profile_stop` reports.
+pub const PROFILE_BUCKET_BYTES: usize = 16;
+
+/// Start sampling this thread's PC every `interval_ms`, at most once per
+/// time slice. Restarting drops the samples taken so far.
+pub fn profile_start(interval_ms: usize) -> isize {
+    sys_profile_start(interval_ms)
+}
+
+/// Stop sampling and fill `buf` with the buckets with the most samples,
+/// most first. Returns how many were filled, or -1 if not sampling.
+pub fn profile_stop(buf: &mut [ProfileBucket]) -> isize {
+    sys_profile_stop(buf)
+}
+
+/// Largest `size` accepted by `membench`.
+pub const MEMBENCH_MAX_SIZE: usize = 0x10_0000;
+
+/// Time a kernel memcpy of `size` bytes, with interrupts masked. Returns the
+/// bandwidth in MB/s of the fastest of a few runs.
+pub fn membench(size: usize) -> isize {
+    sys_membench(size)
+}
+
+/// Slots filled in by `trap_stats`, one per trap cause.
+pub const TRAP_USER_ENV_CALL: usize = 0;
+pub const TRAP_INSTRUCTION_FAULT: usize = 1;
+pub const TRAP_INSTRUCTION_PAGE_FAULT: usize = 2;
+pub const TRAP_LOAD_FAULT: usize = 3;
+pub const TRAP_LOAD_PAGE_FAULT: usize = 4;
+pub const TRAP_STORE_FAULT: usize = 5;
+pub const TRAP_STORE_PAGE_FAULT: usize = 6;
+pub const TRAP_ILLEGAL_INSTRUCTION: usize = 7;
+pub const TRAP_SUPERVISOR_TIMER: usize = 8;
+pub const TRAP_SUPERVISOR_EXTERNAL: usize = 9;
+pub const TRAP_OTHER: usize = 10;
+pub const TRAP_SLOTS: usize = 11;
+
+/// Fill `buf` with the number of traps from user space since boot, indexed
+/// by the `TRAP_*` slots. Returns the number of slots filled.
+pub fn trap_stats(buf: &mut [usize]) -> isize {
+    sys_trap_stats(buf)
+}
+
+/// Recent scheduling decisions as text, one `<time>us <from> -> <to> (<reason>)`
+/// line per switch, oldest first. Returns the number of bytes written.
+pub fn sched_trace(buf: &mut [u8]) -> isize {
+    sys_sched_trace(buf)
+}
+
+pub fn getrusage(usage: &mut RUsage) -> isize {
+    sys_getrusage(usage)
+}
+
+pub fn times(buf: &mut Tms) -> isize {
+    sys_times(buf)
+}
+
+pub fn task_info(info: &mut TaskInfo) -> isize {
+    sys_task_info(info)
+}
+pub fn switch_count() -> isize {
+    sys_switch_count()
+}
+
+pub fn sysinfo(info: &mut SysInfo) -> isize {
+    sys_sysinfo(info)
+}
+
+/// Number of tasks (threads) created since boot.
+pub fn tasks_created() -> isize {
+    sys_tasks_created()
+}
+
+/// Set the kernel log level, 0 (off) to 5 (trace). Returns the previous level.
+pub fn set_log_level(level: usize) -> isize {
+    sys_set_log_level(level)
+}
+
+/// Have the kernel log each syscall of this thread, with its arguments and
+/// return value, as "[strace] ..." lines at info level.
+pub fn trace_syscalls(enable: bool) -> isize {
+    sys_trace_syscalls(enable as usize)
+}
+
+/// Read the most recent kernel log lines into `buf`. Returns the number of
+/// bytes read.
+pub fn dmesg(buf: &mut [u8]) -> isize {
+    sys_dmesg(buf)
+}
+
+/// Read the most recent lines apps wrote to the console into `buf`, as they
+/// came out. Returns the number of bytes read.
+pub fn stdout_log(buf: &mut [u8]) -> isize {
+    sys_stdout_log(buf)
+}
+
+/// Sleep until a signal arrives. All signals are fatal, so the caller is
+/// killed by it right after waking up.
+pub fn pause() -> isize {
+    sys_pause()
+}
+
+/// Buffers `reset_buffer` can empty.
+pub const BUFFER_DMESG: usize = 0;
+pub const BUFFER_SCHED_TRACE: usize = 1;
+pub const BUFFER_STDOUT_LOG: usize = 2;
+
+/// Empty a kernel debug buffer so later reads start from scratch.
+pub fn reset_buffer(which: usize) -> isize {
+    sys_reset_buffer(which)
+}
+
+/// Conditions `fault_inject` can trigger: a kernel read of an unmapped user
+/// pointer, one failed free frame check, a stale CPU time mark, and a kernel
+/// panic, which never returns.
+pub const FAULT_USER_READ: usize = 0;
+pub const FAULT_OUT_OF_FRAMES: usize = 1;
+pub const FAULT_STALE_METRIC: usize = 2;
+pub const FAULT_KERNEL_PANIC: usize = 3;
+
+/// Only available in kernels built with the `testing` feature; others kill
+/// the caller with SIGSYS.
+pub fn fault_inject(kind: usize) -> isize {
+    sys_fault_inject(kind)
+}
+
+/// Set the preemption quantum in microseconds. The kernel clamps it to
+/// [1ms, 1s] and returns the value actually used.
+pub fn set_timeslice(us: usize) -> isize {
+    sys_set_timeslice(us)
+}
+
+/// Round robin, the default scheduler.
+pub const SCHED_RR: usize = 0;
+/// Round robin that holds back the largest task under memory pressure.
+pub const SCHED_MEMORY_AWARE: usize = 1;
+/// The task that became ready last runs first.
+pub const SCHED_LIFO: usize = 2;
+/// The thread with the least recent CPU time runs first. Usage fades with a
+/// half-life of 100 ms.
+pub const SCHED_FAIR: usize = 3;
+
+/// Switch the kernel scheduler to one of the `SCHED_*` kinds. Returns the
+/// previous kind, or -1 if `kind` is unknown or this process has dropped its
+/// privilege.
+pub fn set_scheduler(kind: usize) -> isize {
+    sys_set_scheduler(kind)
+}
+
+/// Threads of this class are picked by the kernel scheduler, the default.
+pub const SCHED_POLICY_RR: usize = 0;
+/// Ready threads of this class run before all `SCHED_POLICY_RR` ones, the
+/// highest priority first.
+pub const SCHED_POLICY_PRIORITY: usize = 1;
+/// Ready threads of this class run first of all, the earliest deadline
+/// first; a thread's deadline is set `param` ms ahead whenever it gets ready.
+pub const SCHED_POLICY_EDF: usize = 2;
+
+/// Move thread `tid` of this process to one of the `SCHED_POLICY_*` classes,
+/// with `param` its priority or relative deadline in ms. Changing a thread
+/// other than the caller, or moving to a class other than `SCHED_POLICY_RR`,
+/// needs a privileged process, see `drop_privilege`. Returns
+/// `KernelError::Invalid` for an unknown policy, -1 for an unknown `tid` or
+/// if the change is not permitted.
+pub fn sched_setscheduler(tid: usize, policy: usize, param: usize) -> isize {
+    sys_sched_setscheduler(tid, policy, param)
+}
+
+/// Give up, for good, the privilege every process inherits from initproc
+/// until one of its ancestors drops it. Children started afterwards do not
+/// get it either.
+pub fn drop_privilege() -> isize {
+    sys_drop_privilege()
+}
+
+/// Fill `buf` with "<app name> <start>-<end>" of the current app image,
+/// truncated to fit. Returns the number of bytes written.
+pub fn current_app_info(buf: &mut [u8]) -> isize {
+    sys_current_app_info(buf)
+}